@@ -0,0 +1,35 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+pub const AUTHORITY_SEED: &[u8] = b"authority";
+
+//Counters handed over to this program have its authority address(from
+//`find_authority_address`) as their authority - which only this program signs for.
+//Every instruction takes the same accounts:
+//0. [writable] counter account
+//1. [] authority address of the counter
+//2. [] program config of the counter program
+//3. [] counter program
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub enum CallerInstruction {
+    //Take over the counter - its authority must have proposed the authority address
+    AcceptAuthority,
+    //Increament the counter as its authority
+    Increament,
+    //Set the counter back to its lower bound
+    Reset,
+}
+
+//Address(and bump seed) of the authority of `counter` of `counter_program`. The counter
+//program is part of the seeds - the program signs for the authority of counters of the
+//program it is handed only
+pub fn find_authority_address(
+    counter_program: &Pubkey,
+    counter: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[AUTHORITY_SEED, counter_program.as_ref(), counter.as_ref()],
+        program_id,
+    )
+}
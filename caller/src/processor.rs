@@ -0,0 +1,63 @@
+use crate::instruction::{find_authority_address, CallerInstruction, AUTHORITY_SEED};
+use borsh::BorshDeserialize;
+use program::cpi::{self, CounterAccounts};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub struct Processor {}
+
+impl Processor {
+    pub fn process_instruction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        let instruction = CallerInstruction::try_from_slice(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let config_acc_info = next_account_info(accounts_iter)?;
+        let counter_program_acc_info = next_account_info(accounts_iter)?;
+        let (authority, bump) = find_authority_address(
+            counter_program_acc_info.key,
+            counter_acc_info.key,
+            program_id,
+        );
+        if authority != *authority_acc_info.key {
+            msg!("Authority of counter {} expected", counter_acc_info.key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let accounts = CounterAccounts {
+            counter_program: counter_program_acc_info,
+            counter: counter_acc_info,
+            signer: authority_acc_info,
+            config: config_acc_info,
+        };
+        let seeds: &[&[u8]] = &[
+            AUTHORITY_SEED,
+            counter_program_acc_info.key.as_ref(),
+            counter_acc_info.key.as_ref(),
+            &[bump],
+        ];
+        match instruction {
+            CallerInstruction::AcceptAuthority => {
+                msg!("Taking over counter {}", counter_acc_info.key);
+                cpi::invoke_accept_authority(&accounts, &[seeds])
+            }
+            CallerInstruction::Increament => {
+                msg!("Increamenting counter {}", counter_acc_info.key);
+                cpi::invoke_increament(&accounts, &[seeds])
+            }
+            CallerInstruction::Reset => {
+                msg!("Resetting counter {}", counter_acc_info.key);
+                cpi::invoke_reset(&accounts, &[seeds])
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//Test harness running the example program and the counter program in a bank
+//(solana-program-test) - the runtime carries out the cross program invocations and
+//checks the signatures the example program lends its authority addresses
+#![allow(dead_code)]
+
+use borsh::BorshSerialize;
+use caller::instruction::{find_authority_address, CallerInstruction};
+use common::{Counter, ProgramConfig};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::Signer;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+pub struct TestBank {
+    pub context: ProgramTestContext,
+    pub caller_program_id: Pubkey,
+    pub counter_program_id: Pubkey,
+    pub counter: Pubkey,
+}
+
+impl TestBank {
+    //A bank with both programs and a counter - `counter` gets the authority address of
+    //the example program for it
+    pub async fn start(counter: impl FnOnce(&Pubkey) -> Counter) -> Self {
+        let (caller_program_id, counter_program_id, key) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut program_test = ProgramTest::new(
+            "caller",
+            caller_program_id,
+            processor!(caller::processor::Processor::process_instruction),
+        );
+        program_test.add_program(
+            "program",
+            counter_program_id,
+            processor!(program::processor::Processor::process_instruction),
+        );
+        let authority = find_authority_address(&counter_program_id, &key, &caller_program_id).0;
+        let mut data = counter(&authority).try_to_vec().unwrap();
+        data.resize(Counter::LEN, 0);
+        program_test.add_account(
+            key,
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: counter_program_id,
+                ..Account::default()
+            },
+        );
+        Self {
+            context: program_test.start_with_context().await,
+            caller_program_id,
+            counter_program_id,
+            counter: key,
+        }
+    }
+
+    //Authority address of `counter` of `counter_program` the example program signs for
+    pub fn authority_address(&self, counter_program: &Pubkey, counter: &Pubkey) -> Pubkey {
+        find_authority_address(counter_program, counter, &self.caller_program_id).0
+    }
+
+    //Example program instruction against the counter - with `authority` and
+    //`counter_program` handed in as given
+    pub fn instruction(
+        &self,
+        instruction: CallerInstruction,
+        authority: &Pubkey,
+        counter_program: &Pubkey,
+    ) -> Instruction {
+        Instruction::new_with_bytes(
+            self.caller_program_id,
+            &instruction.try_to_vec().unwrap(),
+            vec![
+                AccountMeta::new(self.counter, false),
+                AccountMeta::new_readonly(*authority, false),
+                AccountMeta::new_readonly(
+                    ProgramConfig::find_address(&self.counter_program_id).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(*counter_program, false),
+            ],
+        )
+    }
+
+    //Run the instruction in a transaction of its own, paid for by the context payer.
+    //Every transaction waits for a new blockhash, so that repeating one is not taken for
+    //a duplicate
+    pub async fn process(&mut self, instruction: Instruction) -> Result<(), TransactionError> {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.context.payer.pubkey()),
+            &[&self.context.payer],
+            blockhash,
+        );
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|err| err.unwrap())
+    }
+
+    pub async fn counter(&mut self) -> Counter {
+        let account = self
+            .context
+            .banks_client
+            .get_account(self.counter)
+            .await
+            .unwrap()
+            .unwrap();
+        Counter::unpack(&account.data).unwrap()
+    }
+}
+
+//What a transaction failing in its only instruction with `err` comes to
+pub fn failed_with(err: ProgramError) -> Result<(), TransactionError> {
+    Err(TransactionError::InstructionError(
+        0,
+        InstructionError::from(u64::from(err)),
+    ))
+}
@@ -0,0 +1,119 @@
+//Counters driven through cross program invocation by their program derived authority
+
+mod bank;
+
+use bank::{failed_with, TestBank};
+use borsh::BorshDeserialize;
+use caller::instruction::CallerInstruction;
+use common::{AccessMode, Counter, CounterError, CounterInstruction, ProgramConfig};
+use program::cpi;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+//A counter only its authority increaments - proposed to hand over to the example program
+async fn handed_over() -> TestBank {
+    TestBank::start(|authority| Counter {
+        access_mode: AccessMode::AuthorityOnly,
+        pending_authority: Some(*authority),
+        ..Counter::new(Pubkey::new_unique())
+    })
+    .await
+}
+
+#[tokio::test]
+async fn authority_address_increaments_through_cpi() {
+    let mut bank = handed_over().await;
+    let (counter_program_id, counter) = (bank.counter_program_id, bank.counter);
+    let authority = bank.authority_address(&counter_program_id, &counter);
+    let instruction = |instruction| bank.instruction(instruction, &authority, &counter_program_id);
+    let (accept, increament, reset) = (
+        instruction(CallerInstruction::AcceptAuthority),
+        instruction(CallerInstruction::Increament),
+        instruction(CallerInstruction::Reset),
+    );
+    //Not the authority yet
+    assert_eq!(
+        bank.process(increament.clone()).await,
+        failed_with(CounterError::IncreamentNotAllowed.into())
+    );
+    assert_eq!(bank.process(accept).await, Ok(()));
+    assert_eq!(bank.counter().await.authority, authority);
+    for _ in 0..2 {
+        assert_eq!(bank.process(increament.clone()).await, Ok(()));
+    }
+    assert_eq!(bank.counter().await.count, 2);
+    assert_eq!(bank.process(reset).await, Ok(()));
+    assert_eq!(bank.counter().await.count, 0);
+}
+
+#[tokio::test]
+async fn authority_address_is_per_counter() {
+    let mut bank = handed_over().await;
+    let (counter_program_id, counter) = (bank.counter_program_id, bank.counter);
+    let authority = bank.authority_address(&counter_program_id, &counter);
+    let accept = bank.instruction(
+        CallerInstruction::AcceptAuthority,
+        &authority,
+        &counter_program_id,
+    );
+    assert_eq!(bank.process(accept).await, Ok(()));
+    //The authority address of another counter
+    let other = bank.authority_address(&counter_program_id, &Pubkey::new_unique());
+    let increament = bank.instruction(CallerInstruction::Increament, &other, &counter_program_id);
+    assert_eq!(
+        bank.process(increament).await,
+        failed_with(ProgramError::InvalidSeeds)
+    );
+    assert_eq!(bank.counter().await.count, 0);
+}
+
+#[tokio::test]
+async fn authority_address_is_per_counter_program() {
+    let mut bank = handed_over().await;
+    let (counter_program_id, counter) = (bank.counter_program_id, bank.counter);
+    let authority = bank.authority_address(&counter_program_id, &counter);
+    let accept = bank.instruction(
+        CallerInstruction::AcceptAuthority,
+        &authority,
+        &counter_program_id,
+    );
+    assert_eq!(bank.process(accept).await, Ok(()));
+    //Another program handed in place of the counter program gets no signature for
+    //the counter authority
+    let increament = bank.instruction(
+        CallerInstruction::Increament,
+        &authority,
+        &bank.caller_program_id,
+    );
+    assert_eq!(
+        bank.process(increament).await,
+        failed_with(ProgramError::InvalidSeeds)
+    );
+    assert_eq!(bank.counter().await.count, 0);
+}
+
+#[test]
+fn builders_pass_the_config_last() {
+    let (program_id, counter, actor) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let instruction = cpi::increment_if_equals(&program_id, &counter, &actor, 7).unwrap();
+    assert_eq!(instruction.program_id, program_id);
+    let keys: Vec<_> = instruction
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect();
+    assert_eq!(
+        keys,
+        vec![counter, actor, ProgramConfig::find_address(&program_id).0]
+    );
+    assert!(instruction.accounts[0].is_writable);
+    assert!(instruction.accounts[1].is_signer);
+    assert!(matches!(
+        CounterInstruction::try_from_slice(&instruction.data).unwrap(),
+        CounterInstruction::IncrementIfEquals { expected: 7 }
+    ));
+}
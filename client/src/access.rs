@@ -0,0 +1,113 @@
+//Who may increament a counter - and how often
+//
+//A counter is open to anyone, restricted to its authority or restricted to its
+//authority and an allowlist of delegates. Each delegate is a small program derived
+//account(`Delegate`) naming the counter and the delegate - the authority pays its
+//rent and gets it back when the delegate is removed. On top of that a rate limit
+//can space increaments apart and cap them per window(in slots or seconds)
+use crate::client::Client;
+use crate::errors::Result;
+use borsh::BorshSerialize;
+use common::{AccessMode, AccountHeader, AccountType, CounterInstruction, Delegate, RateLimit};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+
+impl Client {
+    //Set the access mode of the counter derived from `owner`. `authority` signs for
+    //the counter authority - the payer if not given
+    pub fn set_access_mode(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        access_mode: AccessMode,
+    ) -> Result<()> {
+        let instruction = CounterInstruction::SetAccessMode { access_mode };
+        self.send_counter_instruction(&instruction, owner, authority)
+            .map(|_signature| ())
+            .map_err(|err| format!("Error setting counter access mode {}", err))
+    }
+
+    //Allow `delegate` to increament the counter derived from `owner` while the counter
+    //is in allowlist mode
+    pub fn add_delegate(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        delegate: &Pubkey,
+    ) -> Result<()> {
+        let instruction = CounterInstruction::AddDelegate {
+            delegate: *delegate,
+        };
+        self.send_counter_instruction(&instruction, owner, authority)
+            .map(|_signature| ())
+            .map_err(|err| format!("Error adding counter delegate {}", err))
+    }
+
+    pub fn remove_delegate(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        delegate: &Pubkey,
+    ) -> Result<()> {
+        let instruction = CounterInstruction::RemoveDelegate {
+            delegate: *delegate,
+        };
+        self.send_counter_instruction(&instruction, owner, authority)
+            .map(|_signature| ())
+            .map_err(|err| format!("Error removing counter delegate {}", err))
+    }
+
+    //Set the rate limit of the counter derived from `owner` - `RateLimit::default()`
+    //lifts it
+    pub fn set_rate_limit(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        rate_limit: RateLimit,
+    ) -> Result<()> {
+        let instruction = CounterInstruction::SetRateLimit { rate_limit };
+        self.send_counter_instruction(&instruction, owner, authority)
+            .map(|_signature| ())
+            .map_err(|err| format!("Error setting counter rate limit {}", err))
+    }
+
+    //Get the delegates of the counter - delegate records are looked up by their
+    //header and counter address
+    pub fn get_delegates(&self, counter_pubkey: &Pubkey) -> Result<Vec<Pubkey>> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let header = AccountHeader {
+            account_type: AccountType::Delegate,
+            version: Delegate::VERSION,
+        }
+        .try_to_vec()
+        .map_err(|err| format!("Error serializing delegate header {}", err))?;
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(Delegate::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, header)),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    AccountHeader::LEN,
+                    counter_pubkey.to_bytes().to_vec(),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self
+            .client
+            .get_program_accounts_with_config(&program_id, config)
+            .map_err(|err| format!("Error retrieving delegate accounts {}", err))?;
+        Ok(accounts
+            .into_iter()
+            .filter_map(|(_pubkey, account)| Delegate::unpack(&account.data).ok())
+            .map(|record| record.delegate)
+            .collect())
+    }
+}
@@ -0,0 +1,76 @@
+//Program wide pause switch
+//
+//The program config account(at a program derived address) holds an admin and a
+//paused flag. While paused the program rejects every instruction that changes
+//counters. Only the upgrade authority of the program can create the config - it
+//names the admin, who pauses, unpauses and hands over to a new admin in two steps
+use crate::client::Client;
+use crate::errors::Result;
+use common::{CounterInstruction, ProgramConfig};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use solana_sdk::signer::keypair::Keypair;
+
+impl Client {
+    //Create the program config with `admin` - the payer must be the upgrade authority
+    //of the program and pays the rent
+    pub fn initialize_config(&self, admin: &Pubkey) -> Result<()> {
+        self.send_config_instruction(
+            &CounterInstruction::InitializeConfig { admin: *admin },
+            None,
+        )
+        .map_err(|err| format!("Error initializing program config {}", err))
+    }
+
+    //Pause or unpause the program. `admin` signs for the admin - the payer if not given
+    pub fn set_paused(&self, admin: Option<&Keypair>, paused: bool) -> Result<()> {
+        let instruction = match paused {
+            true => CounterInstruction::Pause,
+            false => CounterInstruction::Unpause,
+        };
+        self.send_config_instruction(&instruction, admin)
+            .map_err(|err| format!("Error pausing or unpausing the program {}", err))
+    }
+
+    //Propose `new_admin` - it takes over once it accepts
+    pub fn propose_admin(&self, admin: Option<&Keypair>, new_admin: &Pubkey) -> Result<()> {
+        let instruction = CounterInstruction::ProposeAdmin {
+            new_admin: *new_admin,
+        };
+        self.send_config_instruction(&instruction, admin)
+            .map_err(|err| format!("Error proposing admin {}", err))
+    }
+
+    //Take over as the admin - the signer must be the pending admin
+    pub fn accept_admin(&self, new_admin: &Keypair) -> Result<()> {
+        self.send_config_instruction(&CounterInstruction::AcceptAdmin, Some(new_admin))
+            .map_err(|err| format!("Error accepting admin {}", err))
+    }
+
+    //Get the program config - None if it has not been created
+    pub fn get_program_config(&self) -> Result<Option<ProgramConfig>> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let config_pubkey = ProgramConfig::find_address(&program_id).0;
+        let account = match self.client.get_account(&config_pubkey) {
+            Ok(account) if account.owner == program_id => account,
+            _ => return Ok(None),
+        };
+        ProgramConfig::unpack(&account.data)
+            .map(Some)
+            .map_err(|err| format!("Error deserializing bytes to program config {}", err))
+    }
+
+    fn send_config_instruction(
+        &self,
+        counter_instruction: &CounterInstruction,
+        admin: Option<&Keypair>,
+    ) -> Result<()> {
+        let payer_pubkey = Self::get_payer_keypair()
+            .ok_or("Payer keypair not found")?
+            .pubkey();
+        let transaction = self.counter_transaction(counter_instruction, &payer_pubkey, admin)?;
+        self.process_transaction(&transaction, None)
+            .map(|_signature| ())
+    }
+}
@@ -0,0 +1,66 @@
+//Two step counter authority transfer
+//
+//The counter authority proposes a new authority, which takes over only once it signs
+//an accept - so a mistyped key can not lock the counter up. The proposal can be
+//cancelled until it is accepted
+use crate::client::Client;
+use crate::errors::Result;
+use common::{Counter, CounterInstruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+
+impl Client {
+    //Propose `new_authority` for the counter derived from `owner`. `authority` signs
+    //for the current authority - the payer if not given
+    pub fn propose_authority(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        new_authority: &Pubkey,
+    ) -> Result<()> {
+        let instruction = CounterInstruction::ProposeAuthority {
+            new_authority: *new_authority,
+        };
+        self.send_counter_instruction(&instruction, owner, authority)
+            .map(|_signature| ())
+            .map_err(|err| format!("Error proposing counter authority {}", err))
+    }
+
+    //Take over as the authority of the counter derived from `owner` - the signer must
+    //be the pending authority
+    pub fn accept_authority(&self, owner: &Pubkey, new_authority: &Keypair) -> Result<()> {
+        self.send_counter_instruction(
+            &CounterInstruction::AcceptAuthority,
+            owner,
+            Some(new_authority),
+        )
+        .map(|_signature| ())
+        .map_err(|err| format!("Error accepting counter authority {}", err))
+    }
+
+    //Withdraw the pending authority proposal of the counter derived from `owner`
+    pub fn cancel_authority_transfer(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+    ) -> Result<()> {
+        self.send_counter_instruction(
+            &CounterInstruction::CancelAuthorityTransfer,
+            owner,
+            authority,
+        )
+        .map(|_signature| ())
+        .map_err(|err| format!("Error cancelling counter authority transfer {}", err))
+    }
+
+    //Get the counter(authority and pending authority included) stored at the address.
+    //Counters in older layouts have to be migrated first
+    pub fn get_counter(&self, counter_pubkey: &Pubkey) -> Result<Counter> {
+        let account = self
+            .client
+            .get_account(counter_pubkey)
+            .map_err(|err| format!("Error retrieving counter account {}", err))?;
+        Counter::unpack(&account.data)
+            .map_err(|err| format!("Error deserializing bytes to counter {}", err))
+    }
+}
@@ -0,0 +1,196 @@
+//Batching of counter operations
+//
+//A message can carry many instructions - so instead of a transaction per counter
+//operation, as many operations as fit under the transaction size limit are packed
+//into each message and the resulting transactions are sent in parallel
+
+use crate::client::Client;
+use crate::errors::Result;
+use common::CounterInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Signature, Signer};
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::transaction::Transaction;
+use std::ops::Range;
+use std::thread;
+
+//A counter instruction against the counter owned by `owner`
+#[derive(Debug, Clone)]
+pub struct CounterOperation {
+    pub instruction: CounterInstruction,
+    pub owner: Pubkey,
+}
+
+//Outcome of one batch - `operations` are the indices of the operations it carried
+#[derive(Debug)]
+pub struct BatchResult {
+    pub operations: Range<usize>,
+    pub signature: Signature,
+    pub result: Result<()>,
+}
+
+impl Client {
+    //Pack the operations(in order) into as few transactions as possible, send them in
+    //parallel and report the result of each. Authority gated operations(reset, close)
+    //are signed by the counter owner - the authority a counter starts out with - who
+    //must be among `signers`. The payer always signs. Batches use a recent blockhash - a
+    //nonce account can only be used by one transaction at a time
+    pub fn execute_batch(
+        &self,
+        operations: &[CounterOperation],
+        signers: &[&Keypair],
+    ) -> Result<Vec<BatchResult>> {
+        let payer = Self::get_payer_keypair().ok_or("Payer keypair not found")?;
+        let payer_pubkey = payer.pubkey();
+        let blockhash = self
+            .client
+            .get_latest_blockhash()
+            .map_err(|err| format!("Error retrieving latest block hash {}", err))?;
+        let mut prerequisites: Vec<Instruction> = vec![];
+        let instructions = operations
+            .iter()
+            .map(|operation| {
+                let actor = match operation.instruction {
+                    CounterInstruction::Increament
+                    | CounterInstruction::IncrementIfEquals { .. } => &payer_pubkey,
+                    _ => &operation.owner,
+                };
+                let mut instruction =
+                    Self::counter_instruction(&operation.instruction, &operation.owner, actor)?;
+                for prerequisite in self.add_increament_accounts(
+                    &operation.instruction,
+                    &mut instruction,
+                    actor,
+                    &payer_pubkey,
+                )? {
+                    if !prerequisites.contains(&prerequisite) {
+                        prerequisites.push(prerequisite);
+                    }
+                }
+                Ok(instruction)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        //Reward token accounts the increaments need are created ahead of the batches -
+        //which may land in any order
+        if !prerequisites.is_empty() {
+            let message =
+                Message::new_with_blockhash(&prerequisites, Some(&payer_pubkey), &blockhash);
+            let transaction = Transaction::new(&[&payer], message, blockhash);
+            self.process_transaction(&transaction, None)
+                .map_err(|err| format!("Error creating reward token accounts {}", err))?;
+        }
+        let batches = Self::pack_instructions(&instructions, &payer_pubkey)?;
+        println!(
+            "{} counter operations packed into {} transactions",
+            operations.len(),
+            batches.len()
+        );
+
+        let mut all_signers = vec![&payer];
+        all_signers.extend_from_slice(signers);
+        let mut transactions = Vec::with_capacity(batches.len());
+        let mut total_fee = 0;
+        for range in &batches {
+            let message = Message::new_with_blockhash(
+                &instructions[range.clone()],
+                Some(&payer_pubkey),
+                &blockhash,
+            );
+            total_fee += self
+                .client
+                .get_fee_for_message(&message)
+                .map_err(|err| format!("Failed getting fee for message {}", err))?;
+            //Only the keypairs this message needs may sign it
+            let signer_keys = message.signer_keys();
+            let batch_signers: Vec<&Keypair> = all_signers
+                .iter()
+                .copied()
+                .filter(|signer| signer_keys.contains(&&signer.pubkey()))
+                .collect();
+            let mut transaction = Transaction::new_unsigned(message);
+            transaction
+                .try_sign(&batch_signers, blockhash)
+                .map_err(|err| format!("Error signing operations {:?} {}", range, err))?;
+            transactions.push(transaction);
+        }
+        self.request_airdrop(total_fee)
+            .map_err(|err| format!("Error during airdrop {}", err))?;
+
+        let results = thread::scope(|scope| {
+            let handles: Vec<_> = transactions
+                .iter()
+                .map(|transaction| {
+                    scope.spawn(move || {
+                        //Counter account of the first operation - only used in dry run mode
+                        let counter_pubkey = transaction
+                            .message
+                            .account_keys
+                            .get(transaction.message.instructions[0].accounts[0] as usize);
+                        self.process_transaction(transaction, counter_pubkey)
+                            .map(|_signature| ())
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err("Batch sender panicked".to_string()))
+                })
+                .collect::<Vec<_>>()
+        });
+        Ok(batches
+            .into_iter()
+            .zip(transactions)
+            .zip(results)
+            .map(|((operations, transaction), result)| BatchResult {
+                operations,
+                signature: transaction.signatures[0],
+                result,
+            })
+            .collect())
+    }
+
+    //Split the instructions into consecutive runs, each of which fits in a single
+    //transaction
+    pub fn pack_instructions(
+        instructions: &[Instruction],
+        payer_pubkey: &Pubkey,
+    ) -> Result<Vec<Range<usize>>> {
+        let mut batches = Vec::new();
+        let mut start = 0;
+        while start < instructions.len() {
+            let mut end = start + 1;
+            if !Self::fits_in_transaction(&instructions[start..end], payer_pubkey)? {
+                return Err(format!(
+                    "Counter operation {} does not fit in a transaction",
+                    start
+                ));
+            }
+            while end < instructions.len()
+                && Self::fits_in_transaction(&instructions[start..end + 1], payer_pubkey)?
+            {
+                end += 1;
+            }
+            batches.push(start..end);
+            start = end;
+        }
+        Ok(batches)
+    }
+
+    //Whether a signed transaction carrying the instructions is within the packet size
+    pub fn fits_in_transaction(
+        instructions: &[Instruction],
+        payer_pubkey: &Pubkey,
+    ) -> Result<bool> {
+        //Unsigned transaction already has room for the signatures it needs
+        let transaction = Transaction::new_unsigned(Message::new(instructions, Some(payer_pubkey)));
+        let size = bincode::serialized_size(&transaction)
+            .map_err(|err| format!("Error serializing transaction {}", err))?;
+        Ok(size as usize <= PACKET_DATA_SIZE)
+    }
+}
@@ -1,30 +1,130 @@
 use crate::config;
 use crate::errors::Result;
-use borsh::BorshDeserialize;
+use crate::events;
+use crate::nonce::nonce_message;
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::Bounds;
 use common::Counter;
+use common::CounterError;
+use common::CounterEvent;
 use common::CounterInstruction;
+use common::Delegate;
+use common::ProgramConfig;
+use common::VersionedCounter;
+use common::COUNTER_ACCOUNT_SEED;
+use common::{
+    Contribution, History, Leaderboard, Milestones, RedeemedVoucher, Snapshot, TimeSeries,
+};
+use solana_account_decoder::UiAccountEncoding;
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig, RpcTransactionConfig,
+};
 use solana_sdk::account::Account;
+use solana_sdk::bpf_loader_upgradeable;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::instruction::AccountMeta;
 use solana_sdk::instruction::Instruction;
+use solana_sdk::instruction::InstructionError;
 use solana_sdk::message::Message;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::Signer;
+use solana_sdk::signature::{Signature, Signer};
 use solana_sdk::signer::keypair::write_keypair_file;
 use solana_sdk::signer::keypair::Keypair;
 use solana_sdk::system_instruction;
-use solana_sdk::transaction::Transaction;
+use solana_sdk::system_program;
+use solana_sdk::sysvar;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_transaction_status::{
+    UiTransactionEncoding, UiTransactionReturnData, UiTransactionStatusMeta,
+};
 use std::path::Path;
 use std::str::FromStr;
 
-static COUNTER_ACCOUNT_SEED: &str = "COUNTER";
-static COUNTER_ACCOUNT_DATA_SIZE: usize = std::mem::size_of::<Counter>(); //bytes
 static PROGRAM_PATH: &str = "./target/deploy/program.so";
 static PROGRAM_KEYPAIR: &str = "./target/deploy/program-keypair.json";
 
 pub struct Client {
-    client: RpcClient,
+    pub(crate) client: RpcClient,
+    //When set, state changing transactions are simulated - never broadcast
+    pub(crate) dry_run: bool,
+    //When set, transactions use the durable nonce in this account instead of a
+    //recent blockhash
+    pub(crate) nonce_pubkey: Option<Pubkey>,
+}
+
+//Outcome of a simulated(dry run) transaction
+#[derive(Debug)]
+pub struct Simulation {
+    pub err: Option<TransactionError>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    //Events the counter program would emit
+    pub events: Vec<CounterEvent>,
+    //Counter account state as it would be after the transaction
+    pub counter: Option<Counter>,
+    //Counter state handed back by the program as return data
+    pub returned_counter: Option<Counter>,
+}
+
+//Decode return data into a counter - provided it was set by the counter program. Return
+//data of the counter program that is no counter is an error
+pub fn decode_return_data(
+    return_data: Option<UiTransactionReturnData>,
+    program_id: &Pubkey,
+) -> Result<Option<Counter>> {
+    let return_data = match return_data {
+        Some(return_data) if return_data.program_id == program_id.to_string() => return_data,
+        _ => return Ok(None),
+    };
+    //Return data is always base64 encoded
+    let bytes = base64::decode(return_data.data.0)
+        .map_err(|err| format!("Error decoding return data {}", err))?;
+    Counter::try_from_slice(&bytes)
+        .map(Some)
+        .map_err(|err| format!("Error deserializing return data to counter {}", err))
+}
+
+impl Simulation {
+    //What a dry run prints out - the program logs and compute units, followed by the
+    //events and counter state unless the simulation failed. `counter_requested` tells
+    //whether the counter account state was asked for
+    pub fn report(&self, counter_requested: bool) -> Vec<String> {
+        let mut lines = vec!["Program logs:".to_string()];
+        lines.extend(self.logs.iter().map(|log| format!("  {}", log)));
+        lines.push(match self.units_consumed {
+            Some(units) => format!("Compute units consumed {}", units),
+            None => "Compute units consumed not reported by the cluster".to_string(),
+        });
+        if self.err.is_some() {
+            return lines;
+        }
+        lines.extend(self.events.iter().map(|event| format!("Event {:?}", event)));
+        match (counter_requested, &self.counter) {
+            (true, Some(counter)) => {
+                lines.push(format!("Counter value after transaction {}", counter.count))
+            }
+            (true, None) => {
+                lines.push("Counter account state not returned by simulation".to_string())
+            }
+            (false, _) => {}
+        }
+        if let Some(ref counter) = self.returned_counter {
+            lines.push(format!(
+                "Counter value returned by the program {}",
+                counter.count
+            ));
+        }
+        lines
+    }
+
+    //Why the simulation failed - naming the counter program error if that is what failed
+    pub fn error(&self) -> Option<String> {
+        self.err.as_ref().map(|err| match counter_error(err) {
+            Some(counter_err) => format!("Simulation failed {} - {}", counter_err, err),
+            None => format!("Simulation failed {}", err),
+        })
+    }
 }
 
 impl Default for Client {
@@ -37,9 +137,25 @@ impl Client {
     pub fn new() -> Self {
         Self {
             client: Self::get_rpc_client(),
+            dry_run: false,
+            nonce_pubkey: None,
         }
     }
 
+    //Simulate state changing operations(account setup, increament) instead of
+    //sending them to the cluster
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    //Use the durable nonce stored in the nonce account for all transactions so that
+    //they do not expire. The payer must be the nonce authority
+    pub fn with_nonce_account(mut self, nonce_pubkey: Option<Pubkey>) -> Self {
+        self.nonce_pubkey = nonce_pubkey;
+        self
+    }
+
     //Get a handle to rpc client for connecting to solana network
     fn get_rpc_client() -> RpcClient {
         let network = match config::get_config("json_rpc_url") {
@@ -127,11 +243,282 @@ impl Client {
         }
     }
 
+    //Derive the address of the counter account owned by any base key - not just the payer
+    pub fn get_counter_pubkey_for(owner: &Pubkey) -> Result<Pubkey> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        Pubkey::create_with_seed(owner, COUNTER_ACCOUNT_SEED, &program_id)
+            .map_err(|err| format!("Error creating counter account key {}", err))
+    }
+
+    //Build a counter program instruction along with the accounts it expects. `owner`
+    //is the base key the counter account address is derived from, `actor` signs the
+    //instruction - the counter authority for authority gated instructions, the admin
+    //for program config instructions
+    pub fn counter_instruction(
+        counter_instruction: &CounterInstruction,
+        owner: &Pubkey,
+        actor: &Pubkey,
+    ) -> Result<Instruction> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let counter_pubkey = Self::get_counter_pubkey_for(owner)?;
+        let config_pubkey = ProgramConfig::find_address(&program_id).0;
+        let treasury_pubkey = Counter::find_treasury_address(&counter_pubkey, &program_id).0;
+        let mut accounts = match counter_instruction {
+            //Delegate record is only looked at for counters in allowlist mode, the actor
+            //pays the fee of counters charging one, its contribution is tracked and ranked,
+            //the increament counted in the time series
+            CounterInstruction::Increament | CounterInstruction::IncrementIfEquals { .. } => vec![
+                AccountMeta::new(counter_pubkey, false),
+                AccountMeta::new_readonly(*actor, true),
+                AccountMeta::new_readonly(
+                    Delegate::find_address(&counter_pubkey, actor, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new(*actor, true),
+                AccountMeta::new(treasury_pubkey, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(
+                    Contribution::find_address(&counter_pubkey, actor, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new(
+                    Leaderboard::find_address(&counter_pubkey, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new(
+                    TimeSeries::find_address(&counter_pubkey, &program_id).0,
+                    false,
+                ),
+            ],
+            CounterInstruction::Reset
+            | CounterInstruction::ProposeAuthority { .. }
+            | CounterInstruction::AcceptAuthority
+            | CounterInstruction::CancelAuthorityTransfer
+            | CounterInstruction::SetAccessMode { .. }
+            | CounterInstruction::SetRateLimit { .. }
+            | CounterInstruction::SetTokenGate { .. }
+            | CounterInstruction::CompareAndSet { .. } => vec![
+                AccountMeta::new(counter_pubkey, false),
+                AccountMeta::new_readonly(*actor, true),
+            ],
+            //Lamports of the closed account go back to the authority
+            CounterInstruction::Close => vec![
+                AccountMeta::new(counter_pubkey, false),
+                AccountMeta::new_readonly(*actor, true),
+                AccountMeta::new(*actor, false),
+            ],
+            CounterInstruction::Initialize {
+                fee: 0,
+                history_capacity: 0,
+                ..
+            } => vec![
+                AccountMeta::new(counter_pubkey, false),
+                AccountMeta::new_readonly(*actor, true),
+            ],
+            //Actor funds the treasury and the history
+            CounterInstruction::Initialize { .. } => vec![
+                AccountMeta::new(counter_pubkey, false),
+                AccountMeta::new(*actor, true),
+                AccountMeta::new(treasury_pubkey, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            //Fees go to the authority
+            CounterInstruction::WithdrawFees { .. } => vec![
+                AccountMeta::new_readonly(counter_pubkey, false),
+                AccountMeta::new_readonly(*actor, true),
+                AccountMeta::new(treasury_pubkey, false),
+                AccountMeta::new(*actor, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            //Authority pays the rent for the delegate record and gets it back on removal
+            CounterInstruction::AddDelegate { delegate } => vec![
+                AccountMeta::new_readonly(counter_pubkey, false),
+                AccountMeta::new_readonly(*actor, true),
+                AccountMeta::new(*actor, true),
+                AccountMeta::new(
+                    Delegate::find_address(&counter_pubkey, delegate, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            //Authority pays the rent for the leaderboard
+            CounterInstruction::CreateLeaderboard { .. } => vec![
+                AccountMeta::new_readonly(counter_pubkey, false),
+                AccountMeta::new_readonly(*actor, true),
+                AccountMeta::new(*actor, true),
+                AccountMeta::new(
+                    Leaderboard::find_address(&counter_pubkey, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            //Authority pays the rent for the time series
+            CounterInstruction::CreateTimeSeries { .. } => vec![
+                AccountMeta::new_readonly(counter_pubkey, false),
+                AccountMeta::new_readonly(*actor, true),
+                AccountMeta::new(*actor, true),
+                AccountMeta::new(
+                    TimeSeries::find_address(&counter_pubkey, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            //Authority pays the rent for the snapshot
+            CounterInstruction::Snapshot { label } => vec![
+                AccountMeta::new_readonly(counter_pubkey, false),
+                AccountMeta::new_readonly(*actor, true),
+                AccountMeta::new(*actor, true),
+                AccountMeta::new(
+                    Snapshot::find_address(&counter_pubkey, label, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            //Authority pays the rent for the milestones
+            CounterInstruction::CreateMilestones { mint, .. } => vec![
+                AccountMeta::new_readonly(counter_pubkey, false),
+                AccountMeta::new_readonly(*actor, true),
+                AccountMeta::new(*actor, true),
+                AccountMeta::new(
+                    Milestones::find_address(&counter_pubkey, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(*mint, false),
+            ],
+            //Actor redeems - paying for the redeemed voucher record, its contribution
+            //and any fee, it is tracked and ranked, the redeemed amount counted in the
+            //time series
+            CounterInstruction::RedeemVoucher { nonce, .. } => vec![
+                AccountMeta::new(counter_pubkey, false),
+                AccountMeta::new(*actor, true),
+                AccountMeta::new(
+                    RedeemedVoucher::find_address(&counter_pubkey, *nonce, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(sysvar::instructions::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(treasury_pubkey, false),
+                AccountMeta::new(
+                    Contribution::find_address(&counter_pubkey, actor, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new(
+                    Leaderboard::find_address(&counter_pubkey, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new(
+                    TimeSeries::find_address(&counter_pubkey, &program_id).0,
+                    false,
+                ),
+            ],
+            CounterInstruction::RemoveDelegate { delegate } => vec![
+                AccountMeta::new_readonly(counter_pubkey, false),
+                AccountMeta::new_readonly(*actor, true),
+                AccountMeta::new(
+                    Delegate::find_address(&counter_pubkey, delegate, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new(*actor, false),
+            ],
+            CounterInstruction::InitializeMultisig { .. } => {
+                return Err("Multisig accounts are set up with `create_multisig`".to_string())
+            }
+            //Actor pays for the extra rent
+            CounterInstruction::Migrate => vec![
+                AccountMeta::new(counter_pubkey, false),
+                AccountMeta::new(*actor, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            //Actor must be the upgrade authority of the program
+            CounterInstruction::InitializeConfig { .. } => vec![
+                AccountMeta::new(config_pubkey, false),
+                AccountMeta::new(*actor, true),
+                AccountMeta::new_readonly(
+                    Pubkey::find_program_address(
+                        &[program_id.as_ref()],
+                        &bpf_loader_upgradeable::id(),
+                    )
+                    .0,
+                    false,
+                ),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            CounterInstruction::Pause
+            | CounterInstruction::Unpause
+            | CounterInstruction::ProposeAdmin { .. }
+            | CounterInstruction::AcceptAdmin => vec![
+                AccountMeta::new(config_pubkey, false),
+                AccountMeta::new_readonly(*actor, true),
+            ],
+        };
+        if !counter_instruction.is_config_instruction() {
+            accounts.push(AccountMeta::new_readonly(config_pubkey, false));
+        }
+        let data = counter_instruction
+            .try_to_vec()
+            .map_err(|err| format!("Error serializing counter instruction {}", err))?;
+        Ok(Instruction::new_with_bytes(program_id, &data, accounts))
+    }
+
+    //Send a single counter instruction against the counter derived from `owner`. The
+    //payer pays the fees and `actor`(the payer if not given) signs the instruction
+    pub fn send_counter_instruction(
+        &self,
+        counter_instruction: &CounterInstruction,
+        owner: &Pubkey,
+        actor: Option<&Keypair>,
+    ) -> Result<Option<Signature>> {
+        let counter_pubkey = Self::get_counter_pubkey_for(owner)?;
+        let transaction = self.counter_transaction(counter_instruction, owner, actor)?;
+        self.process_transaction(&transaction, Some(&counter_pubkey))
+    }
+
+    //Build and sign the transaction `send_counter_instruction` sends
+    pub(crate) fn counter_transaction(
+        &self,
+        counter_instruction: &CounterInstruction,
+        owner: &Pubkey,
+        actor: Option<&Keypair>,
+    ) -> Result<Transaction> {
+        let payer = Self::get_payer_keypair().ok_or("Payer keypair not found")?;
+        let payer_pubkey = payer.pubkey();
+        let actor = actor.unwrap_or(&payer);
+        let mut instruction =
+            Self::counter_instruction(counter_instruction, owner, &actor.pubkey())?;
+        let mut instructions = self.add_increament_accounts(
+            counter_instruction,
+            &mut instruction,
+            &actor.pubkey(),
+            &payer_pubkey,
+        )?;
+        instructions.push(instruction);
+        let message = self.compose_message(&instructions, &payer_pubkey)?;
+        let fee_for_message = self
+            .client
+            .get_fee_for_message(&message)
+            .map_err(|err| format!("Failed getting fee for message {}", err))?;
+        self.request_airdrop(fee_for_message)
+            .map_err(|err| format!("Error during airdrop {}", err))?;
+        let blockhash = message.recent_blockhash;
+        let mut transaction = Transaction::new_unsigned(message);
+        let signers = match actor.pubkey() == payer_pubkey {
+            true => vec![&payer],
+            false => vec![&payer, actor],
+        };
+        transaction
+            .try_sign(&signers, blockhash)
+            .map_err(|err| format!("Error signing transaction {}", err))?;
+        Ok(transaction)
+    }
+
     //Request airdrop for executing transaction if account balance is not sufficient
     //To skip airdrop(for experimentation) pass in the environment variable skip_airdrop to
-    //some value
+    //some value. Airdrops are always skipped in dry run mode
     pub fn request_airdrop(&self, txn_amt: u64) -> Result<()> {
-        if std::env::var("skip_airdrop").ok().is_some() {
+        if self.dry_run || std::env::var("skip_airdrop").ok().is_some() {
             return Ok(());
         }
         let balance = self.get_payer_account_balance()?;
@@ -165,8 +552,32 @@ impl Client {
      * the greeting account based on its size. Also takes into account the cost of invoking
      * the create account transaction
      */
-
     pub fn setup_counter_account(&self) -> Result<()> {
+        self.setup_bounded_counter_account(Bounds::default())
+    }
+
+    //Setup the counter account kept within the bounds - they only take effect if the
+    //account is created now, the bounds of an existing counter can not be changed
+    pub fn setup_bounded_counter_account(&self, bounds: Bounds) -> Result<()> {
+        self.setup_paid_counter_account(bounds, 0)
+    }
+
+    //Setup the counter account charging `fee` lamports per increament into its treasury
+    //- like the bounds, the fee is only set if the account is created now. The payer
+    //funds the rent exempt minimum of the treasury
+    pub fn setup_paid_counter_account(&self, bounds: Bounds, fee: u64) -> Result<()> {
+        self.setup_counter_account_with_history(bounds, fee, 0)
+    }
+
+    //Setup the counter account keeping its last `history_capacity` changes - set, like
+    //the bounds and the fee, only if the account is created now. The program grows the
+    //account to hold them, the payer covers the extra rent
+    pub fn setup_counter_account_with_history(
+        &self,
+        bounds: Bounds,
+        fee: u64,
+        history_capacity: u16,
+    ) -> Result<()> {
         let payer = Self::get_payer_keypair().ok_or("Payer keypair not found")?;
         let payer_pubkey = payer.pubkey();
         let program_id = Self::get_program_id()
@@ -181,57 +592,118 @@ impl Client {
                     "Counter account {} already exists. Owner program: {:?}",
                     counter_pubkey, account.owner
                 );
-                Ok(())
+                //Accounts written by older program versions are brought to the current layout
+                match VersionedCounter::unpack(&account.data) {
+                    Ok(versioned) if versioned.version() != Counter::VERSION => {
+                        println!(
+                            "Counter account layout version {} is outdated - migrating",
+                            versioned.version()
+                        );
+                        self.migrate_counter()
+                    }
+                    _ => Ok(()),
+                }
             }
             Err(err) => {
                 eprintln!("Counter account does not exist {}. Would create", err);
                 let freestay_lamports = self
                     .client
-                    .get_minimum_balance_for_rent_exemption(COUNTER_ACCOUNT_DATA_SIZE)
+                    .get_minimum_balance_for_rent_exemption(Counter::LEN)
                     .map_err(|err| {
                         format!("Error getting Minimum balance for rent exemption {}", err)
                     })?;
                 println!("Freestay lamports : {}", freestay_lamports);
                 let instruction = system_instruction::create_account_with_seed(
-                    &payer_pubkey,                    //from_keypair
-                    &counter_pubkey,                  //to_keypair
-                    &payer_pubkey,                    //base
-                    COUNTER_ACCOUNT_SEED,             //seed
-                    freestay_lamports,                //lamports
-                    COUNTER_ACCOUNT_DATA_SIZE as u64, //space
-                    &program_id,                      //owner
+                    &payer_pubkey,        //from_keypair
+                    &counter_pubkey,      //to_keypair
+                    &payer_pubkey,        //base
+                    COUNTER_ACCOUNT_SEED, //seed
+                    freestay_lamports,    //lamports
+                    Counter::LEN as u64,  //space
+                    &program_id,          //owner
                 );
-                //Query latest block hash
-                let blockhash = self
-                    .client
-                    .get_latest_blockhash()
-                    .map_err(|s| format!("Error retrieving Latest block hash {}", s))?;
-
-                let message =
-                    Message::new_with_blockhash(&[instruction], Some(&payer_pubkey), &blockhash);
+                //Account header is written in the same transaction
+                let initialize = Self::counter_instruction(
+                    &CounterInstruction::Initialize {
+                        bounds,
+                        fee,
+                        history_capacity,
+                    },
+                    &payer_pubkey,
+                    &payer_pubkey,
+                )?;
+                let message = self.compose_message(&[instruction, initialize], &payer_pubkey)?;
                 //Check lamports needed to send this message
                 let fee_for_message = self
                     .client
                     .get_fee_for_message(&message)
                     .map_err(|_| "Error getting fee for message")?;
                 println!("Fee for message {}", fee_for_message);
-                let total_amt = fee_for_message + freestay_lamports;
+                let treasury_lamports = match fee {
+                    0 => 0,
+                    _ => self
+                        .client
+                        .get_minimum_balance_for_rent_exemption(0)
+                        .map_err(|err| {
+                            format!("Error getting Minimum balance for rent exemption {}", err)
+                        })?,
+                };
+                let history_lamports = match history_capacity {
+                    0 => 0,
+                    _ => self
+                        .client
+                        .get_minimum_balance_for_rent_exemption(
+                            Counter::LEN + History::space(history_capacity),
+                        )
+                        .map_err(|err| {
+                            format!("Error getting Minimum balance for rent exemption {}", err)
+                        })?
+                        .saturating_sub(freestay_lamports),
+                };
+                let total_amt =
+                    fee_for_message + freestay_lamports + treasury_lamports + history_lamports;
                 println!("Total amount for transaction {} lamports", total_amt);
                 //Request airdrop if needed
                 self.request_airdrop(total_amt).map_err(|s| {
                     format!("Airdrop failed while setting up counter account {}", s)
                 })?;
 
+                let blockhash = message.recent_blockhash;
                 let transaction = Transaction::new(&[&payer], message, blockhash);
-                let _signature = self
-                    .client
-                    .send_and_confirm_transaction(&transaction)
-                    .map_err(|err| format!("Error sending account setup transaction {}", err))?;
-                Ok(())
+                self.process_transaction(&transaction, Some(&counter_pubkey))
+                    .map(|_signature| ())
+                    .map_err(|err| format!("Error sending account setup transaction {}", err))
             }
         }
     }
 
+    //Bring the payer's counter account to the current layout. The payer covers the
+    //rent for any extra space and becomes the authority if the old layout had none
+    pub fn migrate_counter(&self) -> Result<()> {
+        let payer = Self::get_payer_keypair().ok_or("Payer keypair not found")?;
+        let payer_pubkey = payer.pubkey();
+        let counter_pubkey = Self::get_counter_pubkey();
+        let instruction =
+            Self::counter_instruction(&CounterInstruction::Migrate, &payer_pubkey, &payer_pubkey)?;
+        let message = self.compose_message(&[instruction], &payer_pubkey)?;
+        let fee_for_message = self
+            .client
+            .get_fee_for_message(&message)
+            .map_err(|err| format!("Failed getting fee for message {}", err))?;
+        //At most the rent for the whole account is needed
+        let rent = self
+            .client
+            .get_minimum_balance_for_rent_exemption(Counter::LEN)
+            .map_err(|err| format!("Error getting Minimum balance for rent exemption {}", err))?;
+        self.request_airdrop(fee_for_message + rent)
+            .map_err(|err| format!("Error during airdrop {}", err))?;
+        let blockhash = message.recent_blockhash;
+        let transaction = Transaction::new(&[&payer], message, blockhash);
+        self.process_transaction(&transaction, Some(&counter_pubkey))
+            .map(|_signature| ())
+            .map_err(|err| format!("Error sending counter migration transaction {}", err))
+    }
+
     //Check if the program has been deployed
     //This program expects that the on-chain program be deployed as `solana program deploy
     //program.so` - this ensures that the deployed program is owned by upgradeable_loader
@@ -317,25 +789,26 @@ impl Client {
     //Send a transaction to increament the counter
 
     pub fn increament_counter(&self) -> Result<()> {
-        let payer = Self::get_payer_keypair().ok_or("Payer keypair not found")?;
-        let payer_pubkey = payer.pubkey();
         let program_id = Self::get_program_id()
             .ok_or("Program pubkey not found! Program may not have been built")?;
+        let payer = Self::get_payer_keypair().ok_or("Payer keypair not found")?;
+        let payer_pubkey = payer.pubkey();
         let counter_pubkey = Self::get_counter_pubkey();
 
-        let counter_instruction = CounterInstruction::Increament;
-        let instruction = Instruction::new_with_borsh(
-            program_id,
-            &counter_instruction,
-            vec![AccountMeta::new(counter_pubkey, false)],
-        );
+        let mut instruction = Self::counter_instruction(
+            &CounterInstruction::Increament,
+            &payer_pubkey,
+            &payer_pubkey,
+        )?;
+        let mut instructions = self.add_increament_accounts(
+            &CounterInstruction::Increament,
+            &mut instruction,
+            &payer_pubkey,
+            &payer_pubkey,
+        )?;
+        instructions.push(instruction);
 
-        let blockhash = self
-            .client
-            .get_latest_blockhash()
-            .map_err(|err| format!("Latest block hash {}", err))?;
-        let message = Message::new_with_blockhash(&[instruction], Some(&payer_pubkey), &blockhash);
-        //let message = Message::new_with_blockhash(&[instruction.clone(), instruction.clone()], Some(&payer_pubkey), &blockhash);
+        let message = self.compose_message(&instructions, &payer_pubkey)?;
         //Check lamports needed to execute this message
         let fee_for_message = self
             .client
@@ -345,36 +818,204 @@ impl Client {
         //Request airdrop if needed
         self.request_airdrop(fee_for_message)
             .map_err(|s| format!("Error during airdrop {}", s))?;
+        let blockhash = message.recent_blockhash;
         let transaction = Transaction::new(&[&payer], message, blockhash);
 
-        let _signature = self
-            .client
-            .send_and_confirm_transaction(&transaction)
+        let signature = self
+            .process_transaction(&transaction, Some(&counter_pubkey))
             .map_err(|err| format!("Error while sending counter increament transaction {}", err))?;
+        //The program returns the new counter value - no need to read the account again.
+        //The increament has landed by now - the transaction lagging behind at the RPC
+        //node is no failure, return data that does not decode is
+        if let Some(ref signature) = signature {
+            match self.get_transaction_meta(signature) {
+                Ok(meta) => match decode_return_data(meta.return_data.into(), &program_id)? {
+                    Some(counter) => println!("Counter value after increament {}", counter.count),
+                    None => println!("Counter value not returned by the program"),
+                },
+                Err(err) => println!("Counter value not available {}", err),
+            }
+        }
         Ok(())
     }
 
+    //Compose a message for the instructions. Uses the blockhash stored in the durable
+    //nonce account if one is configured(the payer must be the nonce authority) -
+    //otherwise the latest blockhash, which expires in about a minute
+    pub(crate) fn compose_message(
+        &self,
+        instructions: &[Instruction],
+        payer_pubkey: &Pubkey,
+    ) -> Result<Message> {
+        match self.nonce_pubkey {
+            Some(ref nonce_pubkey) => {
+                let nonce_data = self.get_nonce_data(nonce_pubkey)?;
+                nonce_message(
+                    instructions,
+                    payer_pubkey,
+                    nonce_pubkey,
+                    payer_pubkey,
+                    &nonce_data,
+                )
+            }
+            None => {
+                let blockhash = self
+                    .client
+                    .get_latest_blockhash()
+                    .map_err(|err| format!("Error retrieving latest block hash {}", err))?;
+                Ok(Message::new_with_blockhash(
+                    instructions,
+                    Some(payer_pubkey),
+                    &blockhash,
+                ))
+            }
+        }
+    }
+
+    //Send the transaction and wait for confirmation - in dry run mode simulate it
+    //instead and print out what it would have done. Returns the signature of the
+    //confirmed transaction - None in dry run mode
+    pub(crate) fn process_transaction(
+        &self,
+        transaction: &Transaction,
+        counter_pubkey: Option<&Pubkey>,
+    ) -> Result<Option<Signature>> {
+        if !self.dry_run {
+            let signature = self
+                .client
+                .send_and_confirm_transaction(transaction)
+                .map_err(|err| {
+                    match err.get_transaction_error().as_ref().and_then(counter_error) {
+                        Some(counter_err) => format!("{} - {}", counter_err, err),
+                        None => err.to_string(),
+                    }
+                })?;
+            println!("Transaction {} confirmed", signature);
+            return Ok(Some(signature));
+        }
+        let simulation = self.simulate_transaction(transaction, counter_pubkey)?;
+        println!("Dry run - transaction was not broadcast");
+        for line in simulation.report(counter_pubkey.is_some()) {
+            println!("{}", line);
+        }
+        match simulation.error() {
+            Some(err) => Err(err),
+            None => Ok(None),
+        }
+    }
+
+    //Simulate a transaction against the current cluster state. Nothing is broadcast.
+    //Also fetches the counter account(if given) as it would be after the transaction
+    pub fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+        counter_pubkey: Option<&Pubkey>,
+    ) -> Result<Simulation> {
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: true,
+            accounts: counter_pubkey.map(|counter_pubkey| RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: vec![counter_pubkey.to_string()],
+            }),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let result = self
+            .client
+            .simulate_transaction_with_config(transaction, config)
+            .map_err(|err| format!("Error simulating transaction {}", err))?
+            .value;
+        //Only account asked for is the counter account
+        let counter = result
+            .accounts
+            .and_then(|accounts| accounts.into_iter().next().flatten())
+            .and_then(|ui_account| ui_account.decode::<Account>())
+            .and_then(|account| Counter::unpack(&account.data).ok());
+        let logs = result.logs.unwrap_or_default();
+        let (events, returned_counter) = match Self::get_program_id() {
+            Some(ref program_id) => (
+                events::decode_events(&logs, program_id),
+                decode_return_data(result.return_data, program_id)?,
+            ),
+            None => (vec![], None),
+        };
+        Ok(Simulation {
+            err: result.err,
+            logs,
+            units_consumed: result.units_consumed,
+            events,
+            counter,
+            returned_counter,
+        })
+    }
+
+    //Get the status meta(logs, return data etc) of a confirmed transaction
+    pub fn get_transaction_meta(&self, signature: &Signature) -> Result<UiTransactionStatusMeta> {
+        //Transactions are not looked up at processed commitment
+        let commitment = match self.client.commitment().is_at_least_confirmed() {
+            true => self.client.commitment(),
+            false => CommitmentConfig::confirmed(),
+        };
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(commitment),
+            max_supported_transaction_version: Some(0),
+        };
+        self.client
+            .get_transaction_with_config(signature, config)
+            .map_err(|err| format!("Error retrieving transaction {}", err))?
+            .transaction
+            .meta
+            .ok_or_else(|| "Transaction status meta not available".to_string())
+    }
+
     //Get the increamented counter value
     pub fn get_counter_reading(&self) -> Result<()> {
-        let program_id = Self::get_program_id()
-            .ok_or("Program pubkey not found! Program may not have been built")?;
-        //Retrieve all accounts owned by our program
-        let counter_accounts = self
+        //The program owns other accounts too(delegate records, snapshots etc) - fetch
+        //the counter by its address
+        let counter_pubkey = Self::get_counter_pubkey();
+        let counter_account = self
             .client
-            .get_program_accounts(&program_id)
-            .map_err(|err| format!("Program counter account may not have been setup {}", err))?;
-        //We have only one account that is owned by our program
-        let counter_account: &Account = match counter_accounts.first() {
-            //We are ignoring the first element of the returned tuple
-            Some((_pubkey, account)) => account,
-            None => return Err("Counter account not found".to_string()),
-        };
+            .get_account(&counter_pubkey)
+            .map_err(|err| format!("Counter account not found {}", err))?;
         //Get the data field out of the account
         let data = &counter_account.data;
-        //Deserialize it back to a Counter
-        let counter = Counter::try_from_slice(data)
+        //Deserialize it back to a Counter - whatever layout version it was written in
+        let versioned = VersionedCounter::unpack(data)
             .map_err(|err| format!("Error deserializing bytes to counter {}", err))?;
+        //Version 0 counters have no authority - it is assigned on migration
+        let has_authority = versioned.has_authority();
+        let counter = versioned.upgrade(&Pubkey::default());
         println!("Counter value {}", counter.count);
+        if !has_authority {
+            println!("Counter has no authority until migrated");
+            return Ok(());
+        }
+        println!("Counter authority {}", counter.authority);
+        println!("Counter access mode {:?}", counter.access_mode);
+        println!("Counter rate limit {:?}", counter.rate_limit);
+        println!(
+            "Counter bounds {} to {} - on overflow {:?}",
+            counter.bounds.min, counter.bounds.max, counter.bounds.overflow_policy
+        );
+        if counter.fee > 0 {
+            println!("Counter fee {} lamports per increament", counter.fee);
+        }
+        if counter.token_gate.is_enabled() {
+            println!("Counter token gate {:?}", counter.token_gate);
+        }
+        if let Some(pending_authority) = counter.pending_authority {
+            println!("Pending authority {}", pending_authority);
+        }
         Ok(())
     }
 }
+
+//The counter program error behind a failed transaction - if that is what failed it
+pub fn counter_error(err: &TransactionError) -> Option<CounterError> {
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            CounterError::from_code(*code)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,135 @@
+//Optimistic counter updates
+//
+//`CompareAndSet` and `IncrementIfEquals` only apply while the counter still holds the
+//value the caller read - otherwise they fail with `CounterError::ValueMismatch` and
+//the counter is left alone. A conflict means someone else updated the counter in
+//between: read it again and retry
+use crate::client::{counter_error, Client};
+use crate::errors::Result;
+use common::{CounterError, CounterInstruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+use std::thread;
+use std::time::Duration;
+
+//Wait before the first retry after a conflict - doubled on every further conflict
+static INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+static MAX_BACKOFF: Duration = Duration::from_secs(8);
+
+//Result of an update retried until it applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CasOutcome {
+    //Counter value the update set
+    pub value: u64,
+    //Transactions sent - the last one applied
+    pub attempts: u32,
+    //Counter values found changed under the update - one per failed attempt
+    pub conflicts: Vec<u64>,
+}
+
+impl Client {
+    //Set the counter derived from `owner` to `new` if it is still `expected`. `authority`
+    //signs for the counter authority - the payer if not given. Returns false if the
+    //counter held another value
+    pub fn compare_and_set(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        expected: u64,
+        new: u64,
+    ) -> Result<bool> {
+        self.send_conditional(
+            &CounterInstruction::CompareAndSet { expected, new },
+            owner,
+            authority,
+        )
+        .map_err(|err| format!("Error setting counter {}", err))
+    }
+
+    //Increament the counter derived from `owner` if it is still `expected`. `actor`
+    //signs the increament - the payer if not given. Returns false if the counter held
+    //another value
+    pub fn increment_if_equals(
+        &self,
+        owner: &Pubkey,
+        actor: Option<&Keypair>,
+        expected: u64,
+    ) -> Result<bool> {
+        self.send_conditional(
+            &CounterInstruction::IncrementIfEquals { expected },
+            owner,
+            actor,
+        )
+        .map_err(|err| format!("Error increamenting counter {}", err))
+    }
+
+    //Read the counter derived from `owner`, compute its new value with `update` and
+    //compare and set it - reading again and backing off whenever another update got in
+    //first. Gives up after `max_attempts` transactions
+    pub fn update_with_retry<F>(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        update: F,
+        max_attempts: u32,
+    ) -> Result<CasOutcome>
+    where
+        F: Fn(u64) -> u64,
+    {
+        let counter_pubkey = Self::get_counter_pubkey_for(owner)?;
+        let mut conflicts = Vec::new();
+        let mut backoff = INITIAL_BACKOFF;
+        for attempt in 1..=max_attempts {
+            let expected = self.get_counter(&counter_pubkey)?.count;
+            let new = update(expected);
+            if self.compare_and_set(owner, authority, expected, new)? {
+                return Ok(CasOutcome {
+                    value: new,
+                    attempts: attempt,
+                    conflicts,
+                });
+            }
+            println!(
+                "Conflict - counter changed from {} before attempt {} applied",
+                expected, attempt
+            );
+            conflicts.push(expected);
+            if attempt < max_attempts {
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+        Err(format!(
+            "Counter update gave up after {} conflicting attempts",
+            conflicts.len()
+        ))
+    }
+
+    //Send a conditional counter instruction - a value mismatch is reported as false
+    //rather than as an error. In dry run mode a mismatch fails the simulation
+    fn send_conditional(
+        &self,
+        counter_instruction: &CounterInstruction,
+        owner: &Pubkey,
+        actor: Option<&Keypair>,
+    ) -> Result<bool> {
+        let counter_pubkey = Self::get_counter_pubkey_for(owner)?;
+        let transaction = self.counter_transaction(counter_instruction, owner, actor)?;
+        if self.dry_run {
+            return self
+                .process_transaction(&transaction, Some(&counter_pubkey))
+                .map(|_signature| true);
+        }
+        match self.client.send_and_confirm_transaction(&transaction) {
+            Ok(signature) => {
+                println!("Transaction {} confirmed", signature);
+                Ok(true)
+            }
+            Err(err) => match err.get_transaction_error().as_ref().and_then(counter_error) {
+                Some(CounterError::ValueMismatch) => Ok(false),
+                Some(counter_err) => Err(format!("{} - {}", counter_err, err)),
+                None => Err(err.to_string()),
+            },
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//Who increamented a counter
+//
+//Increaments sent by this client pass along the contribution record of the signing
+//actor - a small program derived account(`Contribution`) per counter and contributor,
+//created on the first contribution at the expense of the actor. It tallies the units
+//the contributor increamented and the slot it last did
+use crate::client::Client;
+use crate::errors::Result;
+use borsh::BorshSerialize;
+use common::{AccountHeader, AccountType, Contribution};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+
+impl Client {
+    //Contributions to the counter - largest first, the most recent first among equals
+    pub fn get_contributions(&self, counter_pubkey: &Pubkey) -> Result<Vec<Contribution>> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let header = AccountHeader {
+            account_type: AccountType::Contribution,
+            version: Contribution::VERSION,
+        }
+        .try_to_vec()
+        .map_err(|err| format!("Error serializing contribution header {}", err))?;
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(Contribution::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, header)),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    AccountHeader::LEN,
+                    counter_pubkey.to_bytes().to_vec(),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self
+            .client
+            .get_program_accounts_with_config(&program_id, config)
+            .map_err(|err| format!("Error retrieving contribution accounts {}", err))?;
+        let mut contributions: Vec<Contribution> = accounts
+            .into_iter()
+            .filter_map(|(_pubkey, account)| Contribution::unpack(&account.data).ok())
+            .collect();
+        contributions.sort_by(|a, b| {
+            b.total
+                .cmp(&a.total)
+                .then_with(|| b.last_slot.cmp(&a.last_slot))
+        });
+        Ok(contributions)
+    }
+
+    //Contribution of `contributor` to the counter - None if it never contributed
+    pub fn get_contribution(
+        &self,
+        counter_pubkey: &Pubkey,
+        contributor: &Pubkey,
+    ) -> Result<Option<Contribution>> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let record = Contribution::find_address(counter_pubkey, contributor, &program_id).0;
+        match self.client.get_account(&record) {
+            Ok(account) if account.owner == program_id => Contribution::unpack(&account.data)
+                .map(Some)
+                .map_err(|err| format!("Error deserializing bytes to contribution {}", err)),
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//Typed counter program events out of transaction logs
+//
+//The counter program logs a borsh encoded `CounterEvent` for every instruction with
+//`sol_log_data`. The runtime writes these as `Program data: <base64>` lines. Other
+//programs in the same transaction may log data too - so lines are attributed to the
+//program executing at that point, tracked through the `Program <id> invoke [n]` and
+//`Program <id> success|failed` lines
+
+use crate::client::Client;
+use crate::errors::Result;
+use borsh::BorshDeserialize;
+use common::CounterEvent;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+//Extract the counter events from transaction(or simulation) logs
+pub fn decode_events(logs: &[String], program_id: &Pubkey) -> Vec<CounterEvent> {
+    let program_id = program_id.to_string();
+    //Programs currently executing - innermost last
+    let mut invocations: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for log in logs {
+        if let Some(data) = log.strip_prefix("Program data: ") {
+            if invocations.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            //Logged fields are base64 encoded and space separated - the event is the
+            //only field we log
+            events.extend(
+                data.split(' ')
+                    .filter_map(|field| base64::decode(field).ok())
+                    .filter_map(|bytes| CounterEvent::try_from_slice(&bytes).ok()),
+            );
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            let mut parts = rest.split(' ');
+            match (parts.next(), parts.next()) {
+                (Some(id), Some("invoke")) => invocations.push(id),
+                (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                    invocations.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+impl Client {
+    //Get the counter events emitted by a confirmed transaction
+    pub fn get_transaction_events(&self, signature: &Signature) -> Result<Vec<CounterEvent>> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let logs: Option<Vec<String>> = self.get_transaction_meta(signature)?.log_messages.into();
+        Ok(decode_events(&logs.unwrap_or_default(), &program_id))
+    }
+}
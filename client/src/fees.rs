@@ -0,0 +1,45 @@
+//Paid counters
+//
+//A counter may be set up to charge a fee in lamports per increament. Fees are paid by
+//the increamenting signer into the counter treasury - a system account at a program
+//derived address that only the program can move lamports out of, on behalf of the
+//counter authority
+use crate::client::Client;
+use crate::errors::Result;
+use common::{Counter, CounterInstruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+
+impl Client {
+    //Move `lamports` of collected fees(0 for all of them) from the treasury of the
+    //counter derived from `owner` to the authority. `authority` signs for the counter
+    //authority - the payer if not given
+    pub fn withdraw_fees(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        lamports: u64,
+    ) -> Result<()> {
+        let instruction = CounterInstruction::WithdrawFees { lamports };
+        self.send_counter_instruction(&instruction, owner, authority)
+            .map(|_signature| ())
+            .map_err(|err| format!("Error withdrawing fees {}", err))
+    }
+
+    //Fees collected by the counter and not yet withdrawn - the treasury balance above
+    //its rent exempt minimum
+    pub fn get_collected_fees(&self, counter: &Counter) -> Result<u64> {
+        if counter.treasury == Pubkey::default() {
+            return Ok(0);
+        }
+        let balance = self
+            .client
+            .get_balance(&counter.treasury)
+            .map_err(|err| format!("Error retrieving treasury balance {}", err))?;
+        let rent_exempt = self
+            .client
+            .get_minimum_balance_for_rent_exemption(0)
+            .map_err(|err| format!("Error getting Minimum balance for rent exemption {}", err))?;
+        Ok(balance.saturating_sub(rent_exempt))
+    }
+}
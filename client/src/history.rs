@@ -0,0 +1,29 @@
+//Latest changes of a counter
+//
+//A counter may be set up to keep its last changes - who made them, by how much, in
+//which slot and the resulting value - in a ring buffer at the end of the counter
+//account. Its capacity is fixed when the counter is created
+use crate::client::Client;
+use crate::errors::Result;
+use common::{History, HistoryEntry};
+use solana_sdk::pubkey::Pubkey;
+
+impl Client {
+    //Changes kept by the counter, oldest first - None if it keeps no history
+    pub fn get_history(&self, counter_pubkey: &Pubkey) -> Result<Option<Vec<HistoryEntry>>> {
+        let account = self
+            .client
+            .get_account(counter_pubkey)
+            .map_err(|err| format!("Error retrieving counter account {}", err))?;
+        let history = match History::unpack(&account.data)
+            .map_err(|err| format!("Error deserializing counter history {}", err))?
+        {
+            Some(history) => history,
+            None => return Ok(None),
+        };
+        history
+            .entries(&account.data)
+            .map(Some)
+            .map_err(|err| format!("Error deserializing counter history {}", err))
+    }
+}
@@ -0,0 +1,40 @@
+//Top contributors of a counter
+//
+//Once the counter authority creates the leaderboard of a counter - a program derived
+//account of fixed size - every increament that tracks its contribution also ranks the
+//contributor on it. It holds the top contributors by total, so reading it is a single
+//account fetch rather than a scan of all contribution records
+use crate::client::Client;
+use crate::errors::Result;
+use common::{CounterInstruction, Leaderboard};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+
+impl Client {
+    //Start ranking the top `capacity` contributors of the counter derived from `owner`.
+    //`authority` signs for the counter authority and pays the rent - the payer if not given
+    pub fn create_leaderboard(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        capacity: u8,
+    ) -> Result<()> {
+        let instruction = CounterInstruction::CreateLeaderboard { capacity };
+        self.send_counter_instruction(&instruction, owner, authority)
+            .map(|_signature| ())
+            .map_err(|err| format!("Error creating leaderboard {}", err))
+    }
+
+    //Leaderboard of the counter - None if it has not been created
+    pub fn get_leaderboard(&self, counter_pubkey: &Pubkey) -> Result<Option<Leaderboard>> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let leaderboard_pubkey = Leaderboard::find_address(counter_pubkey, &program_id).0;
+        match self.client.get_account(&leaderboard_pubkey) {
+            Ok(account) if account.owner == program_id => Leaderboard::unpack(&account.data)
+                .map(Some)
+                .map_err(|err| format!("Error deserializing bytes to leaderboard {}", err)),
+            _ => Ok(None),
+        }
+    }
+}
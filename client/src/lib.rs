@@ -1,7 +1,34 @@
+//Every module with items of its own is re-exported - the rest only extend `Client`
+pub use batch::*;
 pub use client::*;
+pub use compare_and_set::*;
 pub use config::*;
 pub use errors::*;
+pub use events::*;
+pub use nonce::*;
+pub use offline::*;
+pub use vouchers::*;
+pub use watch::*;
 
+pub mod access;
+pub mod admin;
+pub mod authority;
+pub mod batch;
 pub mod client;
+pub mod compare_and_set;
 pub mod config;
+pub mod contributions;
 pub mod errors;
+pub mod events;
+pub mod fees;
+pub mod history;
+pub mod leaderboard;
+pub mod milestones;
+pub mod multisig;
+pub mod nonce;
+pub mod offline;
+pub mod snapshots;
+pub mod time_series;
+pub mod token_gate;
+pub mod vouchers;
+pub mod watch;
@@ -1,11 +1,57 @@
+use client::batch::CounterOperation;
 use client::client::Client;
 use client::errors::Result;
+use client::offline::{self, TransactionEncoding};
+use client::vouchers::SignedVoucher;
+use common::{
+    AccessMode, Bounds, CounterInstruction, OverflowPolicy, RateLimit, RateUnit, Snapshot,
+    TokenGate,
+};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Signature, Signer};
+use solana_sdk::signer::keypair::{write_keypair_file, Keypair};
+use std::path::Path;
 use std::process;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 fn main() -> Result<()> {
     //Provides elaborate error messages - very useful during development
     solana_logger::setup_with("solana=debug");
-    let client = Client::default();
+    //Flags(like `--dry-run`) may appear anywhere - the rest are a command and its arguments
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (flags, params): (Vec<&str>, Vec<&str>) = args
+        .iter()
+        .map(String::as_str)
+        .partition(|arg| arg.starts_with("--"));
+    //Pass in `--dry-run`(or `simulate`) to simulate the transactions without sending them
+    let dry_run = flags.contains(&"--dry-run") || params.first() == Some(&"simulate");
+    //Pass in `--nonce=NONCE_ACCOUNT` to use a durable nonce instead of a recent blockhash
+    let nonce = match flags.iter().find_map(|flag| flag.strip_prefix("--nonce=")) {
+        Some(nonce) => match parse_pubkey(nonce) {
+            Ok(nonce) => Some(nonce),
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    match params.first() {
+        None | Some(&"simulate") => {}
+        Some(command) => {
+            if let Err(err) = run_command(command, &params[1..], &flags, dry_run, nonce) {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+            return Ok(());
+        }
+    }
+
+    let client = Client::default()
+        .with_dry_run(dry_run)
+        .with_nonce_account(nonce);
 
     if let Err(err) = client.setup_counter_account() {
         eprintln!("Error while setting counter account {}", err);
@@ -29,3 +75,801 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+//Compare and set attempts of the `set` command before it gives up
+const SET_ATTEMPTS: u32 = 5;
+
+fn run_command(
+    command: &str,
+    args: &[&str],
+    flags: &[&str],
+    dry_run: bool,
+    nonce: Option<Pubkey>,
+) -> Result<()> {
+    //Offline transactions are passed around base64 encoded unless `--base58` is given
+    let encoding = match flags.contains(&"--base58") {
+        true => TransactionEncoding::Base58,
+        false => TransactionEncoding::Base64,
+    };
+    //Commands connect to the cluster only when they need to
+    let connect = || {
+        Client::default()
+            .with_dry_run(dry_run)
+            .with_nonce_account(nonce)
+    };
+    match (command, args) {
+        //Build an unsigned transaction that uses a durable nonce - nonce authority
+        //defaults to the counter owner
+        ("offline-create", [operation, owner, nonce, rest @ ..]) => {
+            let counter_instruction = parse_operation(operation)?;
+            let owner = parse_pubkey(owner)?;
+            let nonce = parse_pubkey(nonce)?;
+            let nonce_authority = match rest.first() {
+                Some(nonce_authority) => parse_pubkey(nonce_authority)?,
+                None => owner,
+            };
+            let transaction = connect().create_offline_transaction(
+                &counter_instruction,
+                &owner,
+                &nonce,
+                &nonce_authority,
+            )?;
+            println!("{}", offline::encode_transaction(&transaction, encoding)?);
+            Ok(())
+        }
+        //Sign on the air-gapped machine - no connection to the cluster is made
+        ("offline-sign", [transaction, keypair_path]) => {
+            let transaction = offline::decode_transaction(transaction, encoding)?;
+            let keypair = client::config::get_keypair(keypair_path)
+                .ok_or_else(|| format!("Keypair not found {}", keypair_path))?;
+            let (pubkey, signature) = Client::sign_offline(&transaction, &keypair)?;
+            println!("{}={}", pubkey, signature);
+            Ok(())
+        }
+        //Assemble the detached signatures and send the transaction
+        ("offline-broadcast", [transaction, signatures @ ..]) => {
+            let transaction = offline::decode_transaction(transaction, encoding)?;
+            let signatures = signatures
+                .iter()
+                .map(|signature| offline::parse_signer_signature(signature))
+                .collect::<Result<Vec<_>>>()?;
+            let transaction = Client::assemble_transaction(transaction, &signatures)?;
+            connect().broadcast_transaction(&transaction)?;
+            if !dry_run {
+                println!("Transaction {} sent", transaction.signatures[0]);
+            }
+            Ok(())
+        }
+        //Create a nonce account - the keypair is generated(and saved) if the file does
+        //not exist. Nonce authority defaults to the payer
+        ("nonce-create", [keypair_path, rest @ ..]) => {
+            let nonce_keypair = match Path::new(keypair_path).exists() {
+                true => client::config::get_keypair(keypair_path)
+                    .ok_or_else(|| format!("Keypair not found {}", keypair_path))?,
+                false => {
+                    let keypair = Keypair::new();
+                    write_keypair_file(&keypair, keypair_path)
+                        .map_err(|err| format!("Error writing keypair {}", err))?;
+                    keypair
+                }
+            };
+            let authority = match rest.first() {
+                Some(authority) => parse_pubkey(authority)?,
+                None => Client::get_payer_keypair()
+                    .ok_or("Payer keypair not found")?
+                    .pubkey(),
+            };
+            connect().create_nonce_account(&nonce_keypair, &authority)
+        }
+        ("nonce-advance", [nonce_account]) => {
+            connect().advance_nonce_account(&parse_pubkey(nonce_account)?)
+        }
+        ("nonce-show", [nonce_account]) => {
+            let nonce_data = connect().get_nonce_data(&parse_pubkey(nonce_account)?)?;
+            println!("Nonce authority {}", nonce_data.authority);
+            println!("Nonce blockhash {}", nonce_data.blockhash());
+            println!(
+                "Fee {} lamports per signature",
+                nonce_data.fee_calculator.lamports_per_signature
+            );
+            Ok(())
+        }
+        //Operations are given as OPERATION[:OWNER] - owner defaults to the payer
+        ("batch", operations) if !operations.is_empty() => {
+            let payer_pubkey = Client::get_payer_keypair()
+                .ok_or("Payer keypair not found")?
+                .pubkey();
+            let operations = operations
+                .iter()
+                .map(|operation| {
+                    let (operation, owner) = match operation.split_once(':') {
+                        Some((operation, owner)) => (operation, parse_pubkey(owner)?),
+                        None => (*operation, payer_pubkey),
+                    };
+                    Ok(CounterOperation {
+                        instruction: parse_operation(operation)?,
+                        owner,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            //Owners other than the payer sign their authority gated operations - their
+            //keypairs are passed in with `--signer=KEYPAIR`
+            let signers = flags
+                .iter()
+                .filter_map(|flag| flag.strip_prefix("--signer="))
+                .map(|keypair_path| {
+                    client::config::get_keypair(keypair_path)
+                        .ok_or_else(|| format!("Keypair not found {}", keypair_path))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let signers: Vec<&Keypair> = signers.iter().collect();
+            let results = connect().execute_batch(&operations, &signers)?;
+            let mut failed = 0;
+            for batch in &results {
+                match batch.result {
+                    Ok(()) => println!(
+                        "Operations {:?} succeeded - transaction {}",
+                        batch.operations, batch.signature
+                    ),
+                    Err(ref err) => {
+                        failed += 1;
+                        println!("Operations {:?} failed {}", batch.operations, err)
+                    }
+                }
+            }
+            match failed {
+                0 => Ok(()),
+                _ => Err(format!("{} of {} batches failed", failed, results.len())),
+            }
+        }
+        //Print every change to the counter owned by OWNER(defaults to the payer)
+        ("watch", rest) if rest.len() <= 1 => {
+            let counter_pubkey = match rest.first() {
+                Some(owner) => Client::get_counter_pubkey_for(&parse_pubkey(owner)?)?,
+                None => Client::get_counter_pubkey(),
+            };
+            println!("Watching counter account {}", counter_pubkey);
+            for update in connect().watch_counter(&counter_pubkey)? {
+                match update {
+                    Ok(update) => match update.delta {
+                        Some(delta) => println!(
+                            "Counter value {} at slot {} (delta {:+})",
+                            update.counter.count, update.slot, delta
+                        ),
+                        None => println!(
+                            "Counter value {} at slot {}",
+                            update.counter.count, update.slot
+                        ),
+                    },
+                    Err(err) => eprintln!("{}", err),
+                }
+            }
+            Ok(())
+        }
+        //Create the payer's counter kept within MIN and MAX
+        //Optionally charging FEE lamports per increament and keeping the last HISTORY
+        //changes
+        ("setup", [min, max, overflow_policy, rest @ ..]) if rest.len() <= 2 => {
+            let bounds = Bounds {
+                min: parse_number(min)?,
+                max: parse_number(max)?,
+                overflow_policy: match *overflow_policy {
+                    "fail" => OverflowPolicy::Fail,
+                    "saturate" => OverflowPolicy::Saturate,
+                    "wrap" => OverflowPolicy::Wrap,
+                    _ => return Err(format!("Unknown overflow policy {}", overflow_policy)),
+                },
+            };
+            let fee = match rest.first() {
+                Some(fee) => parse_number(fee)?,
+                None => 0,
+            };
+            let history_capacity = match rest.get(1) {
+                Some(history_capacity) => parse_number(history_capacity)?,
+                None => 0,
+            };
+            connect().setup_counter_account_with_history(bounds, fee, history_capacity)
+        }
+        //Bring the payer's counter account to the current layout
+        ("migrate", []) => connect().migrate_counter(),
+        //Authority of the counter owned by OWNER(defaults to the payer)
+        ("authority-show", rest) if rest.len() <= 1 => {
+            let counter_pubkey = match rest.first() {
+                Some(owner) => Client::get_counter_pubkey_for(&parse_pubkey(owner)?)?,
+                None => Client::get_counter_pubkey(),
+            };
+            let counter = connect().get_counter(&counter_pubkey)?;
+            println!("Counter authority {}", counter.authority);
+            println!("Counter access mode {:?}", counter.access_mode);
+            println!("Counter rate limit {:?}", counter.rate_limit);
+            println!(
+                "Counter bounds {} to {} - on overflow {:?}",
+                counter.bounds.min, counter.bounds.max, counter.bounds.overflow_policy
+            );
+            match counter.fee {
+                0 => println!("Counter charges no fee"),
+                fee => println!("Counter fee {} lamports per increament", fee),
+            }
+            match counter.token_gate.is_enabled() {
+                true => println!(
+                    "Counter increamented by holders of at least {} of mint {}",
+                    counter.token_gate.min_balance, counter.token_gate.mint
+                ),
+                false => println!("Counter is not token gated"),
+            }
+            match counter.pending_authority {
+                Some(pending_authority) => println!("Pending authority {}", pending_authority),
+                None => println!("No authority transfer pending"),
+            }
+            Ok(())
+        }
+        //Latest changes of the counter owned by OWNER(defaults to the payer), oldest first
+        ("history", rest) if rest.len() <= 1 => {
+            let counter_pubkey = match rest.first() {
+                Some(owner) => Client::get_counter_pubkey_for(&parse_pubkey(owner)?)?,
+                None => Client::get_counter_pubkey(),
+            };
+            match connect().get_history(&counter_pubkey)? {
+                Some(entries) => {
+                    for entry in entries {
+                        println!(
+                            "slot {} {:+} to {} by {}",
+                            entry.slot, entry.delta, entry.value, entry.actor
+                        );
+                    }
+                }
+                None => println!("Counter keeps no history"),
+            }
+            Ok(())
+        }
+        //Authority keypair and owner default to the payer
+        ("authority-propose", [new_authority, rest @ ..]) if rest.len() <= 2 => {
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().propose_authority(&owner, authority.as_ref(), &parse_pubkey(new_authority)?)
+        }
+        //Signed by the pending authority
+        ("authority-accept", [_keypair_path, rest @ ..]) if rest.len() <= 1 => {
+            let (new_authority, owner) = parse_authority_args(args)?;
+            let new_authority = new_authority.ok_or("Pending authority keypair not given")?;
+            connect().accept_authority(&owner, &new_authority)
+        }
+        ("authority-cancel", rest) if rest.len() <= 2 => {
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().cancel_authority_transfer(&owner, authority.as_ref())
+        }
+        //Who may increament: open, authority or allowlist
+        ("access-mode", [access_mode, rest @ ..]) if rest.len() <= 2 => {
+            let access_mode = parse_access_mode(access_mode)?;
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().set_access_mode(&owner, authority.as_ref(), access_mode)
+        }
+        //Rate limit in slots or seconds - `off` lifts it
+        ("rate-limit", ["off", rest @ ..]) if rest.len() <= 2 => {
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().set_rate_limit(&owner, authority.as_ref(), RateLimit::default())
+        }
+        ("rate-limit", [unit, min_interval, window, max_per_window, rest @ ..])
+            if rest.len() <= 2 =>
+        {
+            let rate_limit = RateLimit {
+                unit: match *unit {
+                    "slots" => RateUnit::Slots,
+                    "seconds" => RateUnit::Seconds,
+                    _ => return Err(format!("Unknown rate limit unit {}", unit)),
+                },
+                min_interval: parse_number(min_interval)?,
+                window: parse_number(window)?,
+                max_per_window: parse_number(max_per_window)?,
+            };
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().set_rate_limit(&owner, authority.as_ref(), rate_limit)
+        }
+        //Only holders of at least MIN_BALANCE of MINT may increament - `off` lifts the gate
+        ("token-gate", ["off", rest @ ..]) if rest.len() <= 2 => {
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().set_token_gate(&owner, authority.as_ref(), TokenGate::default())
+        }
+        ("token-gate", [mint, min_balance, rest @ ..]) if rest.len() <= 2 => {
+            let token_gate = TokenGate {
+                mint: parse_pubkey(mint)?,
+                min_balance: parse_number(min_balance)?,
+            };
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().set_token_gate(&owner, authority.as_ref(), token_gate)
+        }
+        //Set the counter only if it still holds EXPECTED
+        ("cas", [expected, new, rest @ ..]) if rest.len() <= 2 => {
+            let (authority, owner) = parse_authority_args(rest)?;
+            let (expected, new) = (parse_number(expected)?, parse_number(new)?);
+            match connect().compare_and_set(&owner, authority.as_ref(), expected, new)? {
+                true => println!("Counter set to {}", new),
+                false => println!("Conflict - counter no longer holds {}", expected),
+            }
+            Ok(())
+        }
+        //Increament signed by the payer, only if the counter still holds EXPECTED
+        ("increment-if", [expected, rest @ ..]) if rest.len() <= 1 => {
+            let owner = match rest.first() {
+                Some(owner) => parse_pubkey(owner)?,
+                None => Client::get_payer_keypair()
+                    .ok_or("Payer keypair not found")?
+                    .pubkey(),
+            };
+            let expected = parse_number(expected)?;
+            match connect().increment_if_equals(&owner, None, expected)? {
+                true => println!("Counter increamented from {}", expected),
+                false => println!("Conflict - counter no longer holds {}", expected),
+            }
+            Ok(())
+        }
+        //Set the counter - read and compare and set, retried on conflicts
+        ("set", [value, rest @ ..]) if rest.len() <= 2 => {
+            let (authority, owner) = parse_authority_args(rest)?;
+            let value = parse_number(value)?;
+            let outcome =
+                connect().update_with_retry(&owner, authority.as_ref(), |_| value, SET_ATTEMPTS)?;
+            println!(
+                "Counter set to {} after {} attempts - {} conflicts",
+                outcome.value,
+                outcome.attempts,
+                outcome.conflicts.len()
+            );
+            Ok(())
+        }
+        ("fees", rest) if rest.len() <= 1 => {
+            let counter_pubkey = match rest.first() {
+                Some(owner) => Client::get_counter_pubkey_for(&parse_pubkey(owner)?)?,
+                None => Client::get_counter_pubkey(),
+            };
+            let client = connect();
+            let counter = client.get_counter(&counter_pubkey)?;
+            println!("Counter fee {} lamports per increament", counter.fee);
+            println!("Counter treasury {}", counter.treasury);
+            println!(
+                "Fees collected {} lamports",
+                client.get_collected_fees(&counter)?
+            );
+            Ok(())
+        }
+        //Fees go to the authority - `all` withdraws everything collected
+        ("fees-withdraw", [lamports, rest @ ..]) if rest.len() <= 2 => {
+            let lamports = match *lamports {
+                "all" => 0,
+                lamports => parse_number(lamports)?,
+            };
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().withdraw_fees(&owner, authority.as_ref(), lamports)
+        }
+        ("delegate-add", [delegate, rest @ ..]) if rest.len() <= 2 => {
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().add_delegate(&owner, authority.as_ref(), &parse_pubkey(delegate)?)
+        }
+        ("delegate-remove", [delegate, rest @ ..]) if rest.len() <= 2 => {
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().remove_delegate(&owner, authority.as_ref(), &parse_pubkey(delegate)?)
+        }
+        ("delegate-list", rest) if rest.len() <= 1 => {
+            let counter_pubkey = match rest.first() {
+                Some(owner) => Client::get_counter_pubkey_for(&parse_pubkey(owner)?)?,
+                None => Client::get_counter_pubkey(),
+            };
+            for delegate in connect().get_delegates(&counter_pubkey)? {
+                println!("{}", delegate);
+            }
+            Ok(())
+        }
+        //Contributors by contribution, largest first - or the one of CONTRIBUTOR
+        ("contributors", rest) if rest.len() <= 1 => {
+            let counter_pubkey = match rest.first() {
+                Some(owner) => Client::get_counter_pubkey_for(&parse_pubkey(owner)?)?,
+                None => Client::get_counter_pubkey(),
+            };
+            for contribution in connect().get_contributions(&counter_pubkey)? {
+                println!(
+                    "{} {} - last at slot {}",
+                    contribution.contributor, contribution.total, contribution.last_slot
+                );
+            }
+            Ok(())
+        }
+        ("leaderboard-create", [capacity, rest @ ..]) if rest.len() <= 2 => {
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().create_leaderboard(&owner, authority.as_ref(), parse_number(capacity)?)
+        }
+        //Mint with DECIMALS the counter(of the payer if no OWNER is given) can reward with
+        ("reward-mint-create", [decimals, rest @ ..]) if rest.len() <= 1 => {
+            let owner = match rest.first() {
+                Some(owner) => parse_pubkey(owner)?,
+                None => Client::get_payer_keypair()
+                    .ok_or("Payer keypair not found")?
+                    .pubkey(),
+            };
+            let mint = connect().create_reward_mint(&owner, parse_number(decimals)?)?;
+            println!("Reward mint {}", mint);
+            Ok(())
+        }
+        //REWARD base units of MINT for every EVERY increaments
+        ("milestones-create", [mint, every, reward, rest @ ..]) if rest.len() <= 2 => {
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().create_milestones(
+                &owner,
+                authority.as_ref(),
+                &parse_pubkey(mint)?,
+                parse_number(every)?,
+                parse_number(reward)?,
+            )
+        }
+        ("milestones", rest) if rest.len() <= 1 => {
+            let counter_pubkey = match rest.first() {
+                Some(owner) => Client::get_counter_pubkey_for(&parse_pubkey(owner)?)?,
+                None => Client::get_counter_pubkey(),
+            };
+            match connect().get_milestones(&counter_pubkey)? {
+                Some(milestones) => println!(
+                    "{} of mint {} every {} increaments",
+                    milestones.reward, milestones.mint, milestones.every
+                ),
+                None => println!("Counter has no milestones"),
+            }
+            Ok(())
+        }
+        //Buckets WIDTH(hourly, daily or seconds) wide
+        ("time-series-create", [width, count, rest @ ..]) if rest.len() <= 2 => {
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().create_time_series(
+                &owner,
+                authority.as_ref(),
+                parse_bucket_width(width)?,
+                parse_number(count)?,
+            )
+        }
+        ("time-series", rest) if rest.len() <= 1 => {
+            let counter_pubkey = match rest.first() {
+                Some(owner) => Client::get_counter_pubkey_for(&parse_pubkey(owner)?)?,
+                None => Client::get_counter_pubkey(),
+            };
+            match connect().get_time_series(&counter_pubkey)? {
+                Some(series) => {
+                    for (start, increaments) in series {
+                        println!("{} {}", start, increaments);
+                    }
+                }
+                None => println!("Counter has no time series"),
+            }
+            Ok(())
+        }
+        //Approval to increament by AMOUNT, redeemable once for NONCE within VALID_SECONDS.
+        //Signed by the counter authority - the payer if no keypair is given
+        ("voucher-issue", [amount, nonce, valid_seconds, rest @ ..]) if rest.len() <= 2 => {
+            let (issuer, owner) = parse_authority_args(rest)?;
+            let issuer = match issuer {
+                Some(issuer) => issuer,
+                None => Client::get_payer_keypair().ok_or("Payer keypair not found")?,
+            };
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|err| format!("Error reading the system time {}", err))?;
+            let valid_seconds: i64 = parse_number(valid_seconds)?;
+            let signed_voucher = Client::issue_voucher(
+                &owner,
+                &issuer,
+                parse_number(amount)?,
+                parse_number(nonce)?,
+                now.as_secs() as i64 + valid_seconds,
+            )?;
+            println!("{}", signed_voucher.encode());
+            Ok(())
+        }
+        ("voucher-redeem", [voucher, rest @ ..]) if rest.len() <= 1 => {
+            let redeemer = match rest.first() {
+                Some(keypair_path) => Some(
+                    client::config::get_keypair(keypair_path)
+                        .ok_or_else(|| format!("Keypair not found {}", keypair_path))?,
+                ),
+                None => None,
+            };
+            connect().redeem_voucher(&SignedVoucher::decode(voucher)?, redeemer.as_ref())
+        }
+        ("snapshot-create", [label, rest @ ..]) if rest.len() <= 2 => {
+            let (authority, owner) = parse_authority_args(rest)?;
+            connect().take_snapshot(&owner, authority.as_ref(), label)
+        }
+        ("snapshot-list", rest) if rest.len() <= 1 => {
+            let counter_pubkey = match rest.first() {
+                Some(owner) => Client::get_counter_pubkey_for(&parse_pubkey(owner)?)?,
+                None => Client::get_counter_pubkey(),
+            };
+            for snapshot in connect().get_snapshots(&counter_pubkey)? {
+                print_snapshot(&snapshot);
+            }
+            Ok(())
+        }
+        ("snapshot-show", [label, rest @ ..]) if rest.len() <= 1 => {
+            let counter_pubkey = match rest.first() {
+                Some(owner) => Client::get_counter_pubkey_for(&parse_pubkey(owner)?)?,
+                None => Client::get_counter_pubkey(),
+            };
+            match connect().get_snapshot(&counter_pubkey, label)? {
+                Some(snapshot) => print_snapshot(&snapshot),
+                None => println!("No snapshot {}", label),
+            }
+            Ok(())
+        }
+        ("leaderboard", rest) if rest.len() <= 1 => {
+            let counter_pubkey = match rest.first() {
+                Some(owner) => Client::get_counter_pubkey_for(&parse_pubkey(owner)?)?,
+                None => Client::get_counter_pubkey(),
+            };
+            match connect().get_leaderboard(&counter_pubkey)? {
+                Some(leaderboard) => {
+                    println!("Top {} contributors", leaderboard.capacity);
+                    for (rank, entry) in leaderboard.entries().iter().enumerate() {
+                        println!("{}. {} {}", rank + 1, entry.contributor, entry.total);
+                    }
+                }
+                None => println!("Counter has no leaderboard"),
+            }
+            Ok(())
+        }
+        ("contribution", [contributor, rest @ ..]) if rest.len() <= 1 => {
+            let counter_pubkey = match rest.first() {
+                Some(owner) => Client::get_counter_pubkey_for(&parse_pubkey(owner)?)?,
+                None => Client::get_counter_pubkey(),
+            };
+            let contributor = parse_pubkey(contributor)?;
+            match connect().get_contribution(&counter_pubkey, &contributor)? {
+                Some(contribution) => println!(
+                    "{} {} - last at slot {}",
+                    contributor, contribution.total, contribution.last_slot
+                ),
+                None => println!("{} has not contributed", contributor),
+            }
+            Ok(())
+        }
+        //M of N multisig over the given signer pubkeys
+        ("multisig-create", [threshold, signers @ ..]) if !signers.is_empty() => {
+            let threshold = threshold
+                .parse::<u8>()
+                .map_err(|err| format!("Invalid threshold {} {}", threshold, err))?;
+            let signers = signers
+                .iter()
+                .map(|signer| parse_pubkey(signer))
+                .collect::<Result<Vec<_>>>()?;
+            let multisig_pubkey = connect().create_multisig(threshold, &signers)?;
+            println!("Multisig {} created", multisig_pubkey);
+            Ok(())
+        }
+        ("multisig-show", [multisig]) => {
+            let multisig = connect().get_multisig(&parse_pubkey(multisig)?)?;
+            println!(
+                "{} of {} multisig",
+                multisig.threshold,
+                multisig.signers().len()
+            );
+            for signer in multisig.signers() {
+                println!("Signer {}", signer);
+            }
+            Ok(())
+        }
+        //Authority gated OPERATION[:OWNER] signed on behalf of the multisig by the keypairs
+        ("multisig-send", [multisig, operation, keypair_paths @ ..])
+            if !keypair_paths.is_empty() =>
+        {
+            let multisig = parse_pubkey(multisig)?;
+            let (operation, owner) = match operation.split_once(':') {
+                Some((operation, owner)) => (operation, parse_pubkey(owner)?),
+                None => (
+                    *operation,
+                    Client::get_payer_keypair()
+                        .ok_or("Payer keypair not found")?
+                        .pubkey(),
+                ),
+            };
+            let keypairs = keypair_paths
+                .iter()
+                .map(|keypair_path| {
+                    client::config::get_keypair(keypair_path)
+                        .ok_or_else(|| format!("Keypair not found {}", keypair_path))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let signers: Vec<&Keypair> = keypairs.iter().collect();
+            connect()
+                .send_multisig_instruction(
+                    &parse_operation(operation)?,
+                    &owner,
+                    &multisig,
+                    &signers,
+                )
+                .map(|_signature| ())
+        }
+        //Signed by the payer - the upgrade authority of the program. Admin defaults to it
+        ("config-init", rest) if rest.len() <= 1 => {
+            let admin = match rest.first() {
+                Some(admin) => parse_pubkey(admin)?,
+                None => Client::get_payer_keypair()
+                    .ok_or("Payer keypair not found")?
+                    .pubkey(),
+            };
+            connect().initialize_config(&admin)
+        }
+        ("config-show", []) => {
+            match connect().get_program_config()? {
+                Some(config) => {
+                    println!("Program admin {}", config.admin);
+                    println!("Program paused {}", config.paused);
+                    match config.pending_admin {
+                        Some(pending_admin) => println!("Pending admin {}", pending_admin),
+                        None => println!("No admin rotation pending"),
+                    }
+                }
+                None => println!("Program config not created - the program is not paused"),
+            }
+            Ok(())
+        }
+        //Admin keypair defaults to the payer
+        ("pause", rest) if rest.len() <= 1 => {
+            let (admin, _) = parse_authority_args(rest)?;
+            connect().set_paused(admin.as_ref(), true)
+        }
+        ("unpause", rest) if rest.len() <= 1 => {
+            let (admin, _) = parse_authority_args(rest)?;
+            connect().set_paused(admin.as_ref(), false)
+        }
+        ("admin-propose", [new_admin, rest @ ..]) if rest.len() <= 1 => {
+            let (admin, _) = parse_authority_args(rest)?;
+            connect().propose_admin(admin.as_ref(), &parse_pubkey(new_admin)?)
+        }
+        //Signed by the pending admin
+        ("admin-accept", [keypair_path]) => {
+            let new_admin = client::config::get_keypair(keypair_path)
+                .ok_or_else(|| format!("Keypair not found {}", keypair_path))?;
+            connect().accept_admin(&new_admin)
+        }
+        //Print the counter events emitted by a confirmed transaction
+        ("events", [signature]) => {
+            let signature = Signature::from_str(signature)
+                .map_err(|err| format!("Invalid signature {} {}", signature, err))?;
+            for event in connect().get_transaction_events(&signature)? {
+                println!("{:?}", event);
+            }
+            Ok(())
+        }
+        _ => Err(format!(
+            "Unknown command or missing arguments: {} {}",
+            command,
+            args.join(" ")
+        )),
+    }
+}
+
+//Operations taking an argument are given as OPERATION=ARGUMENT
+fn parse_operation(operation: &str) -> Result<CounterInstruction> {
+    match operation.split_once('=') {
+        None => match operation {
+            "increament" | "increment" => Ok(CounterInstruction::Increament),
+            "reset" => Ok(CounterInstruction::Reset),
+            "close" => Ok(CounterInstruction::Close),
+            "migrate" => Ok(CounterInstruction::Migrate),
+            "accept" => Ok(CounterInstruction::AcceptAuthority),
+            "cancel" => Ok(CounterInstruction::CancelAuthorityTransfer),
+            "pause" => Ok(CounterInstruction::Pause),
+            "unpause" => Ok(CounterInstruction::Unpause),
+            "admin-accept" => Ok(CounterInstruction::AcceptAdmin),
+            _ => Err(format!("Unknown counter operation {}", operation)),
+        },
+        Some(("propose", new_authority)) => Ok(CounterInstruction::ProposeAuthority {
+            new_authority: parse_pubkey(new_authority)?,
+        }),
+        Some(("access", access_mode)) => Ok(CounterInstruction::SetAccessMode {
+            access_mode: parse_access_mode(access_mode)?,
+        }),
+        Some(("withdraw", lamports)) => Ok(CounterInstruction::WithdrawFees {
+            lamports: parse_number(lamports)?,
+        }),
+        Some(("admin-propose", new_admin)) => Ok(CounterInstruction::ProposeAdmin {
+            new_admin: parse_pubkey(new_admin)?,
+        }),
+        Some(("delegate-add", delegate)) => Ok(CounterInstruction::AddDelegate {
+            delegate: parse_pubkey(delegate)?,
+        }),
+        Some(("delegate-remove", delegate)) => Ok(CounterInstruction::RemoveDelegate {
+            delegate: parse_pubkey(delegate)?,
+        }),
+        Some(("cas", values)) => {
+            let (expected, new) = values
+                .split_once(',')
+                .ok_or_else(|| format!("Expected cas=EXPECTED,NEW - got {}", operation))?;
+            Ok(CounterInstruction::CompareAndSet {
+                expected: parse_number(expected)?,
+                new: parse_number(new)?,
+            })
+        }
+        Some(("leaderboard", capacity)) => Ok(CounterInstruction::CreateLeaderboard {
+            capacity: parse_number(capacity)?,
+        }),
+        Some(("time-series", buckets)) => {
+            let (width, count) = buckets
+                .split_once(',')
+                .ok_or_else(|| format!("Expected time-series=WIDTH,COUNT - got {}", operation))?;
+            Ok(CounterInstruction::CreateTimeSeries {
+                bucket_width: parse_bucket_width(width)?,
+                bucket_count: parse_number(count)?,
+            })
+        }
+        Some(("milestones", milestones)) => match milestones.split(',').collect::<Vec<_>>()[..] {
+            [mint, every, reward] => Ok(CounterInstruction::CreateMilestones {
+                mint: parse_pubkey(mint)?,
+                every: parse_number(every)?,
+                reward: parse_number(reward)?,
+            }),
+            _ => Err(format!(
+                "Expected milestones=MINT,EVERY,REWARD - got {}",
+                operation
+            )),
+        },
+        Some(("snapshot", label)) => Ok(CounterInstruction::Snapshot {
+            label: label.to_string(),
+        }),
+        Some(("increment-if", expected)) => Ok(CounterInstruction::IncrementIfEquals {
+            expected: parse_number(expected)?,
+        }),
+        _ => Err(format!("Unknown counter operation {}", operation)),
+    }
+}
+
+fn parse_access_mode(access_mode: &str) -> Result<AccessMode> {
+    match access_mode {
+        "open" => Ok(AccessMode::Open),
+        "authority" => Ok(AccessMode::AuthorityOnly),
+        "allowlist" => Ok(AccessMode::Allowlist),
+        _ => Err(format!("Unknown access mode {}", access_mode)),
+    }
+}
+
+//Optional [KEYPAIR_PATH [OWNER]] arguments of the authority commands - the owner
+//defaults to the payer
+fn parse_authority_args(args: &[&str]) -> Result<(Option<Keypair>, Pubkey)> {
+    let authority = match args.first() {
+        Some(keypair_path) => Some(
+            client::config::get_keypair(keypair_path)
+                .ok_or_else(|| format!("Keypair not found {}", keypair_path))?,
+        ),
+        None => None,
+    };
+    let owner = match args.get(1) {
+        Some(owner) => parse_pubkey(owner)?,
+        None => Client::get_payer_keypair()
+            .ok_or("Payer keypair not found")?
+            .pubkey(),
+    };
+    Ok((authority, owner))
+}
+
+fn print_snapshot(snapshot: &Snapshot) {
+    println!(
+        "{} {} at slot {}(unix timestamp {})",
+        snapshot.label, snapshot.value, snapshot.slot, snapshot.unix_timestamp
+    );
+}
+
+//Seconds per time series bucket
+fn parse_bucket_width(width: &str) -> Result<u32> {
+    match width {
+        "hourly" => Ok(3_600),
+        "daily" => Ok(86_400),
+        seconds => parse_number(seconds),
+    }
+}
+
+fn parse_number<T: FromStr>(number: &str) -> Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    number
+        .parse()
+        .map_err(|err| format!("Invalid number {} {}", number, err))
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<Pubkey> {
+    Pubkey::from_str(pubkey).map_err(|err| format!("Invalid pubkey {} {}", pubkey, err))
+}
@@ -0,0 +1,172 @@
+//Milestone rewards
+//
+//Once the counter authority creates the milestones of a counter - a program derived
+//account naming a reward mint - the increament taking the counter to a multiple of
+//`every` mints `reward` tokens to the associated token account of the contributor. The
+//mint authority of the reward mint must be the mint authority of the counter, a program
+//derived address only the program signs for. Increaments sent by this client pass
+//along the reward accounts, creating the token account of the actor if need be
+use crate::client::Client;
+use crate::errors::Result;
+use common::{CounterInstruction, Milestones};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use spl_token::state::Mint;
+
+impl Client {
+    //Create a mint with `decimals` whose mint authority is the mint authority of the
+    //counter derived from `owner` - ready to be the reward mint of its milestones. The
+    //payer pays the rent
+    pub fn create_reward_mint(&self, owner: &Pubkey, decimals: u8) -> Result<Pubkey> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let payer = Self::get_payer_keypair().ok_or("Payer keypair not found")?;
+        let payer_pubkey = payer.pubkey();
+        let counter_pubkey = Self::get_counter_pubkey_for(owner)?;
+        let mint_authority =
+            Milestones::find_mint_authority_address(&counter_pubkey, &program_id).0;
+        let mint = Keypair::new();
+        let rent = self
+            .client
+            .get_minimum_balance_for_rent_exemption(Mint::LEN)
+            .map_err(|err| format!("Error getting Minimum balance for rent exemption {}", err))?;
+        let instructions = [
+            system_instruction::create_account(
+                &payer_pubkey,
+                &mint.pubkey(),
+                rent,
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &mint_authority,
+                None,
+                decimals,
+            )
+            .map_err(|err| format!("Error creating mint instruction {}", err))?,
+        ];
+        let message = self.compose_message(&instructions, &payer_pubkey)?;
+        let fee_for_message = self
+            .client
+            .get_fee_for_message(&message)
+            .map_err(|err| format!("Failed getting fee for message {}", err))?;
+        self.request_airdrop(fee_for_message + rent)
+            .map_err(|err| format!("Error during airdrop {}", err))?;
+        let blockhash = message.recent_blockhash;
+        let mut transaction = Transaction::new_unsigned(message);
+        transaction
+            .try_sign(&[&payer, &mint], blockhash)
+            .map_err(|err| format!("Error signing transaction {}", err))?;
+        self.process_transaction(&transaction, None)
+            .map_err(|err| format!("Error creating reward mint {}", err))?;
+        Ok(mint.pubkey())
+    }
+
+    //Reward every `every` increaments of the counter derived from `owner` with `reward`
+    //tokens of `mint`. `authority` signs for the counter authority and pays the rent -
+    //the payer if not given
+    pub fn create_milestones(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        mint: &Pubkey,
+        every: u64,
+        reward: u64,
+    ) -> Result<()> {
+        let instruction = CounterInstruction::CreateMilestones {
+            mint: *mint,
+            every,
+            reward,
+        };
+        self.send_counter_instruction(&instruction, owner, authority)
+            .map(|_signature| ())
+            .map_err(|err| format!("Error creating milestones {}", err))
+    }
+
+    //Milestones of the counter - None if they have not been created
+    pub fn get_milestones(&self, counter_pubkey: &Pubkey) -> Result<Option<Milestones>> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let milestones_pubkey = Milestones::find_address(counter_pubkey, &program_id).0;
+        match self.client.get_account(&milestones_pubkey) {
+            Ok(account) if account.owner == program_id => Milestones::unpack(&account.data)
+                .map(Some)
+                .map_err(|err| format!("Error deserializing bytes to milestones {}", err)),
+            _ => Ok(None),
+        }
+    }
+
+    //Pass the accounts the counter needs along with an increament or a redeemed voucher -
+    //the token account
+    //of `actor` for token gated counters, the reward accounts for counters with
+    //milestones. Returns the instructions that must go ahead of the increament - creating
+    //the reward token account of `actor` at the expense of `payer`. Other instructions
+    //and counters are left as they are
+    pub(crate) fn add_increament_accounts(
+        &self,
+        counter_instruction: &CounterInstruction,
+        instruction: &mut Instruction,
+        actor: &Pubkey,
+        payer: &Pubkey,
+    ) -> Result<Vec<Instruction>> {
+        if !matches!(
+            counter_instruction,
+            CounterInstruction::Increament
+                | CounterInstruction::IncrementIfEquals { .. }
+                | CounterInstruction::RedeemVoucher { .. }
+        ) {
+            return Ok(vec![]);
+        }
+        let counter_pubkey = instruction.accounts[0].pubkey;
+        let counter = self.get_counter(&counter_pubkey)?;
+        let milestones = self.get_milestones(&counter_pubkey)?;
+        if !counter.token_gate.is_enabled() && milestones.is_none() {
+            return Ok(vec![]);
+        }
+        //Right ahead of the program config - which comes last
+        let config = instruction
+            .accounts
+            .split_off(instruction.accounts.len() - 1);
+        instruction
+            .accounts
+            .push(Self::token_account(&counter, actor));
+        let mut prerequisites = vec![];
+        if let Some(milestones) = milestones {
+            let program_id = Self::get_program_id()
+                .ok_or("Program pubkey not found! Program may not have been built")?;
+            let token_account = get_associated_token_address(actor, &milestones.mint);
+            instruction.accounts.extend([
+                AccountMeta::new_readonly(
+                    Milestones::find_address(&counter_pubkey, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new(milestones.mint, false),
+                AccountMeta::new_readonly(
+                    Milestones::find_mint_authority_address(&counter_pubkey, &program_id).0,
+                    false,
+                ),
+                AccountMeta::new(token_account, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ]);
+            if self.client.get_account(&token_account).is_err() {
+                prerequisites.push(create_associated_token_account_idempotent(
+                    payer,
+                    actor,
+                    &milestones.mint,
+                    &spl_token::id(),
+                ));
+            }
+        }
+        instruction.accounts.extend(config);
+        Ok(prerequisites)
+    }
+}
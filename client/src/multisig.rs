@@ -0,0 +1,159 @@
+//M of N multisig counter authorities
+//
+//A multisig account(owned by the counter program) lists up to `MAX_SIGNERS` signers
+//and a threshold. Made the authority of a counter(propose it, then accept it with
+//the multisig), the authority gated instructions need the signatures of at least the
+//threshold of its signers - the multisig account itself never signs
+use crate::client::Client;
+use crate::errors::Result;
+use borsh::BorshSerialize;
+use common::{CounterInstruction, Multisig, ProgramConfig};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Signature, Signer};
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+
+impl Client {
+    //Create and initialize a multisig account at a new address - returned. The payer
+    //funds its rent
+    pub fn create_multisig(&self, threshold: u8, signers: &[Pubkey]) -> Result<Pubkey> {
+        let payer = Self::get_payer_keypair().ok_or("Payer keypair not found")?;
+        let payer_pubkey = payer.pubkey();
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let multisig = Keypair::new();
+        let multisig_pubkey = multisig.pubkey();
+        let lamports = self
+            .client
+            .get_minimum_balance_for_rent_exemption(Multisig::LEN)
+            .map_err(|err| format!("Error getting Minimum balance for rent exemption {}", err))?;
+        let create = system_instruction::create_account(
+            &payer_pubkey,
+            &multisig_pubkey,
+            lamports,
+            Multisig::LEN as u64,
+            &program_id,
+        );
+        //Initialized in the same transaction - nobody else gets to initialize it
+        let mut accounts = vec![AccountMeta::new(multisig_pubkey, false)];
+        accounts.extend(
+            signers
+                .iter()
+                .map(|signer| AccountMeta::new_readonly(*signer, false)),
+        );
+        accounts.push(AccountMeta::new_readonly(
+            ProgramConfig::find_address(&program_id).0,
+            false,
+        ));
+        let data = CounterInstruction::InitializeMultisig { threshold }
+            .try_to_vec()
+            .map_err(|err| format!("Error serializing counter instruction {}", err))?;
+        let initialize = Instruction::new_with_bytes(program_id, &data, accounts);
+        let message = self.compose_message(&[create, initialize], &payer_pubkey)?;
+        let fee_for_message = self
+            .client
+            .get_fee_for_message(&message)
+            .map_err(|err| format!("Failed getting fee for message {}", err))?;
+        self.request_airdrop(fee_for_message + lamports)
+            .map_err(|err| format!("Airdrop failed while creating multisig {}", err))?;
+        let blockhash = message.recent_blockhash;
+        let transaction = Transaction::new(&[&payer, &multisig], message, blockhash);
+        self.process_transaction(&transaction, None)
+            .map_err(|err| format!("Error sending multisig creation transaction {}", err))?;
+        Ok(multisig_pubkey)
+    }
+
+    //Get the threshold and signers of the multisig account
+    pub fn get_multisig(&self, multisig_pubkey: &Pubkey) -> Result<Multisig> {
+        let account = self
+            .client
+            .get_account(multisig_pubkey)
+            .map_err(|err| format!("Error retrieving multisig account {}", err))?;
+        Multisig::unpack(&account.data)
+            .map_err(|err| format!("Error deserializing bytes to multisig {}", err))
+    }
+
+    //Build an authority gated counter instruction for the counter derived from `owner`
+    //whose authority(or pending authority, for an accept) is the multisig - or a program
+    //config instruction for a multisig admin. Any lamports the instruction hands back
+    //go to the payer
+    pub fn multisig_instruction(
+        counter_instruction: &CounterInstruction,
+        owner: &Pubkey,
+        multisig_pubkey: &Pubkey,
+        signers: &[Pubkey],
+        payer_pubkey: &Pubkey,
+    ) -> Result<Instruction> {
+        if let CounterInstruction::Increament
+        | CounterInstruction::IncrementIfEquals { .. }
+        | CounterInstruction::RedeemVoucher { .. }
+        | CounterInstruction::Initialize { .. }
+        | CounterInstruction::Migrate
+        | CounterInstruction::InitializeConfig { .. } = counter_instruction
+        {
+            return Err(format!(
+                "{:?} is not an authority gated instruction",
+                counter_instruction
+            ));
+        }
+        let mut instruction = Self::counter_instruction(counter_instruction, owner, payer_pubkey)?;
+        //Authority is always the second account
+        instruction.accounts[1] = AccountMeta::new_readonly(*multisig_pubkey, false);
+        //Signers go ahead of the program config - which comes last
+        let signers_at = match counter_instruction.is_config_instruction() {
+            true => instruction.accounts.len(),
+            false => instruction.accounts.len() - 1,
+        };
+        instruction.accounts.splice(
+            signers_at..signers_at,
+            signers
+                .iter()
+                .map(|signer| AccountMeta::new_readonly(*signer, true)),
+        );
+        Ok(instruction)
+    }
+
+    //Send an authority gated counter instruction signed by `signers` on behalf of the
+    //multisig. The payer pays the fees
+    pub fn send_multisig_instruction(
+        &self,
+        counter_instruction: &CounterInstruction,
+        owner: &Pubkey,
+        multisig_pubkey: &Pubkey,
+        signers: &[&Keypair],
+    ) -> Result<Option<Signature>> {
+        let payer = Self::get_payer_keypair().ok_or("Payer keypair not found")?;
+        let payer_pubkey = payer.pubkey();
+        let counter_pubkey = Self::get_counter_pubkey_for(owner)?;
+        let signer_pubkeys: Vec<Pubkey> = signers.iter().map(|signer| signer.pubkey()).collect();
+        let instruction = Self::multisig_instruction(
+            counter_instruction,
+            owner,
+            multisig_pubkey,
+            &signer_pubkeys,
+            &payer_pubkey,
+        )?;
+        let message = self.compose_message(&[instruction], &payer_pubkey)?;
+        let fee_for_message = self
+            .client
+            .get_fee_for_message(&message)
+            .map_err(|err| format!("Failed getting fee for message {}", err))?;
+        self.request_airdrop(fee_for_message)
+            .map_err(|err| format!("Error during airdrop {}", err))?;
+        let blockhash = message.recent_blockhash;
+        let mut all_signers = vec![&payer];
+        all_signers.extend(
+            signers
+                .iter()
+                .copied()
+                .filter(|signer| signer.pubkey() != payer_pubkey),
+        );
+        let mut transaction = Transaction::new_unsigned(message);
+        transaction
+            .try_sign(&all_signers, blockhash)
+            .map_err(|err| format!("Error signing transaction {}", err))?;
+        self.process_transaction(&transaction, Some(&counter_pubkey))
+    }
+}
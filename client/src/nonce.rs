@@ -0,0 +1,110 @@
+//Durable nonce accounts
+//
+//A transaction built with a recent blockhash expires in about a minute. A nonce
+//account stores a durable blockhash that transactions can use instead - it stays
+//valid until the nonce is advanced, which every transaction using it does first
+
+use crate::client::Client;
+use crate::errors::Result;
+use solana_client::nonce_utils;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::nonce::state::Data;
+use solana_sdk::nonce::State;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+
+//Message for the instructions that uses the blockhash stored in the nonce account - with
+//advancing the nonce put first. The nonce must be advanced by `nonce_authority`
+pub fn nonce_message(
+    instructions: &[Instruction],
+    payer_pubkey: &Pubkey,
+    nonce_pubkey: &Pubkey,
+    nonce_authority: &Pubkey,
+    nonce_data: &Data,
+) -> Result<Message> {
+    if nonce_data.authority != *nonce_authority {
+        return Err(format!(
+            "{} is not the authority of nonce account {}",
+            nonce_authority, nonce_pubkey
+        ));
+    }
+    let mut message = Message::new_with_nonce(
+        instructions.to_vec(),
+        Some(payer_pubkey),
+        nonce_pubkey,
+        nonce_authority,
+    );
+    message.recent_blockhash = nonce_data.blockhash();
+    Ok(message)
+}
+
+impl Client {
+    //Create a nonce account at the address of `nonce_keypair` that `authority` can
+    //advance. The payer funds the rent exempt balance
+    pub fn create_nonce_account(&self, nonce_keypair: &Keypair, authority: &Pubkey) -> Result<()> {
+        let payer = Self::get_payer_keypair().ok_or("Payer keypair not found")?;
+        let payer_pubkey = payer.pubkey();
+        let nonce_pubkey = nonce_keypair.pubkey();
+        if self.client.get_account(&nonce_pubkey).is_ok() {
+            return Err(format!("Nonce account {} already exists", nonce_pubkey));
+        }
+        let lamports = self
+            .client
+            .get_minimum_balance_for_rent_exemption(State::size())
+            .map_err(|err| format!("Error getting Minimum balance for rent exemption {}", err))?;
+        let instructions = system_instruction::create_nonce_account(
+            &payer_pubkey,
+            &nonce_pubkey,
+            authority,
+            lamports,
+        );
+        let message = self.compose_message(&instructions, &payer_pubkey)?;
+        let fee_for_message = self
+            .client
+            .get_fee_for_message(&message)
+            .map_err(|err| format!("Failed getting fee for message {}", err))?;
+        self.request_airdrop(fee_for_message + lamports)
+            .map_err(|err| format!("Airdrop failed while creating nonce account {}", err))?;
+        let blockhash = message.recent_blockhash;
+        let transaction = Transaction::new(&[&payer, nonce_keypair], message, blockhash);
+        self.process_transaction(&transaction, None)
+            .map_err(|err| format!("Error sending nonce account creation transaction {}", err))?;
+        println!("Nonce account {} created", nonce_pubkey);
+        Ok(())
+    }
+
+    //Advance the nonce. Transactions signed with the current durable nonce can not be
+    //executed afterwards. The payer must be the nonce authority
+    pub fn advance_nonce_account(&self, nonce_pubkey: &Pubkey) -> Result<()> {
+        let payer = Self::get_payer_keypair().ok_or("Payer keypair not found")?;
+        let payer_pubkey = payer.pubkey();
+        let instruction = system_instruction::advance_nonce_account(nonce_pubkey, &payer_pubkey);
+        //The nonce is advanced by the instruction itself - so a recent blockhash is used
+        //even if the client is configured with a nonce account
+        let blockhash = self
+            .client
+            .get_latest_blockhash()
+            .map_err(|err| format!("Error retrieving latest block hash {}", err))?;
+        let message = Message::new_with_blockhash(&[instruction], Some(&payer_pubkey), &blockhash);
+        let transaction = Transaction::new(&[&payer], message, blockhash);
+        self.process_transaction(&transaction, None)
+            .map(|_signature| ())
+            .map_err(|err| format!("Error sending advance nonce transaction {}", err))
+    }
+
+    //Get the nonce account state - the authority and the durable blockhash in use
+    pub fn get_nonce_data(&self, nonce_pubkey: &Pubkey) -> Result<Data> {
+        let nonce_account = nonce_utils::get_account_with_commitment(
+            &self.client,
+            nonce_pubkey,
+            self.client.commitment(),
+        )
+        .map_err(|err| format!("Error retrieving nonce account {}", err))?;
+        nonce_utils::data_from_account(&nonce_account)
+            .map_err(|err| format!("Invalid nonce account {} {}", nonce_pubkey, err))
+    }
+}
@@ -0,0 +1,155 @@
+//Offline signing of counter transactions
+//
+//Keys that live on an air-gapped machine can not sign a transaction built with a
+//recent blockhash in time - it expires in about a minute. So the online machine
+//builds an unsigned transaction that uses a durable nonce instead, the offline
+//machine signs the message and hands back detached signatures(`PUBKEY=SIGNATURE`)
+//which are then put in place and the transaction is broadcast whenever convenient
+
+use crate::client::Client;
+use crate::errors::Result;
+use crate::nonce::nonce_message;
+use common::CounterInstruction;
+use solana_sdk::bs58;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signature::Signer;
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::transaction::Transaction;
+use std::str::FromStr;
+
+//How transactions are encoded when passed between the online and offline machines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionEncoding {
+    Base58,
+    Base64,
+}
+
+//Serialize a(possibly unsigned) transaction to text
+pub fn encode_transaction(
+    transaction: &Transaction,
+    encoding: TransactionEncoding,
+) -> Result<String> {
+    let bytes = bincode::serialize(transaction)
+        .map_err(|err| format!("Error serializing transaction {}", err))?;
+    Ok(match encoding {
+        TransactionEncoding::Base58 => bs58::encode(bytes).into_string(),
+        TransactionEncoding::Base64 => base64::encode(bytes),
+    })
+}
+
+//Deserialize a transaction from text produced by `encode_transaction`
+pub fn decode_transaction(encoded: &str, encoding: TransactionEncoding) -> Result<Transaction> {
+    let bytes = match encoding {
+        TransactionEncoding::Base58 => bs58::decode(encoded.trim())
+            .into_vec()
+            .map_err(|err| format!("Invalid base58 transaction {}", err))?,
+        TransactionEncoding::Base64 => base64::decode(encoded.trim())
+            .map_err(|err| format!("Invalid base64 transaction {}", err))?,
+    };
+    bincode::deserialize(&bytes).map_err(|err| format!("Error deserializing transaction {}", err))
+}
+
+//Parse a detached signature given as `PUBKEY=SIGNATURE` - same as the solana cli
+pub fn parse_signer_signature(signer_signature: &str) -> Result<(Pubkey, Signature)> {
+    let (pubkey, signature) = signer_signature
+        .split_once('=')
+        .ok_or_else(|| format!("Expected PUBKEY=SIGNATURE, found {}", signer_signature))?;
+    let pubkey =
+        Pubkey::from_str(pubkey).map_err(|err| format!("Invalid signer {} {}", pubkey, err))?;
+    let signature = Signature::from_str(signature)
+        .map_err(|err| format!("Invalid signature {} {}", signature, err))?;
+    Ok((pubkey, signature))
+}
+
+impl Client {
+    //Build an unsigned counter transaction for the counter owned by `owner`, who also
+    //pays the fees and signs as the counter authority. The blockhash stored in the
+    //nonce account is used in place of a recent blockhash and advancing the nonce
+    //becomes the first instruction
+    pub fn create_offline_transaction(
+        &self,
+        counter_instruction: &CounterInstruction,
+        owner: &Pubkey,
+        nonce_pubkey: &Pubkey,
+        nonce_authority: &Pubkey,
+    ) -> Result<Transaction> {
+        let instruction = Self::counter_instruction(counter_instruction, owner, owner)?;
+        let nonce_data = self.get_nonce_data(nonce_pubkey)?;
+        let message = nonce_message(
+            &[instruction],
+            owner,
+            nonce_pubkey,
+            nonce_authority,
+            &nonce_data,
+        )?;
+        Ok(Transaction::new_unsigned(message))
+    }
+
+    //Sign the transaction message on the offline machine - does not need the cluster
+    pub fn sign_offline(
+        transaction: &Transaction,
+        keypair: &Keypair,
+    ) -> Result<(Pubkey, Signature)> {
+        let pubkey = keypair.pubkey();
+        if !transaction.message.signer_keys().contains(&&pubkey) {
+            return Err(format!(
+                "{} is not a required signer of the transaction",
+                pubkey
+            ));
+        }
+        Ok((pubkey, keypair.sign_message(&transaction.message_data())))
+    }
+
+    //Put the detached signatures in place. Fails if a required signature is missing
+    //or any signature does not verify
+    pub fn assemble_transaction(
+        mut transaction: Transaction,
+        signatures: &[(Pubkey, Signature)],
+    ) -> Result<Transaction> {
+        let signer_keys: Vec<Pubkey> = transaction
+            .message
+            .signer_keys()
+            .into_iter()
+            .copied()
+            .collect();
+        for (pubkey, signature) in signatures {
+            let index = signer_keys
+                .iter()
+                .position(|signer| signer == pubkey)
+                .ok_or_else(|| format!("{} is not a required signer of the transaction", pubkey))?;
+            transaction.signatures[index] = *signature;
+        }
+        if let Some((signer, _)) = signer_keys
+            .iter()
+            .zip(&transaction.signatures)
+            .find(|(_, signature)| **signature == Signature::default())
+        {
+            return Err(format!("Missing signature for {}", signer));
+        }
+        transaction
+            .verify()
+            .map_err(|err| format!("Transaction signature verification failed {}", err))?;
+        Ok(transaction)
+    }
+
+    //Send an assembled offline transaction(simulated in dry run mode)
+    pub fn broadcast_transaction(&self, transaction: &Transaction) -> Result<()> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        //Counter account is the first account of the counter program instruction
+        let message = &transaction.message;
+        let counter_pubkey = message
+            .instructions
+            .iter()
+            .find(|instruction| {
+                message.account_keys[instruction.program_id_index as usize] == program_id
+            })
+            .and_then(|instruction| instruction.accounts.first())
+            .map(|index| message.account_keys[*index as usize])
+            .ok_or("Transaction does not carry a counter instruction")?;
+        self.process_transaction(transaction, Some(&counter_pubkey))
+            .map(|_signature| ())
+            .map_err(|err| format!("Error broadcasting offline transaction {}", err))
+    }
+}
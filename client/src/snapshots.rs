@@ -0,0 +1,82 @@
+//Counter values as of given moments
+//
+//The counter authority can snapshot the counter under a label - the program records the
+//value along with the slot and the unix timestamp in a program derived account that is
+//never changed afterwards. Rewards can then be settled against it
+use crate::client::Client;
+use crate::errors::Result;
+use borsh::BorshSerialize;
+use common::{AccountHeader, AccountType, CounterInstruction, Snapshot};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+
+impl Client {
+    //Snapshot the counter derived from `owner` under `label`. `authority` signs for the
+    //counter authority and pays the rent - the payer if not given
+    pub fn take_snapshot(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        label: &str,
+    ) -> Result<()> {
+        let instruction = CounterInstruction::Snapshot {
+            label: label.to_string(),
+        };
+        self.send_counter_instruction(&instruction, owner, authority)
+            .map(|_signature| ())
+            .map_err(|err| format!("Error taking snapshot {}", err))
+    }
+
+    //Snapshot of the counter labelled `label` - None if there is none
+    pub fn get_snapshot(&self, counter_pubkey: &Pubkey, label: &str) -> Result<Option<Snapshot>> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let snapshot_pubkey = Snapshot::find_address(counter_pubkey, label, &program_id).0;
+        match self.client.get_account(&snapshot_pubkey) {
+            Ok(account) if account.owner == program_id => Snapshot::unpack(&account.data)
+                .map(Some)
+                .map_err(|err| format!("Error deserializing bytes to snapshot {}", err)),
+            _ => Ok(None),
+        }
+    }
+
+    //Snapshots of the counter, oldest first - looked up by their header and counter
+    //address
+    pub fn get_snapshots(&self, counter_pubkey: &Pubkey) -> Result<Vec<Snapshot>> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let header = AccountHeader {
+            account_type: AccountType::Snapshot,
+            version: Snapshot::VERSION,
+        }
+        .try_to_vec()
+        .map_err(|err| format!("Error serializing snapshot header {}", err))?;
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, header)),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    AccountHeader::LEN,
+                    counter_pubkey.to_bytes().to_vec(),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self
+            .client
+            .get_program_accounts_with_config(&program_id, config)
+            .map_err(|err| format!("Error retrieving snapshot accounts {}", err))?;
+        let mut snapshots: Vec<Snapshot> = accounts
+            .into_iter()
+            .filter_map(|(_pubkey, account)| Snapshot::unpack(&account.data).ok())
+            .collect();
+        snapshots.sort_by_key(|snapshot| snapshot.slot);
+        Ok(snapshots)
+    }
+}
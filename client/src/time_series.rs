@@ -0,0 +1,55 @@
+//Increaments per hour, day or any other time bucket
+//
+//Once the counter authority creates the time series of a counter - a program derived
+//account holding a ring buffer of buckets - every increament is counted in the bucket
+//of the current cluster time. Buckets roll over as time goes by, so only the latest
+//ones are kept
+use crate::client::Client;
+use crate::errors::Result;
+use common::{CounterInstruction, TimeSeries};
+use solana_sdk::account::from_account;
+use solana_sdk::clock::Clock;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::sysvar;
+
+impl Client {
+    //Start counting the increaments of the counter derived from `owner` per
+    //`bucket_width` seconds over the last `bucket_count` buckets. `authority` signs for
+    //the counter authority and pays the rent - the payer if not given
+    pub fn create_time_series(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        bucket_width: u32,
+        bucket_count: u16,
+    ) -> Result<()> {
+        let instruction = CounterInstruction::CreateTimeSeries {
+            bucket_width,
+            bucket_count,
+        };
+        self.send_counter_instruction(&instruction, owner, authority)
+            .map(|_signature| ())
+            .map_err(|err| format!("Error creating time series {}", err))
+    }
+
+    //Bucket starts(unix timestamps) and the increaments within them as of the current
+    //cluster time, oldest first - None if the counter has no time series
+    pub fn get_time_series(&self, counter_pubkey: &Pubkey) -> Result<Option<Vec<(i64, u64)>>> {
+        let program_id = Self::get_program_id()
+            .ok_or("Program pubkey not found! Program may not have been built")?;
+        let time_series_pubkey = TimeSeries::find_address(counter_pubkey, &program_id).0;
+        let time_series = match self.client.get_account(&time_series_pubkey) {
+            Ok(account) if account.owner == program_id => TimeSeries::unpack(&account.data)
+                .map_err(|err| format!("Error deserializing bytes to time series {}", err))?,
+            _ => return Ok(None),
+        };
+        let clock = self
+            .client
+            .get_account(&sysvar::clock::id())
+            .ok()
+            .and_then(|account| from_account::<Clock, _>(&account))
+            .ok_or("Error retrieving the cluster clock")?;
+        Ok(Some(time_series.series(clock.unix_timestamp)))
+    }
+}
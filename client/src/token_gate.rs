@@ -0,0 +1,41 @@
+//Token gated counters
+//
+//The authority may restrict increaments to holders of an SPL token - at least a
+//minimum balance of a mint. Increaments of gated counters pass along the associated
+//token account of the actor for the mint, whose owner, mint and amount the program
+//checks
+use crate::client::Client;
+use crate::errors::Result;
+use common::{Counter, CounterInstruction, TokenGate};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::system_program;
+use spl_associated_token_account::get_associated_token_address;
+
+impl Client {
+    //Set the token gate of the counter derived from `owner` - `TokenGate::default()`
+    //lifts it. `authority` signs for the counter authority - the payer if not given
+    pub fn set_token_gate(
+        &self,
+        owner: &Pubkey,
+        authority: Option<&Keypair>,
+        token_gate: TokenGate,
+    ) -> Result<()> {
+        let instruction = CounterInstruction::SetTokenGate { token_gate };
+        self.send_counter_instruction(&instruction, owner, authority)
+            .map(|_signature| ())
+            .map_err(|err| format!("Error setting token gate {}", err))
+    }
+
+    //Token account of `actor` an increament passes along - its associated token account
+    //for the gate mint. Counters that are not token gated get the system program in
+    //its place
+    pub(crate) fn token_account(counter: &Counter, actor: &Pubkey) -> AccountMeta {
+        let token_account = match counter.token_gate.is_enabled() {
+            true => get_associated_token_address(actor, &counter.token_gate.mint),
+            false => system_program::id(),
+        };
+        AccountMeta::new_readonly(token_account, false)
+    }
+}
@@ -0,0 +1,143 @@
+//Increaments approved off-chain
+//
+//The counter authority(a backend, say) issues vouchers - signed approvals to increament
+//the counter by an amount - without sending anything. Whoever redeems a voucher sends
+//it along with an Ed25519 program instruction verifying the signature, and pays for
+//the transaction. Each voucher nonce is redeemed once, before the voucher expires
+use crate::client::Client;
+use crate::errors::Result;
+use common::{CounterInstruction, Voucher};
+use solana_sdk::ed25519_program;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Signature, Signer};
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::transaction::Transaction;
+
+//A voucher along with its issuer's signature - and the owner the counter address is
+//derived from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedVoucher {
+    pub owner: Pubkey,
+    pub voucher: Voucher,
+    pub issuer: Pubkey,
+    pub signature: Signature,
+}
+
+impl SignedVoucher {
+    const LEN: usize = 32 + 32 + 8 + 8 + 8 + 32 + 64;
+
+    //Base64 text to hand over to the redeemer
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(Self::LEN);
+        bytes.extend_from_slice(self.owner.as_ref());
+        bytes.extend_from_slice(&self.voucher.message());
+        bytes.extend_from_slice(self.issuer.as_ref());
+        bytes.extend_from_slice(self.signature.as_ref());
+        base64::encode(bytes)
+    }
+
+    pub fn decode(encoded: &str) -> Result<Self> {
+        let bytes =
+            base64::decode(encoded).map_err(|err| format!("Error decoding voucher {}", err))?;
+        if bytes.len() != Self::LEN {
+            return Err(format!("Voucher of {} bytes expected", Self::LEN));
+        }
+        let pubkey = |at: usize| Pubkey::new_from_array(bytes[at..at + 32].try_into().unwrap());
+        let number = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        Ok(Self {
+            owner: pubkey(0),
+            voucher: Voucher {
+                counter: pubkey(32),
+                amount: number(64),
+                nonce: number(72),
+                expiry: number(80) as i64,
+            },
+            issuer: pubkey(88),
+            signature: Signature::from(<[u8; 64]>::try_from(&bytes[120..]).unwrap()),
+        })
+    }
+}
+
+impl Client {
+    //Approve increamenting the counter derived from `owner` by `amount` - redeemable
+    //once per `nonce`, until `expiry`(unix timestamp). The issuer must be the counter
+    //authority. Nothing is sent
+    pub fn issue_voucher(
+        owner: &Pubkey,
+        issuer: &Keypair,
+        amount: u64,
+        nonce: u64,
+        expiry: i64,
+    ) -> Result<SignedVoucher> {
+        let voucher = Voucher {
+            counter: Self::get_counter_pubkey_for(owner)?,
+            amount,
+            nonce,
+            expiry,
+        };
+        Ok(SignedVoucher {
+            owner: *owner,
+            voucher,
+            issuer: issuer.pubkey(),
+            signature: issuer.sign_message(&voucher.message()),
+        })
+    }
+
+    //Redeem the voucher - `redeemer` pays the rent for the redeemed voucher record and
+    //any counter fee, the payer if not given
+    pub fn redeem_voucher(
+        &self,
+        signed_voucher: &SignedVoucher,
+        redeemer: Option<&Keypair>,
+    ) -> Result<()> {
+        let payer = Self::get_payer_keypair().ok_or("Payer keypair not found")?;
+        let payer_pubkey = payer.pubkey();
+        let redeemer = redeemer.unwrap_or(&payer);
+        let voucher = &signed_voucher.voucher;
+        let signature: [u8; 64] = signed_voucher
+            .signature
+            .as_ref()
+            .try_into()
+            .map_err(|_| "Voucher signature of 64 bytes expected")?;
+        //The program looks for the verification right ahead of the redeem instruction
+        let verify = Instruction::new_with_bytes(
+            ed25519_program::id(),
+            &voucher.ed25519_instruction_data(&signed_voucher.issuer, &signature),
+            vec![],
+        );
+        let redeem_voucher = CounterInstruction::RedeemVoucher {
+            amount: voucher.amount,
+            nonce: voucher.nonce,
+            expiry: voucher.expiry,
+        };
+        let mut redeem =
+            Self::counter_instruction(&redeem_voucher, &signed_voucher.owner, &redeemer.pubkey())?;
+        let mut instructions = self.add_increament_accounts(
+            &redeem_voucher,
+            &mut redeem,
+            &redeemer.pubkey(),
+            &payer_pubkey,
+        )?;
+        instructions.extend([verify, redeem]);
+        let message = self.compose_message(&instructions, &payer_pubkey)?;
+        let fee_for_message = self
+            .client
+            .get_fee_for_message(&message)
+            .map_err(|err| format!("Failed getting fee for message {}", err))?;
+        self.request_airdrop(fee_for_message)
+            .map_err(|err| format!("Error during airdrop {}", err))?;
+        let blockhash = message.recent_blockhash;
+        let mut transaction = Transaction::new_unsigned(message);
+        let signers = match redeemer.pubkey() == payer_pubkey {
+            true => vec![&payer],
+            false => vec![&payer, redeemer],
+        };
+        transaction
+            .try_sign(&signers, blockhash)
+            .map_err(|err| format!("Error signing transaction {}", err))?;
+        self.process_transaction(&transaction, Some(&voucher.counter))
+            .map(|_signature| ())
+            .map_err(|err| format!("Error redeeming voucher {}", err))
+    }
+}
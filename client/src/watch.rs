@@ -0,0 +1,176 @@
+//Live counter updates over a websocket account subscription
+//
+//Instead of polling, the counter account is subscribed to with
+//`PubsubClient::account_subscribe` and every change is decoded into a `Counter`.
+//When the websocket drops the subscription is set up again
+
+use crate::client::Client;
+use crate::config;
+use crate::errors::Result;
+use common::Counter;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::pubsub_client::{AccountSubscription, PubsubClient};
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::thread;
+use std::time::Duration;
+
+//Wait before trying to subscribe again after the websocket dropped - doubled with
+//every attempt that fails in a row, up to the maximum
+static RECONNECT_DELAY: Duration = Duration::from_secs(2);
+static MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+//A change to the counter account
+#[derive(Debug, Clone)]
+pub struct CounterUpdate {
+    pub slot: u64,
+    pub counter: Counter,
+    //Change from the previously seen value - None for the very first reading
+    pub delta: Option<i128>,
+}
+
+//Stream of counter updates - iterate over it to receive them as they happen. It
+//never ends on its own, dropped websocket connections are re-established
+pub struct CounterWatch {
+    websocket_url: String,
+    counter_pubkey: Pubkey,
+    commitment: CommitmentConfig,
+    subscription: Option<AccountSubscription>,
+    last_count: Option<u64>,
+    //Reconnects in a row that did not get an update through
+    reconnects: u32,
+}
+
+//How long to wait before the next reconnect, after `reconnects` of them in a row
+//did not get an update through
+pub fn reconnect_delay(reconnects: u32) -> Duration {
+    RECONNECT_DELAY
+        .checked_mul(2u32.saturating_pow(reconnects))
+        .map_or(MAX_RECONNECT_DELAY, |delay| delay.min(MAX_RECONNECT_DELAY))
+}
+
+//Websocket url derived from the json rpc url the same way the solana cli does: ws(s)
+//scheme and the port next to the rpc port
+pub fn websocket_url_for(json_rpc_url: &str) -> String {
+    let (scheme, rest) = json_rpc_url
+        .split_once("://")
+        .unwrap_or(("http", json_rpc_url));
+    let scheme = match scheme {
+        "https" => "wss",
+        _ => "ws",
+    };
+    let (host, path) = match rest.split_once('/') {
+        Some((host, path)) => (host, format!("/{}", path)),
+        None => (rest, String::new()),
+    };
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) => match port.parse::<u16>() {
+            Ok(port) => format!("{}:{}", name, port.saturating_add(1)),
+            Err(_) => host.to_string(),
+        },
+        None => host.to_string(),
+    };
+    format!("{}://{}{}", scheme, host, path)
+}
+
+impl CounterWatch {
+    //Back off before the next reconnect
+    fn wait_to_reconnect(&mut self) {
+        thread::sleep(reconnect_delay(self.reconnects));
+        self.reconnects = self.reconnects.saturating_add(1);
+    }
+
+    fn subscribe(&mut self) -> Result<()> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.commitment),
+            ..RpcAccountInfoConfig::default()
+        };
+        let subscription = PubsubClient::account_subscribe(
+            &self.websocket_url,
+            &self.counter_pubkey,
+            Some(config),
+        )
+        .map_err(|err| format!("Error subscribing to counter account {}", err))?;
+        self.subscription = Some(subscription);
+        Ok(())
+    }
+
+    fn update(&mut self, slot: u64, account: Option<Account>) -> Result<CounterUpdate> {
+        let account = account.ok_or("Error decoding counter account")?;
+        let counter = Counter::unpack(&account.data)
+            .map_err(|err| format!("Error deserializing bytes to counter {}", err))?;
+        let delta = self
+            .last_count
+            .map(|last_count| counter.count as i128 - last_count as i128);
+        self.last_count = Some(counter.count);
+        Ok(CounterUpdate {
+            slot,
+            counter,
+            delta,
+        })
+    }
+}
+
+impl Iterator for CounterWatch {
+    type Item = Result<CounterUpdate>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let receiver = match self.subscription {
+                Some((_, ref receiver)) => receiver,
+                None => {
+                    if let Err(err) = self.subscribe() {
+                        eprintln!("{} - retrying", err);
+                        self.wait_to_reconnect();
+                    }
+                    continue;
+                }
+            };
+            match receiver.recv() {
+                Ok(response) => {
+                    self.reconnects = 0;
+                    let account = response.value.decode::<Account>();
+                    return Some(self.update(response.context.slot, account));
+                }
+                //Sending side is gone once the websocket connection drops
+                Err(_) => {
+                    eprintln!("Websocket connection dropped - reconnecting");
+                    self.subscription = None;
+                    self.wait_to_reconnect();
+                }
+            }
+        }
+    }
+}
+
+impl Client {
+    //Watch the counter account for changes. The current value is read first so that
+    //the first update already carries a delta
+    pub fn watch_counter(&self, counter_pubkey: &Pubkey) -> Result<CounterWatch> {
+        let last_count = self
+            .client
+            .get_account(counter_pubkey)
+            .ok()
+            .and_then(|account| Counter::unpack(&account.data).ok())
+            .map(|counter| counter.count);
+        Ok(CounterWatch {
+            websocket_url: Self::get_websocket_url(self.client.url()),
+            counter_pubkey: *counter_pubkey,
+            commitment: self.client.commitment(),
+            subscription: None,
+            last_count,
+            reconnects: 0,
+        })
+    }
+
+    //Websocket url from the solana cli config - or else derived from the json rpc url
+    fn get_websocket_url(json_rpc_url: String) -> String {
+        match config::get_config("websocket_url") {
+            Some(websocket_url) if !websocket_url.is_empty() => websocket_url,
+            _ => websocket_url_for(&json_rpc_url),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//Packing of counter operations into transactions
+
+use client::Client;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::message::Message;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+
+//An instruction against a counter of its own carrying `data_len` bytes
+fn instruction(program_id: &Pubkey, data_len: usize) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &vec![0; data_len],
+        vec![AccountMeta::new(Pubkey::new_unique(), false)],
+    )
+}
+
+fn transaction_size(instructions: &[Instruction], payer_pubkey: &Pubkey) -> usize {
+    let transaction = Transaction::new_unsigned(Message::new(instructions, Some(payer_pubkey)));
+    bincode::serialized_size(&transaction).unwrap() as usize
+}
+
+#[test]
+fn transactions_fit_up_to_the_packet_size() {
+    let (program_id, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let data_len = 200;
+    let size = transaction_size(&[instruction(&program_id, data_len)], &payer);
+    //Exactly as big as a packet
+    let data_len = data_len + PACKET_DATA_SIZE - size;
+    let largest = instruction(&program_id, data_len);
+    assert_eq!(
+        transaction_size(std::slice::from_ref(&largest), &payer),
+        PACKET_DATA_SIZE
+    );
+    assert_eq!(Client::fits_in_transaction(&[largest], &payer), Ok(true));
+    let too_large = instruction(&program_id, data_len + 1);
+    assert_eq!(Client::fits_in_transaction(&[too_large], &payer), Ok(false));
+}
+
+#[test]
+fn packs_consecutive_runs_below_the_packet_size() {
+    let (program_id, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let instructions: Vec<_> = (0..20)
+        .map(|index| instruction(&program_id, 50 + index * 10))
+        .collect();
+    let batches = Client::pack_instructions(&instructions, &payer).unwrap();
+    assert!(batches.len() > 1);
+    assert_eq!(batches.first().unwrap().start, 0);
+    assert_eq!(batches.last().unwrap().end, instructions.len());
+    for (batch, next) in batches.iter().zip(batches.iter().skip(1)) {
+        assert_eq!(batch.end, next.start);
+        //Split where the next instruction would not have fit
+        assert_eq!(
+            Client::fits_in_transaction(&instructions[batch.start..batch.end + 1], &payer),
+            Ok(false)
+        );
+    }
+    for batch in &batches {
+        assert!(transaction_size(&instructions[batch.clone()], &payer) <= PACKET_DATA_SIZE);
+    }
+    //Nothing to pack
+    assert_eq!(Client::pack_instructions(&[], &payer), Ok(vec![]));
+}
+
+#[test]
+fn instructions_too_large_for_a_transaction_are_rejected() {
+    let (program_id, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let instructions = vec![
+        instruction(&program_id, 10),
+        instruction(&program_id, PACKET_DATA_SIZE),
+        instruction(&program_id, 10),
+    ];
+    assert!(Client::pack_instructions(&instructions, &payer).is_err());
+    assert!(Client::pack_instructions(&instructions[..1], &payer).is_ok());
+}
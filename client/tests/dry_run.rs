@@ -0,0 +1,92 @@
+//What a dry run reports of a simulated transaction
+
+use client::Simulation;
+use common::{Counter, CounterError, CounterEvent};
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+
+fn counter(count: u64) -> Counter {
+    Counter {
+        count,
+        ..Counter::new(Pubkey::new_unique())
+    }
+}
+
+fn simulation(err: Option<TransactionError>) -> Simulation {
+    Simulation {
+        err,
+        logs: vec!["Program log: Counter reset".to_string()],
+        units_consumed: Some(1_200),
+        events: vec![CounterEvent::Reset {
+            counter: Pubkey::default(),
+            previous_value: 3,
+            actor: Pubkey::default(),
+        }],
+        counter: Some(counter(0)),
+        returned_counter: Some(counter(0)),
+    }
+}
+
+#[test]
+fn successful_simulations_report_events_and_counter_state() {
+    let simulation = simulation(None);
+    assert_eq!(
+        simulation.report(true),
+        vec![
+            "Program logs:".to_string(),
+            "  Program log: Counter reset".to_string(),
+            "Compute units consumed 1200".to_string(),
+            format!("Event {:?}", simulation.events[0]),
+            "Counter value after transaction 0".to_string(),
+            "Counter value returned by the program 0".to_string(),
+        ]
+    );
+    assert_eq!(simulation.error(), None);
+    //The counter account state was not asked for
+    assert!(!simulation
+        .report(false)
+        .contains(&"Counter value after transaction 0".to_string()));
+}
+
+#[test]
+fn missing_state_is_reported_as_such() {
+    let simulation = Simulation {
+        units_consumed: None,
+        counter: None,
+        returned_counter: None,
+        events: vec![],
+        ..simulation(None)
+    };
+    assert_eq!(
+        simulation.report(true)[2..],
+        [
+            "Compute units consumed not reported by the cluster".to_string(),
+            "Counter account state not returned by simulation".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn failed_simulations_report_the_logs_and_the_error() {
+    let code = CounterError::OutOfBounds as u32;
+    let simulation = simulation(Some(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(code),
+    )));
+    assert_eq!(simulation.report(true).len(), 3);
+    let err = simulation.error().unwrap();
+    assert!(err.starts_with(&format!("Simulation failed {}", CounterError::OutOfBounds)));
+    //Errors of other programs go by their transaction error
+    let simulation = Simulation {
+        err: Some(TransactionError::AccountNotFound),
+        ..simulation
+    };
+    assert_eq!(
+        simulation.error(),
+        Some(format!(
+            "Simulation failed {}",
+            TransactionError::AccountNotFound
+        ))
+    );
+}
@@ -0,0 +1,124 @@
+//Counter events out of transaction logs
+
+use borsh::BorshSerialize;
+use client::decode_events;
+use common::CounterEvent;
+use solana_sdk::pubkey::Pubkey;
+
+fn reset(counter: Pubkey, previous_value: u64) -> CounterEvent {
+    CounterEvent::Reset {
+        counter,
+        previous_value,
+        actor: Pubkey::new_unique(),
+    }
+}
+
+//The log line `sol_log_data` writes for the event
+fn data_line(event: &CounterEvent) -> String {
+    format!(
+        "Program data: {}",
+        base64::encode(event.try_to_vec().unwrap())
+    )
+}
+
+//Logs of `program_id` invoked at `depth`, logging `lines`
+fn invocation(program_id: &Pubkey, depth: u8, lines: Vec<String>) -> Vec<String> {
+    let mut logs = vec![format!("Program {} invoke [{}]", program_id, depth)];
+    logs.extend(lines);
+    logs.push(format!("Program {} success", program_id));
+    logs
+}
+
+#[test]
+fn decodes_program_data_lines() {
+    let (program_id, counter) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let events = vec![reset(counter, 3), reset(counter, 0)];
+    let logs = invocation(
+        &program_id,
+        1,
+        vec![
+            "Program log: Counter reset".to_string(),
+            data_line(&events[0]),
+            data_line(&events[1]),
+        ],
+    );
+    assert_eq!(decode_events(&logs, &program_id), events);
+    //Nothing logged
+    assert!(decode_events(&[], &program_id).is_empty());
+}
+
+#[test]
+fn ignores_data_of_other_programs() {
+    let (program_id, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let event = reset(Pubkey::new_unique(), 3);
+    //Another program logging what looks like a counter event - top level and invoked
+    //by the counter program
+    let mut logs = invocation(&other, 1, vec![data_line(&reset(Pubkey::new_unique(), 1))]);
+    let inner = invocation(&other, 2, vec![data_line(&reset(Pubkey::new_unique(), 2))]);
+    logs.extend(invocation(
+        &program_id,
+        1,
+        [inner, vec![data_line(&event)]].concat(),
+    ));
+    assert_eq!(decode_events(&logs, &program_id), vec![event]);
+    //Nor is anything logged once the counter program failed
+    let logs = vec![
+        format!("Program {} invoke [1]", program_id),
+        data_line(&reset(Pubkey::new_unique(), 4)),
+        format!("Program {} failed: custom program error: 0x1", program_id),
+        data_line(&reset(Pubkey::new_unique(), 5)),
+    ];
+    assert_eq!(decode_events(&logs, &program_id).len(), 1);
+}
+
+#[test]
+fn skips_malformed_data() {
+    let program_id = Pubkey::new_unique();
+    let event = reset(Pubkey::new_unique(), 3);
+    let mut truncated = event.try_to_vec().unwrap();
+    truncated.pop();
+    let logs = invocation(
+        &program_id,
+        1,
+        vec![
+            "Program data: not*base64".to_string(),
+            format!("Program data: {}", base64::encode(truncated)),
+            format!("Program data: {}", base64::encode([u8::MAX; 8])),
+            data_line(&event),
+        ],
+    );
+    assert_eq!(decode_events(&logs, &program_id), vec![event]);
+}
+
+#[test]
+fn tracks_the_counter_across_nested_invocations() {
+    let (program_id, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (before, after) = (
+        reset(Pubkey::new_unique(), 1),
+        reset(Pubkey::new_unique(), 2),
+    );
+    //The counter invokes another program between its events - the lines after the
+    //inner success are the counter's again
+    let inner = invocation(&other, 2, vec![data_line(&reset(Pubkey::new_unique(), 3))]);
+    let logs = invocation(
+        &program_id,
+        1,
+        [vec![data_line(&before)], inner, vec![data_line(&after)]].concat(),
+    );
+    assert_eq!(
+        decode_events(&logs, &program_id),
+        vec![before.clone(), after.clone()]
+    );
+    //Invoked by another program, with that program logging around it
+    let logs = invocation(
+        &other,
+        1,
+        [
+            vec![data_line(&reset(Pubkey::new_unique(), 4))],
+            invocation(&program_id, 2, vec![data_line(&before)]),
+            vec![data_line(&reset(Pubkey::new_unique(), 5))],
+        ]
+        .concat(),
+    );
+    assert_eq!(decode_events(&logs, &program_id), vec![before]);
+}
@@ -0,0 +1,165 @@
+//Durable nonce transactions signed offline - nothing here needs a cluster
+
+use client::{
+    decode_transaction, encode_transaction, nonce_message, parse_signer_signature, Client,
+    TransactionEncoding,
+};
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::nonce::state::{Data, DurableNonce};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Signature, Signer};
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::system_instruction::SystemInstruction;
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+
+//An instruction of some counter program the owner signs
+fn counter_instruction(owner: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[0],
+        vec![
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+    )
+}
+
+fn nonce_data(authority: &Pubkey) -> Data {
+    let durable_nonce = DurableNonce::from_blockhash(&Hash::new_unique());
+    Data::new(*authority, durable_nonce, 5_000)
+}
+
+//Unsigned transaction the owner pays for, the nonce advanced by `nonce_authority`
+fn unsigned(owner: &Pubkey, nonce_authority: &Pubkey) -> Transaction {
+    let message = nonce_message(
+        &[counter_instruction(owner)],
+        owner,
+        &Pubkey::new_unique(),
+        nonce_authority,
+        &nonce_data(nonce_authority),
+    )
+    .unwrap();
+    Transaction::new_unsigned(message)
+}
+
+#[test]
+fn nonce_message_advances_the_nonce_first() {
+    let (payer, nonce_pubkey) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let nonce_data = nonce_data(&payer);
+    let instruction = counter_instruction(&payer);
+    let message = nonce_message(
+        std::slice::from_ref(&instruction),
+        &payer,
+        &nonce_pubkey,
+        &payer,
+        &nonce_data,
+    )
+    .unwrap();
+    assert_eq!(message.recent_blockhash, nonce_data.blockhash());
+    assert_eq!(message.account_keys[0], payer);
+    assert_eq!(message.instructions.len(), 2);
+    let advance = &message.instructions[0];
+    assert_eq!(
+        message.account_keys[advance.program_id_index as usize],
+        system_program::id()
+    );
+    assert_eq!(
+        bincode::deserialize::<SystemInstruction>(&advance.data).unwrap(),
+        SystemInstruction::AdvanceNonceAccount
+    );
+    assert_eq!(
+        message.account_keys[advance.accounts[0] as usize],
+        nonce_pubkey
+    );
+    assert_eq!(message.instructions[1].data, instruction.data);
+}
+
+#[test]
+fn nonce_message_needs_the_nonce_authority() {
+    let payer = Pubkey::new_unique();
+    let result = nonce_message(
+        &[counter_instruction(&payer)],
+        &payer,
+        &Pubkey::new_unique(),
+        &payer,
+        &nonce_data(&Pubkey::new_unique()),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn transactions_survive_encoding() {
+    let transaction = unsigned(&Pubkey::new_unique(), &Pubkey::new_unique());
+    for encoding in [TransactionEncoding::Base58, TransactionEncoding::Base64] {
+        let encoded = encode_transaction(&transaction, encoding).unwrap();
+        //Surrounding whitespace - as pasted in - does not matter
+        let decoded = decode_transaction(&format!(" {}\n", encoded), encoding).unwrap();
+        assert_eq!(decoded, transaction);
+    }
+    assert!(decode_transaction("not a transaction", TransactionEncoding::Base58).is_err());
+    assert!(decode_transaction("bm90IGEgdHJhbnNhY3Rpb24=", TransactionEncoding::Base64).is_err());
+}
+
+#[test]
+fn detached_signatures_assemble_the_transaction() {
+    let (owner, nonce_authority) = (Keypair::new(), Keypair::new());
+    let transaction = unsigned(&owner.pubkey(), &nonce_authority.pubkey());
+    //Passed around as text, as between the machines
+    let encoded = encode_transaction(&transaction, TransactionEncoding::Base64).unwrap();
+    let transaction = decode_transaction(&encoded, TransactionEncoding::Base64).unwrap();
+    let signatures: Vec<_> = [&owner, &nonce_authority]
+        .iter()
+        .map(|keypair| {
+            let (pubkey, signature) = Client::sign_offline(&transaction, keypair).unwrap();
+            parse_signer_signature(&format!("{}={}", pubkey, signature)).unwrap()
+        })
+        .collect();
+    let assembled = Client::assemble_transaction(transaction.clone(), &signatures).unwrap();
+    assert!(assembled.verify().is_ok());
+    //Signing one by one comes to the same
+    let mut signed = transaction.clone();
+    signed.sign(
+        &[&owner, &nonce_authority],
+        transaction.message.recent_blockhash,
+    );
+    assert_eq!(assembled, signed);
+}
+
+#[test]
+fn assembling_needs_every_signature_to_verify() {
+    let (owner, nonce_authority) = (Keypair::new(), Keypair::new());
+    let transaction = unsigned(&owner.pubkey(), &nonce_authority.pubkey());
+    let stranger = Keypair::new();
+    assert!(Client::sign_offline(&transaction, &stranger).is_err());
+    let signature = Client::sign_offline(&transaction, &owner).unwrap();
+    //One missing
+    assert!(Client::assemble_transaction(transaction.clone(), &[signature]).is_err());
+    //One that is not for this message
+    let forged = (
+        nonce_authority.pubkey(),
+        nonce_authority.sign_message(b"other"),
+    );
+    assert!(Client::assemble_transaction(transaction.clone(), &[signature, forged]).is_err());
+    //One of a key that need not sign
+    let stranger = (stranger.pubkey(), Signature::default());
+    assert!(Client::assemble_transaction(transaction, &[signature, stranger]).is_err());
+}
+
+#[test]
+fn signer_signatures_parse_as_pubkey_equals_signature() {
+    let keypair = Keypair::new();
+    let signature = keypair.sign_message(b"message");
+    assert_eq!(
+        parse_signer_signature(&format!("{}={}", keypair.pubkey(), signature)).unwrap(),
+        (keypair.pubkey(), signature)
+    );
+    for invalid in [
+        keypair.pubkey().to_string(),
+        format!("{}={}", "not a pubkey", signature),
+        format!("{}={}", keypair.pubkey(), "not a signature"),
+    ] {
+        assert!(parse_signer_signature(&invalid).is_err());
+    }
+}
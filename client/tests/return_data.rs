@@ -0,0 +1,54 @@
+//Counter state handed back by the program as return data
+
+use borsh::BorshSerialize;
+use client::decode_return_data;
+use common::Counter;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{UiReturnDataEncoding, UiTransactionReturnData};
+
+//Return data as the RPC node reports it - base64 encoded
+fn return_data(program_id: &Pubkey, data: &[u8]) -> Option<UiTransactionReturnData> {
+    Some(UiTransactionReturnData {
+        program_id: program_id.to_string(),
+        data: (base64::encode(data), UiReturnDataEncoding::Base64),
+    })
+}
+
+#[test]
+fn decodes_the_returned_counter() {
+    let program_id = Pubkey::new_unique();
+    let data = Counter {
+        count: 5,
+        ..Counter::default()
+    }
+    .try_to_vec()
+    .unwrap();
+    let counter = decode_return_data(return_data(&program_id, &data), &program_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(counter.count, 5);
+    //Nothing returned - or returned by another program
+    assert!(decode_return_data(None, &program_id).unwrap().is_none());
+    let other = Pubkey::new_unique();
+    assert!(decode_return_data(return_data(&other, &data), &program_id)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn returned_data_that_is_no_counter_fails() {
+    let program_id = Pubkey::new_unique();
+    let data = Counter {
+        count: 5,
+        ..Counter::default()
+    }
+    .try_to_vec()
+    .unwrap();
+    let truncated = return_data(&program_id, &data[..data.len() - 1]);
+    assert!(decode_return_data(truncated, &program_id).is_err());
+    let not_base64 = Some(UiTransactionReturnData {
+        program_id: program_id.to_string(),
+        data: ("not*base64".to_string(), UiReturnDataEncoding::Base64),
+    });
+    assert!(decode_return_data(not_base64, &program_id).is_err());
+}
@@ -0,0 +1,38 @@
+//Websocket url derivation and reconnect backoff of counter watches
+
+use client::{reconnect_delay, websocket_url_for};
+use std::time::Duration;
+
+#[test]
+fn websocket_urls_follow_the_rpc_url() {
+    assert_eq!(
+        websocket_url_for("http://api.devnet.solana.com"),
+        "ws://api.devnet.solana.com"
+    );
+    assert_eq!(
+        websocket_url_for("https://api.devnet.solana.com/"),
+        "wss://api.devnet.solana.com/"
+    );
+    //The websocket port is the one next to an explicit rpc port
+    assert_eq!(
+        websocket_url_for("http://localhost:8899"),
+        "ws://localhost:8900"
+    );
+    assert_eq!(
+        websocket_url_for("https://rpc.example.com:443/path?key=value"),
+        "wss://rpc.example.com:444/path?key=value"
+    );
+    //No scheme at all is taken for http
+    assert_eq!(websocket_url_for("127.0.0.1:8899"), "ws://127.0.0.1:8900");
+}
+
+#[test]
+fn reconnects_back_off_up_to_the_maximum() {
+    let delays: Vec<_> = (0..6).map(reconnect_delay).collect();
+    assert_eq!(
+        delays,
+        [2, 4, 8, 16, 30, 30].map(Duration::from_secs).to_vec()
+    );
+    //However long the connection stays down
+    assert_eq!(reconnect_delay(u32::MAX), Duration::from_secs(30));
+}
@@ -0,0 +1,81 @@
+use solana_program::program_error::ProgramError;
+use std::fmt;
+
+//Counter program specific failures - surfaced as `ProgramError::Custom(code)` so that
+//clients can tell them apart from generic program errors. Codes must never change -
+//add new variants at the end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterError {
+    //Counter access mode does not allow the signer(if any) to increament it
+    IncreamentNotAllowed,
+    //Less than the minimum interval of the rate limit since the last increament
+    IncreamentTooSoon,
+    //Rate limit window already holds the most increaments allowed
+    WindowLimitReached,
+    //Counter value would leave its bounds - and the overflow policy is to fail
+    OutOfBounds,
+    //Counter value is not the one the instruction expected - someone else changed it
+    ValueMismatch,
+    //Program is paused by its admin - counters can not be changed
+    Paused,
+    //Voucher is past its expiry
+    VoucherExpired,
+    //No Ed25519 verified signature of the voucher by the counter authority ahead of
+    //the redeem instruction
+    VoucherNotApproved,
+    //Voucher nonce has been redeemed already
+    VoucherRedeemed,
+    //Token account passed along does not hold the minimum balance of the gate mint for
+    //the actor
+    TokenHoldingRequired,
+}
+
+impl CounterError {
+    //The counter error behind a `ProgramError::Custom` code
+    pub fn from_code(code: u32) -> Option<Self> {
+        match code {
+            0 => Some(Self::IncreamentNotAllowed),
+            1 => Some(Self::IncreamentTooSoon),
+            2 => Some(Self::WindowLimitReached),
+            3 => Some(Self::OutOfBounds),
+            4 => Some(Self::ValueMismatch),
+            5 => Some(Self::Paused),
+            6 => Some(Self::VoucherExpired),
+            7 => Some(Self::VoucherNotApproved),
+            8 => Some(Self::VoucherRedeemed),
+            9 => Some(Self::TokenHoldingRequired),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CounterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::IncreamentNotAllowed => {
+                write!(f, "Signer is not allowed to increament the counter")
+            }
+            Self::IncreamentTooSoon => write!(f, "Counter was increamented too recently"),
+            Self::WindowLimitReached => {
+                write!(f, "Counter increament limit for the current window reached")
+            }
+            Self::OutOfBounds => write!(f, "Counter value would leave its bounds"),
+            Self::ValueMismatch => write!(f, "Counter value is not the expected value"),
+            Self::Paused => write!(f, "Counter program is paused"),
+            Self::VoucherExpired => write!(f, "Voucher has expired"),
+            Self::VoucherNotApproved => {
+                write!(f, "Voucher is not signed by the counter authority")
+            }
+            Self::VoucherRedeemed => write!(f, "Voucher has been redeemed already"),
+            Self::TokenHoldingRequired => {
+                write!(f, "Signer does not hold enough of the counter gate token")
+            }
+        }
+    }
+}
+
+impl From<CounterError> for ProgramError {
+    fn from(err: CounterError) -> Self {
+        ProgramError::Custom(err as u32)
+    }
+}
@@ -0,0 +1,144 @@
+use crate::state::{AccessMode, RateLimit, TokenGate};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+//Events emitted by the counter program - borsh encoded and logged with `sol_log_data`,
+//they show up as `Program data: <base64>` lines in the transaction logs
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub enum CounterEvent {
+    Incremented {
+        counter: Pubkey,
+        by: u64,
+        new_value: u64,
+        //Signer passed along with the increament - if any
+        actor: Option<Pubkey>,
+    },
+    Reset {
+        counter: Pubkey,
+        previous_value: u64,
+        actor: Pubkey,
+    },
+    Closed {
+        counter: Pubkey,
+        //Lamports sent to the destination account
+        lamports: u64,
+        actor: Pubkey,
+    },
+    Initialized {
+        counter: Pubkey,
+    },
+    Migrated {
+        counter: Pubkey,
+        from_version: u8,
+        to_version: u8,
+    },
+    AuthorityProposed {
+        counter: Pubkey,
+        authority: Pubkey,
+        proposed: Pubkey,
+    },
+    AuthorityTransferred {
+        counter: Pubkey,
+        previous: Pubkey,
+        authority: Pubkey,
+    },
+    AuthorityTransferCancelled {
+        counter: Pubkey,
+        authority: Pubkey,
+        //Proposal withdrawn
+        cancelled: Pubkey,
+    },
+    AccessModeChanged {
+        counter: Pubkey,
+        access_mode: AccessMode,
+    },
+    DelegateAdded {
+        counter: Pubkey,
+        delegate: Pubkey,
+    },
+    DelegateRemoved {
+        counter: Pubkey,
+        delegate: Pubkey,
+    },
+    MultisigInitialized {
+        multisig: Pubkey,
+        threshold: u8,
+        signers: Vec<Pubkey>,
+    },
+    RateLimitChanged {
+        counter: Pubkey,
+        rate_limit: RateLimit,
+    },
+    Set {
+        counter: Pubkey,
+        previous_value: u64,
+        new_value: u64,
+        actor: Pubkey,
+    },
+    ConfigInitialized {
+        admin: Pubkey,
+    },
+    Paused {
+        admin: Pubkey,
+    },
+    Unpaused {
+        admin: Pubkey,
+    },
+    AdminProposed {
+        admin: Pubkey,
+        proposed: Pubkey,
+    },
+    AdminTransferred {
+        previous: Pubkey,
+        admin: Pubkey,
+    },
+    FeePaid {
+        counter: Pubkey,
+        payer: Pubkey,
+        lamports: u64,
+    },
+    FeesWithdrawn {
+        counter: Pubkey,
+        destination: Pubkey,
+        lamports: u64,
+    },
+    LeaderboardCreated {
+        counter: Pubkey,
+        capacity: u8,
+    },
+    TimeSeriesCreated {
+        counter: Pubkey,
+        bucket_width: u32,
+        bucket_count: u16,
+    },
+    SnapshotTaken {
+        counter: Pubkey,
+        label: String,
+        value: u64,
+        slot: u64,
+    },
+    VoucherRedeemed {
+        counter: Pubkey,
+        redeemer: Pubkey,
+        nonce: u64,
+        amount: u64,
+        new_value: u64,
+    },
+    TokenGateChanged {
+        counter: Pubkey,
+        token_gate: TokenGate,
+    },
+    MilestonesCreated {
+        counter: Pubkey,
+        mint: Pubkey,
+        every: u64,
+        reward: u64,
+    },
+    MilestoneReached {
+        counter: Pubkey,
+        value: u64,
+        contributor: Pubkey,
+        //Tokens minted to the contributor
+        reward: u64,
+    },
+}
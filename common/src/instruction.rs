@@ -1,6 +1,287 @@
+use crate::state::{AccessMode, Bounds, RateLimit, TokenGate};
 use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
 
+//Authority gated instructions take the counter authority as their second account.
+//When the authority is a `Multisig` account it does not sign itself - at least the
+//threshold of its signers follow as trailing signer accounts instead.
+//Instructions other than the program config ones also take the program config
+//account(address from `ProgramConfig::find_address`) as their last account - they
+//are rejected while the program is paused
 #[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
 pub enum CounterInstruction {
+    //Accounts expected:
+    //0. [writable] counter account
+    //1. [signer] optional - the actor recorded in the emitted event. Required unless the
+    //   counter access mode is `Open`
+    //2. [] optional - delegate record of the actor, for counters in `Allowlist` mode
+    //3. [signer, writable] payer of the fee and of a new contribution record - for
+    //   counters charging a fee or when tracking the contribution
+    //4. [writable] counter treasury - for counters charging a fee
+    //5. [] system program - for counters charging a fee or when tracking the
+    //   contribution
+    //6. [writable] optional - contribution record of the actor, address from
+    //   `Contribution::find_address`. Created if need be - the actor must sign
+    //7. [writable] optional - leaderboard of the counter, address from
+    //   `Leaderboard::find_address`. The actor is ranked on it with its contribution,
+    //   if the leaderboard has been created
+    //8. [writable] optional - time series of the counter, address from
+    //   `TimeSeries::find_address`. The increament is counted in its current bucket, if
+    //   the time series has been created
+    //9. [] SPL token account of the actor holding the gate mint - for token gated
+    //   counters, the actor must sign. Not looked at otherwise
+    //10. [] optional - milestones of the counter, address from `Milestones::find_address`.
+    //    The actor is rewarded when its increament reaches a milestone, if the
+    //    milestones have been created - it must sign then
+    //11. [writable] reward mint - with the milestones
+    //12. [] mint authority of the counter - with the milestones, address from
+    //    `Milestones::find_mint_authority_address`
+    //13. [writable] associated token account of the actor for the reward mint - with
+    //    the milestones
+    //14. [] SPL token program - with the milestones
     Increament,
+    //Set the counter back to its lower bound
+    //Accounts expected:
+    //0. [writable] counter account
+    //1. [signer] counter authority
+    Reset,
+    //Close the counter account, sending its lamports to the destination
+    //Accounts expected:
+    //0. [writable] counter account
+    //1. [signer] counter authority
+    //2. [writable] destination account for the reclaimed lamports - not the counter
+    Close,
+    //Write the account header of a freshly created(zeroed) counter account. The
+    //counter starts at the lower bound. Increaments cost `fee` lamports per unit, paid
+    //into the counter treasury. The counter keeps its last `history_capacity`(at most
+    //`MAX_HISTORY_ENTRIES`, 0 for none) changes - the account grows to hold them. The
+    //bounds, the fee and the history capacity can not be changed later
+    //Accounts expected:
+    //0. [writable] counter account - owned by the program, at least `Counter::LEN` bytes
+    //1. [signer] authority of the counter - writable if there is a fee or a history, it
+    //   funds the rent exempt minimum of the treasury and the rent of the history
+    //2. [writable] treasury - address from `Counter::find_treasury_address`, if there
+    //   is a fee. Not looked at otherwise
+    //3. [] system program - if there is a fee or a history
+    Initialize {
+        bounds: Bounds,
+        fee: u64,
+        history_capacity: u16,
+    },
+    //Bring a counter account written by the original program version(headerless
+    //layout 0) to the current layout, growing the account as needed
+    //Accounts expected:
+    //0. [writable] counter account
+    //1. [signer, writable] payer of the extra rent - the base key the counter account
+    //   address was derived from, it becomes the authority
+    //2. [] system program
+    Migrate,
+    //First step of an authority transfer - the proposed authority takes over only
+    //once it accepts. Replaces any earlier proposal
+    //Accounts expected:
+    //0. [writable] counter account
+    //1. [signer] counter authority
+    ProposeAuthority {
+        new_authority: Pubkey,
+    },
+    //Second step of an authority transfer
+    //Accounts expected:
+    //0. [writable] counter account
+    //1. [signer] the pending authority
+    AcceptAuthority,
+    //Withdraw a pending authority proposal
+    //Accounts expected:
+    //0. [writable] counter account
+    //1. [signer] counter authority
+    CancelAuthorityTransfer,
+    //Change who may increament the counter
+    //Accounts expected:
+    //0. [writable] counter account
+    //1. [signer] counter authority
+    SetAccessMode {
+        access_mode: AccessMode,
+    },
+    //Allow the delegate to increament the counter while it is in `Allowlist` mode
+    //Accounts expected:
+    //0. [] counter account
+    //1. [signer] counter authority
+    //2. [signer, writable] payer of the rent for the delegate record
+    //3. [writable] delegate record - address from `Delegate::find_address`
+    //4. [] system program
+    AddDelegate {
+        delegate: Pubkey,
+    },
+    //Close the delegate record
+    //Accounts expected:
+    //0. [] counter account
+    //1. [signer] counter authority
+    //2. [writable] delegate record
+    //3. [writable] destination account for the reclaimed lamports - not the record
+    RemoveDelegate {
+        delegate: Pubkey,
+    },
+    //Set up a multisig in a freshly created(zeroed) account - in the same transaction
+    //that creates it
+    //Accounts expected:
+    //0. [writable] multisig account - owned by the program, at least `Multisig::LEN` bytes
+    //1..=N. [] the signers - 1 to `MAX_SIGNERS` distinct keys
+    InitializeMultisig {
+        threshold: u8,
+    },
+    //Limit how often the counter may be increamented - the default(all zeros) rate
+    //limit lifts any limit
+    //Accounts expected:
+    //0. [writable] counter account
+    //1. [signer] counter authority
+    SetRateLimit {
+        rate_limit: RateLimit,
+    },
+    //Set the counter to `new` - only if it still is `expected`. `new` must be within
+    //the counter bounds
+    //Accounts expected:
+    //0. [writable] counter account
+    //1. [signer] counter authority
+    CompareAndSet {
+        expected: u64,
+        new: u64,
+    },
+    //Increament the counter - only if it still is `expected`
+    //Accounts expected: same as `Increament`
+    IncrementIfEquals {
+        expected: u64,
+    },
+    //Create the program config account. Only the upgrade authority of the program
+    //may - it sets the admin, which may be a multisig
+    //Accounts expected:
+    //0. [writable] program config account - address from `ProgramConfig::find_address`
+    //1. [signer, writable] upgrade authority of the program - pays the rent
+    //2. [] program data account of the program
+    //3. [] system program
+    InitializeConfig {
+        admin: Pubkey,
+    },
+    //Reject every instruction that changes counters until unpaused
+    //Accounts expected:
+    //0. [writable] program config account
+    //1. [signer] admin
+    Pause,
+    //Accounts expected:
+    //0. [writable] program config account
+    //1. [signer] admin
+    Unpause,
+    //First step of an admin rotation - the proposed admin takes over only once it
+    //accepts. Replaces any earlier proposal
+    //Accounts expected:
+    //0. [writable] program config account
+    //1. [signer] admin
+    ProposeAdmin {
+        new_admin: Pubkey,
+    },
+    //Second step of an admin rotation
+    //Accounts expected:
+    //0. [writable] program config account
+    //1. [signer] the pending admin
+    AcceptAdmin,
+    //Move collected fees out of the counter treasury - 0 lamports for all of them. The
+    //treasury keeps its rent exempt minimum
+    //Accounts expected:
+    //0. [] counter account
+    //1. [signer] counter authority
+    //2. [writable] counter treasury
+    //3. [writable] destination account
+    //4. [] system program
+    WithdrawFees {
+        lamports: u64,
+    },
+    //Start ranking the top `capacity`(at most `MAX_LEADERBOARD_ENTRIES`) contributors
+    //of the counter. Contributors get on it as they increament
+    //Accounts expected:
+    //0. [] counter account
+    //1. [signer] counter authority
+    //2. [signer, writable] payer of the rent for the leaderboard
+    //3. [writable] leaderboard - address from `Leaderboard::find_address`
+    //4. [] system program
+    CreateLeaderboard {
+        capacity: u8,
+    },
+    //Start counting the increaments of the counter per `bucket_width` seconds, over the
+    //last `bucket_count`(at most `MAX_TIME_BUCKETS`) buckets
+    //Accounts expected:
+    //0. [] counter account
+    //1. [signer] counter authority
+    //2. [signer, writable] payer of the rent for the time series
+    //3. [writable] time series - address from `TimeSeries::find_address`
+    //4. [] system program
+    CreateTimeSeries {
+        bucket_width: u32,
+        bucket_count: u16,
+    },
+    //Record the counter value as of the current slot under `label`(1 to
+    //`MAX_SNAPSHOT_LABEL_LEN` bytes) - once per label, snapshots can not be changed
+    //Accounts expected:
+    //0. [] counter account
+    //1. [signer] counter authority
+    //2. [signer, writable] payer of the rent for the snapshot
+    //3. [writable] snapshot - address from `Snapshot::find_address`
+    //4. [] system program
+    Snapshot {
+        label: String,
+    },
+    //Increament the counter by the `amount` of a voucher issued by the counter
+    //authority - the instruction right ahead of this one must be an Ed25519 program
+    //instruction verifying the authority's signature of the `Voucher`. The access mode
+    //does not apply - everything else an increament goes through does, the fee per unit.
+    //Each nonce is redeemed once, until the expiry
+    //Accounts expected:
+    //0. [writable] counter account
+    //1. [signer, writable] redeemer - pays the rent for the redeemed voucher record, a
+    //   new contribution record and the fee
+    //2. [writable] redeemed voucher record - address from `RedeemedVoucher::find_address`
+    //3. [] instructions sysvar
+    //4. [] system program
+    //5. [writable] counter treasury - for counters charging a fee
+    //6. - 14. optional - as for `Increament`, the redeemer being the actor
+    RedeemVoucher {
+        amount: u64,
+        nonce: u64,
+        expiry: i64,
+    },
+    //Only let holders of at least `min_balance` of the gate mint increament the counter
+    //- the default(no mint) token gate lifts the gate
+    //Accounts expected:
+    //0. [writable] counter account
+    //1. [signer] counter authority
+    SetTokenGate {
+        token_gate: TokenGate,
+    },
+    //Reward contributors with `reward` tokens of `mint` for every `every` increaments of
+    //the counter - paid to whoever reaches the milestone. The mint authority of `mint`
+    //must be the mint authority of the counter. Can not be changed later
+    //Accounts expected:
+    //0. [] counter account
+    //1. [signer] counter authority
+    //2. [signer, writable] payer of the rent for the milestones
+    //3. [writable] milestones - address from `Milestones::find_address`
+    //4. [] system program
+    //5. [] reward mint
+    CreateMilestones {
+        mint: Pubkey,
+        every: u64,
+        reward: u64,
+    },
+}
+
+impl CounterInstruction {
+    //Program config instructions - the only ones allowed while the program is paused,
+    //they do not take the program config as their last account
+    pub fn is_config_instruction(&self) -> bool {
+        matches!(
+            self,
+            Self::InitializeConfig { .. }
+                | Self::Pause
+                | Self::Unpause
+                | Self::ProposeAdmin { .. }
+                | Self::AcceptAdmin
+        )
+    }
 }
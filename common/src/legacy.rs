@@ -0,0 +1,66 @@
+//Counter layout written by the original version of the program. It is kept around so
+//that such accounts can still be read and migrated to the current layout
+use crate::state::{AccountHeader, AccountType, Counter};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use std::io;
+
+//Version 0 - the original layout. Just the count, no header - accounts are exactly
+//`CounterV0::LEN` bytes
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct CounterV0 {
+    pub count: u64,
+}
+
+impl CounterV0 {
+    pub const LEN: usize = 8;
+}
+
+//A counter in either of the layouts ever written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionedCounter {
+    V0(CounterV0),
+    Current(Box<Counter>),
+}
+
+impl VersionedCounter {
+    //Recognize the layout of counter account data
+    pub fn unpack(data: &[u8]) -> io::Result<Self> {
+        //Headerless version 0 accounts are told apart by their size
+        if data.len() == CounterV0::LEN {
+            return Ok(Self::V0(CounterV0::try_from_slice(data)?));
+        }
+        let header = AccountHeader::deserialize(&mut &data[..])?;
+        if header.account_type != AccountType::Counter {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not a counter account",
+            ));
+        }
+        Ok(Self::Current(Box::new(Counter::unpack(data)?)))
+    }
+
+    pub fn version(&self) -> u8 {
+        match self {
+            Self::V0(_) => 0,
+            Self::Current(counter) => counter.header.version,
+        }
+    }
+
+    //Whether the layout records a counter authority - version 0 does not
+    pub fn has_authority(&self) -> bool {
+        matches!(self, Self::Current(_))
+    }
+
+    //Bring the counter to the current layout. `owner` becomes the authority of version
+    //0 counters - they stay open to anyone, unbounded and free to increament
+    pub fn upgrade(self, owner: &Pubkey) -> Counter {
+        match self {
+            Self::V0(counter) => Counter {
+                count: counter.count,
+                ..Counter::new(*owner)
+            },
+            Self::Current(counter) => *counter,
+        }
+    }
+}
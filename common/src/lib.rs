@@ -1,5 +1,13 @@
+pub use error::*;
+pub use event::*;
 pub use instruction::*;
+pub use legacy::*;
 pub use state::*;
+pub use voucher::*;
 
+pub mod error;
+pub mod event;
 pub mod instruction;
+pub mod legacy;
 pub mod state;
+pub mod voucher;
@@ -1,7 +1,935 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use std::io;
 
-#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+//Seed used to derive counter account addresses(`Pubkey::create_with_seed`) from
+//the base key of the counter owner
+pub const COUNTER_ACCOUNT_SEED: &str = "COUNTER";
 
+//Leading seed of the program derived delegate record addresses
+pub const DELEGATE_SEED: &[u8] = b"delegate";
+
+//Leading seed of the program derived counter treasury addresses
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+//Leading seed of the program derived contribution record addresses
+pub const CONTRIBUTION_SEED: &[u8] = b"contribution";
+
+//Leading seed of the program derived leaderboard addresses
+pub const LEADERBOARD_SEED: &[u8] = b"leaderboard";
+
+//Leading seed of the program derived time series addresses
+pub const TIME_SERIES_SEED: &[u8] = b"time_series";
+
+//Leading seed of the program derived snapshot addresses
+pub const SNAPSHOT_SEED: &[u8] = b"snapshot";
+
+//Leading seed of the program derived redeemed voucher addresses
+pub const VOUCHER_SEED: &[u8] = b"voucher";
+
+//Leading seed of the program derived milestones addresses
+pub const MILESTONES_SEED: &[u8] = b"milestones";
+
+//Leading seed of the program derived mint authority addresses - one per counter, it
+//mints the milestone rewards
+pub const MINT_AUTHORITY_SEED: &[u8] = b"mint_authority";
+
+//Seed of the program derived program config address
+pub const CONFIG_SEED: &[u8] = b"config";
+
+//Most signers a multisig account can hold
+pub const MAX_SIGNERS: usize = 11;
+
+//Most changes a counter history can hold - its space is added to the counter account
+//in one go, within the limit on growing an account per instruction
+pub const MAX_HISTORY_ENTRIES: u16 = 128;
+
+//Most buckets a time series can hold - a month of hourly buckets
+pub const MAX_TIME_BUCKETS: u16 = 744;
+
+//Longest snapshot label in bytes - the label is a seed of the snapshot address
+pub const MAX_SNAPSHOT_LABEL_LEN: usize = 32;
+
+//Most contributors a leaderboard can rank - keeps the update on each increament cheap
+pub const MAX_LEADERBOARD_ENTRIES: usize = 10;
+
+//Tells apart the kinds of accounts owned by the counter program. Freshly created
+//accounts are zeroed - hence `Uninitialized` comes first
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+    Uninitialized,
+    Counter,
+    Delegate,
+    Multisig,
+    Config,
+    Contribution,
+    Leaderboard,
+    TimeSeries,
+    Snapshot,
+    RedeemedVoucher,
+    Milestones,
+}
+
+//Leads every account owned by the counter program - the layout version allows
+//accounts written by older program versions to be recognized and migrated
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountHeader {
+    pub account_type: AccountType,
+    pub version: u8,
+}
+
+impl AccountHeader {
+    pub const LEN: usize = 1 + 1;
+
+    //Account data must be of the expected type and in the current layout
+    fn check(&self, account_type: AccountType, version: u8) -> io::Result<()> {
+        if self.account_type != account_type {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Not a {:?} account", account_type),
+            ));
+        }
+        if self.version != version {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{:?} layout version {} is not current({}) - migrate the account",
+                    account_type, self.version, version
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+//What happens when a counter would go past its upper bound
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    //The instruction fails
+    #[default]
+    Fail,
+    //The counter stays at the upper bound
+    Saturate,
+    //The counter starts over from the lower bound
+    Wrap,
+}
+
+//Range a counter is kept in - both bounds inclusive. Defaults to the whole u64 range
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: u64,
+    pub max: u64,
+    pub overflow_policy: OverflowPolicy,
+}
+
+impl Bounds {
+    pub const LEN: usize = 8 + 8 + 1;
+
+    pub fn is_valid(&self) -> bool {
+        self.min <= self.max
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    //Add to a value within the bounds, applying the overflow policy - None if the
+    //policy is to fail
+    pub fn add(&self, value: u64, by: u64) -> Option<u64> {
+        match value.checked_add(by).filter(|sum| *sum <= self.max) {
+            Some(sum) => Some(sum),
+            None => match self.overflow_policy {
+                OverflowPolicy::Fail => None,
+                OverflowPolicy::Saturate => Some(self.max),
+                OverflowPolicy::Wrap => {
+                    let span = (self.max - self.min) as u128 + 1;
+                    let offset = value.saturating_sub(self.min) as u128 + by as u128;
+                    Some(self.min + (offset % span) as u64)
+                }
+            },
+        }
+    }
+}
+
+impl Default for Bounds {
+    fn default() -> Self {
+        Self {
+            min: 0,
+            max: u64::MAX,
+            overflow_policy: OverflowPolicy::Fail,
+        }
+    }
+}
+
+//Who may increament a counter
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessMode {
+    //Anyone
+    Open,
+    //Only the counter authority
+    AuthorityOnly,
+    //The counter authority and the delegates it added
+    Allowlist,
+}
+
+//Clock the rate limit of a counter is measured by
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RateUnit {
+    #[default]
+    Slots,
+    //Unix timestamp seconds
+    Seconds,
+}
+
+//How often a counter may be increamented. All zeros - the default - means no limit
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RateLimit {
+    pub unit: RateUnit,
+    //Least time between increaments - 0 for none
+    pub min_interval: u64,
+    //Length of the(consecutive, fixed) windows increaments are capped in
+    pub window: u64,
+    //Most increaments per window - 0 for no cap
+    pub max_per_window: u32,
+}
+
+impl RateLimit {
+    pub const LEN: usize = 1 + 8 + 8 + 4;
+}
+
+//SPL token holding an increamenter must have. The default(no mint) means no gate
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TokenGate {
+    pub mint: Pubkey,
+    //Least amount(in base units of the mint) the increamenter's token account holds
+    pub min_balance: u64,
+}
+
+impl TokenGate {
+    pub const LEN: usize = 32 + 8;
+
+    pub fn is_enabled(&self) -> bool {
+        self.mint != Pubkey::default()
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
 pub struct Counter {
+    pub header: AccountHeader,
     pub count: u64,
+    //Signs the authority gated instructions(reset, close, authority transfer)
+    pub authority: Pubkey,
+    pub access_mode: AccessMode,
+    pub rate_limit: RateLimit,
+    //Clock at the last increament - zero if never increamented
+    pub last_update_slot: u64,
+    pub last_update_timestamp: i64,
+    //Start of the current rate limit window and the increaments within it
+    pub window_start: u64,
+    pub window_count: u32,
+    //Fixed at initialization
+    pub bounds: Bounds,
+    //Lamports charged per unit increamented - 0 for free. Fixed at initialization
+    pub fee: u64,
+    //Collects the fees - the program derived address from `Counter::find_treasury_address`,
+    //only the authority can withdraw from it. Default(no treasury) for free counters
+    pub treasury: Pubkey,
+    pub token_gate: TokenGate,
+    //Proposed new authority - it takes over once it accepts. Optional fields go last
+    //so that the others stay at fixed offsets
+    pub pending_authority: Option<Pubkey>,
+}
+
+impl Counter {
+    //Current layout version - bump it(and keep the old layout in `legacy`) whenever
+    //fields are added
+    pub const VERSION: u8 = 1;
+    //Account space needed for the current layout - with a pending authority in place
+    pub const LEN: usize = AccountHeader::LEN
+        + 8
+        + 32
+        + 1
+        + RateLimit::LEN
+        + 8
+        + 8
+        + 8
+        + 4
+        + Bounds::LEN
+        + 8
+        + 32
+        + TokenGate::LEN
+        + 33;
+
+    //A zeroed counter under the given authority
+    pub fn new(authority: Pubkey) -> Self {
+        Self {
+            authority,
+            ..Self::default()
+        }
+    }
+
+    //A counter under the given authority, starting at the lower bound
+    pub fn with_bounds(authority: Pubkey, bounds: Bounds) -> Self {
+        Self {
+            count: bounds.min,
+            bounds,
+            ..Self::new(authority)
+        }
+    }
+
+    //Address(and bump seed) of the system account collecting the fees of `counter`
+    pub fn find_treasury_address(counter: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TREASURY_SEED, counter.as_ref()], program_id)
+    }
+
+    //Deserialize a counter in the current layout. Accounts may be larger than the
+    //layout - trailing bytes are ignored
+    pub fn unpack(data: &[u8]) -> io::Result<Self> {
+        let counter = Self::deserialize(&mut &data[..])?;
+        counter.header.check(AccountType::Counter, Self::VERSION)?;
+        Ok(counter)
+    }
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Self {
+            header: AccountHeader {
+                account_type: AccountType::Counter,
+                version: Self::VERSION,
+            },
+            count: 0,
+            authority: Pubkey::default(),
+            access_mode: AccessMode::Open,
+            rate_limit: RateLimit::default(),
+            last_update_slot: 0,
+            last_update_timestamp: 0,
+            window_start: 0,
+            window_count: 0,
+            bounds: Bounds::default(),
+            fee: 0,
+            treasury: Pubkey::default(),
+            token_gate: TokenGate::default(),
+            pending_authority: None,
+        }
+    }
+}
+
+//Allows `delegate` to increament `counter` while the counter is in `Allowlist` mode.
+//Lives at the program address derived from `DELEGATE_SEED`, the counter and the
+//delegate - so there is at most one record per pair
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct Delegate {
+    pub header: AccountHeader,
+    pub counter: Pubkey,
+    pub delegate: Pubkey,
+}
+
+impl Delegate {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = AccountHeader::LEN + 32 + 32;
+
+    pub fn new(counter: Pubkey, delegate: Pubkey) -> Self {
+        Self {
+            header: AccountHeader {
+                account_type: AccountType::Delegate,
+                version: Self::VERSION,
+            },
+            counter,
+            delegate,
+        }
+    }
+
+    pub fn unpack(data: &[u8]) -> io::Result<Self> {
+        let record = Self::deserialize(&mut &data[..])?;
+        record.header.check(AccountType::Delegate, Self::VERSION)?;
+        Ok(record)
+    }
+
+    //Address(and bump seed) of the record allowing `delegate` on `counter`
+    pub fn find_address(counter: &Pubkey, delegate: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[DELEGATE_SEED, counter.as_ref(), delegate.as_ref()],
+            program_id,
+        )
+    }
+}
+
+//M of N multisig - set as a counter authority, authority gated instructions need the
+//signatures of at least `threshold` of its signers
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct Multisig {
+    pub header: AccountHeader,
+    pub threshold: u8,
+    //Number of `signers` in use
+    pub signer_count: u8,
+    pub signers: [Pubkey; MAX_SIGNERS],
+}
+
+impl Multisig {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = AccountHeader::LEN + 1 + 1 + 32 * MAX_SIGNERS;
+
+    //Signers beyond `MAX_SIGNERS` are ignored
+    pub fn new(threshold: u8, signers: &[Pubkey]) -> Self {
+        let mut multisig = Self {
+            header: AccountHeader {
+                account_type: AccountType::Multisig,
+                version: Self::VERSION,
+            },
+            threshold,
+            signer_count: signers.len().min(MAX_SIGNERS) as u8,
+            signers: [Pubkey::default(); MAX_SIGNERS],
+        };
+        multisig.signers[..multisig.signer_count as usize]
+            .copy_from_slice(&signers[..multisig.signer_count as usize]);
+        multisig
+    }
+
+    pub fn unpack(data: &[u8]) -> io::Result<Self> {
+        let multisig = Self::deserialize(&mut &data[..])?;
+        multisig
+            .header
+            .check(AccountType::Multisig, Self::VERSION)?;
+        Ok(multisig)
+    }
+
+    pub fn signers(&self) -> &[Pubkey] {
+        &self.signers[..(self.signer_count as usize).min(MAX_SIGNERS)]
+    }
+}
+
+//Program wide settings - a single account at the address derived from `CONFIG_SEED`.
+//Until it is created the program runs unpaused
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProgramConfig {
+    pub header: AccountHeader,
+    //Pauses and unpauses the program - may be a multisig
+    pub admin: Pubkey,
+    //Counters can not be changed while paused
+    pub paused: bool,
+    //Proposed new admin - it takes over once it accepts
+    pub pending_admin: Option<Pubkey>,
+}
+
+impl ProgramConfig {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = AccountHeader::LEN + 32 + 1 + 33;
+
+    pub fn new(admin: Pubkey) -> Self {
+        Self {
+            header: AccountHeader {
+                account_type: AccountType::Config,
+                version: Self::VERSION,
+            },
+            admin,
+            paused: false,
+            pending_admin: None,
+        }
+    }
+
+    pub fn unpack(data: &[u8]) -> io::Result<Self> {
+        let config = Self::deserialize(&mut &data[..])?;
+        config.header.check(AccountType::Config, Self::VERSION)?;
+        Ok(config)
+    }
+
+    //Address(and bump seed) of the program config
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+    }
+}
+
+//Tally of the increaments one contributor made to a counter. Lives at the program
+//address derived from `CONTRIBUTION_SEED`, the counter and the contributor - created
+//on the first increament that passes it along
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct Contribution {
+    pub header: AccountHeader,
+    pub counter: Pubkey,
+    pub contributor: Pubkey,
+    //Units increamented by the contributor
+    pub total: u64,
+    //Slot of the last increament by the contributor
+    pub last_slot: u64,
+}
+
+impl Contribution {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = AccountHeader::LEN + 32 + 32 + 8 + 8;
+
+    pub fn new(counter: Pubkey, contributor: Pubkey) -> Self {
+        Self {
+            header: AccountHeader {
+                account_type: AccountType::Contribution,
+                version: Self::VERSION,
+            },
+            counter,
+            contributor,
+            total: 0,
+            last_slot: 0,
+        }
+    }
+
+    pub fn unpack(data: &[u8]) -> io::Result<Self> {
+        let record = Self::deserialize(&mut &data[..])?;
+        record
+            .header
+            .check(AccountType::Contribution, Self::VERSION)?;
+        Ok(record)
+    }
+
+    //Address(and bump seed) of the record of `contributor` to `counter`
+    pub fn find_address(
+        counter: &Pubkey,
+        contributor: &Pubkey,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[CONTRIBUTION_SEED, counter.as_ref(), contributor.as_ref()],
+            program_id,
+        )
+    }
+}
+
+//A contributor and its total on a leaderboard
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LeaderboardEntry {
+    pub contributor: Pubkey,
+    pub total: u64,
+}
+
+//Top contributors of a counter by total, largest first. Lives at the program address
+//derived from `LEADERBOARD_SEED` and the counter - increaments that track their
+//contribution rank the contributor on it
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct Leaderboard {
+    pub header: AccountHeader,
+    pub counter: Pubkey,
+    //Contributors ranked - 1 to `MAX_LEADERBOARD_ENTRIES`
+    pub capacity: u8,
+    //Number of `entries` in use
+    pub entry_count: u8,
+    pub entries: [LeaderboardEntry; MAX_LEADERBOARD_ENTRIES],
+}
+
+impl Leaderboard {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = AccountHeader::LEN + 32 + 1 + 1 + (32 + 8) * MAX_LEADERBOARD_ENTRIES;
+
+    //Capacity is kept within 1 and `MAX_LEADERBOARD_ENTRIES`
+    pub fn new(counter: Pubkey, capacity: u8) -> Self {
+        Self {
+            header: AccountHeader {
+                account_type: AccountType::Leaderboard,
+                version: Self::VERSION,
+            },
+            counter,
+            capacity: capacity.clamp(1, MAX_LEADERBOARD_ENTRIES as u8),
+            entry_count: 0,
+            entries: [LeaderboardEntry::default(); MAX_LEADERBOARD_ENTRIES],
+        }
+    }
+
+    pub fn unpack(data: &[u8]) -> io::Result<Self> {
+        let leaderboard = Self::deserialize(&mut &data[..])?;
+        leaderboard
+            .header
+            .check(AccountType::Leaderboard, Self::VERSION)?;
+        Ok(leaderboard)
+    }
+
+    pub fn entries(&self) -> &[LeaderboardEntry] {
+        &self.entries[..(self.entry_count as usize).min(self.capacity())]
+    }
+
+    fn capacity(&self) -> usize {
+        (self.capacity as usize).clamp(1, MAX_LEADERBOARD_ENTRIES)
+    }
+
+    //Rank the contributor with its new total - totals only grow, so it can only move
+    //up. A newcomer takes the last place of a full leaderboard if it beats it. Returns
+    //whether the leaderboard changed
+    pub fn record(&mut self, contributor: &Pubkey, total: u64) -> bool {
+        let count = self.entries().len();
+        let mut at = match self
+            .entries()
+            .iter()
+            .position(|e| e.contributor == *contributor)
+        {
+            Some(at) => at,
+            None if count < self.capacity() => {
+                self.entry_count = count as u8 + 1;
+                count
+            }
+            None if total > self.entries[count - 1].total => count - 1,
+            None => return false,
+        };
+        self.entries[at] = LeaderboardEntry {
+            contributor: *contributor,
+            total,
+        };
+        //Equal totals keep their order - the one that got there first stays ahead
+        while at > 0 && self.entries[at - 1].total < total {
+            self.entries.swap(at - 1, at);
+            at -= 1;
+        }
+        true
+    }
+
+    //Address(and bump seed) of the leaderboard of `counter`
+    pub fn find_address(counter: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[LEADERBOARD_SEED, counter.as_ref()], program_id)
+    }
+}
+
+//A change of the counter value
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HistoryEntry {
+    //Signer behind the change - default for anonymous increaments
+    pub actor: Pubkey,
+    //Resulting value less the previous one - negative for resets and wraps
+    pub delta: i64,
+    pub slot: u64,
+    //Counter value after the change
+    pub value: u64,
+}
+
+impl HistoryEntry {
+    pub const LEN: usize = 32 + 8 + 8 + 8;
+}
+
+//Ring buffer of the latest changes of a counter, set up at initialization. It lives in
+//the counter account right after the counter fields(at `Counter::LEN`) - this header
+//followed by `capacity` entries. Counters without a history have no(or zeroed) space
+//there
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct History {
+    pub capacity: u16,
+    //Entries written so far - at most `capacity`
+    pub len: u16,
+    //Slot of the next entry - the oldest one once full
+    pub next: u16,
+}
+
+impl History {
+    pub const LEN: usize = 2 + 2 + 2;
+
+    //Account space taken by a history of `capacity` entries
+    pub fn space(capacity: u16) -> usize {
+        Self::LEN + HistoryEntry::LEN * capacity as usize
+    }
+
+    //History kept in the counter account data - None if the counter keeps none
+    pub fn unpack(data: &[u8]) -> io::Result<Option<Self>> {
+        let header = match data.get(Counter::LEN..Counter::LEN + Self::LEN) {
+            Some(mut header) => Self::deserialize(&mut header)?,
+            None => return Ok(None),
+        };
+        if header.capacity == 0 {
+            return Ok(None);
+        }
+        if data.len() < Counter::LEN + Self::space(header.capacity)
+            || header.len > header.capacity
+            || header.next >= header.capacity
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Counter history is corrupt",
+            ));
+        }
+        Ok(Some(header))
+    }
+
+    //Write `entry` over the oldest one once full - only the header and that entry are
+    //touched
+    pub fn push(&mut self, data: &mut [u8], entry: &HistoryEntry) -> io::Result<()> {
+        let at = Counter::LEN + Self::LEN + HistoryEntry::LEN * self.next as usize;
+        entry.serialize(&mut &mut data[at..at + HistoryEntry::LEN])?;
+        self.next = (self.next + 1) % self.capacity;
+        self.len = self.len.saturating_add(1).min(self.capacity);
+        self.serialize(&mut &mut data[Counter::LEN..Counter::LEN + Self::LEN])
+    }
+
+    //Entries oldest first
+    pub fn entries(&self, data: &[u8]) -> io::Result<Vec<HistoryEntry>> {
+        let oldest = match self.len == self.capacity {
+            true => self.next,
+            false => 0,
+        };
+        (0..self.len)
+            .map(|i| {
+                let at = Counter::LEN
+                    + Self::LEN
+                    + HistoryEntry::LEN * ((oldest + i) % self.capacity) as usize;
+                HistoryEntry::deserialize(&mut &data[at..at + HistoryEntry::LEN])
+            })
+            .collect()
+    }
+}
+
+//Increaments of a counter per time bucket - hourly or daily, say - over the last
+//`bucket_count` buckets. Lives at the program address derived from `TIME_SERIES_SEED`
+//and the counter. Buckets start at multiples of their width in unix time(UTC) and
+//roll over as the `Clock` moves on - the oldest bucket starting over from zero
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct TimeSeries {
+    pub header: AccountHeader,
+    pub counter: Pubkey,
+    //Seconds per bucket
+    pub bucket_width: u32,
+    //Start(unix timestamp) of the latest bucket
+    pub latest_start: i64,
+    //Index of the latest bucket in `buckets`
+    pub latest: u16,
+    //Ring buffer of increaments per bucket - 1 to `MAX_TIME_BUCKETS` of them
+    pub buckets: Vec<u64>,
+}
+
+impl TimeSeries {
+    pub const VERSION: u8 = 1;
+
+    //Account space taken by a time series of `bucket_count` buckets
+    pub fn space(bucket_count: u16) -> usize {
+        AccountHeader::LEN + 32 + 4 + 8 + 2 + 4 + 8 * bucket_count as usize
+    }
+
+    //The latest bucket is the one `unix_timestamp` falls in. Width and count must be
+    //at least 1
+    pub fn new(counter: Pubkey, bucket_width: u32, bucket_count: u16, unix_timestamp: i64) -> Self {
+        Self {
+            header: AccountHeader {
+                account_type: AccountType::TimeSeries,
+                version: Self::VERSION,
+            },
+            counter,
+            bucket_width,
+            latest_start: Self::bucket_start(bucket_width, unix_timestamp),
+            latest: 0,
+            buckets: vec![0; bucket_count as usize],
+        }
+    }
+
+    pub fn unpack(data: &[u8]) -> io::Result<Self> {
+        let time_series = Self::deserialize(&mut &data[..])?;
+        time_series
+            .header
+            .check(AccountType::TimeSeries, Self::VERSION)?;
+        if time_series.bucket_width == 0 || time_series.latest as usize >= time_series.buckets.len()
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Time series is corrupt",
+            ));
+        }
+        Ok(time_series)
+    }
+
+    fn bucket_start(bucket_width: u32, unix_timestamp: i64) -> i64 {
+        unix_timestamp - unix_timestamp.rem_euclid(bucket_width as i64)
+    }
+
+    //Move the latest bucket up to the one `unix_timestamp` falls in - the buckets passed
+    //over start from zero. Touches no more than all the buckets once
+    pub fn roll_over(&mut self, unix_timestamp: i64) {
+        let start = Self::bucket_start(self.bucket_width, unix_timestamp);
+        if start <= self.latest_start {
+            return;
+        }
+        let count = self.buckets.len();
+        let elapsed = (start - self.latest_start) / self.bucket_width as i64;
+        for _ in 0..elapsed.min(count as i64) {
+            self.latest = ((self.latest as usize + 1) % count) as u16;
+            self.buckets[self.latest as usize] = 0;
+        }
+        self.latest_start = start;
+    }
+
+    //Count `units` at `unix_timestamp`. A clock that went back counts towards the
+    //latest bucket
+    pub fn record(&mut self, unix_timestamp: i64, units: u64) {
+        self.roll_over(unix_timestamp);
+        let latest = &mut self.buckets[self.latest as usize];
+        *latest = latest.saturating_add(units);
+    }
+
+    //Bucket starts and their increaments as of `unix_timestamp`, oldest first
+    pub fn series(&self, unix_timestamp: i64) -> Vec<(i64, u64)> {
+        let mut time_series = self.clone();
+        time_series.roll_over(unix_timestamp);
+        let count = time_series.buckets.len();
+        (0..count)
+            .map(|i| {
+                let age = (count - 1 - i) as i64;
+                (
+                    time_series.latest_start - age * time_series.bucket_width as i64,
+                    time_series.buckets[(time_series.latest as usize + 1 + i) % count],
+                )
+            })
+            .collect()
+    }
+
+    //Address(and bump seed) of the time series of `counter`
+    pub fn find_address(counter: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TIME_SERIES_SEED, counter.as_ref()], program_id)
+    }
+}
+
+//Counter value as of the slot a snapshot was taken in - never changed afterwards. Lives
+//at the program address derived from `SNAPSHOT_SEED`, the counter and the label
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub header: AccountHeader,
+    pub counter: Pubkey,
+    pub value: u64,
+    pub slot: u64,
+    pub unix_timestamp: i64,
+    //1 to `MAX_SNAPSHOT_LABEL_LEN` bytes
+    pub label: String,
+}
+
+impl Snapshot {
+    pub const VERSION: u8 = 1;
+
+    //Account space taken by a snapshot labelled `label`
+    pub fn space(label: &str) -> usize {
+        AccountHeader::LEN + 32 + 8 + 8 + 8 + 4 + label.len()
+    }
+
+    pub fn new(counter: Pubkey, value: u64, slot: u64, unix_timestamp: i64, label: String) -> Self {
+        Self {
+            header: AccountHeader {
+                account_type: AccountType::Snapshot,
+                version: Self::VERSION,
+            },
+            counter,
+            value,
+            slot,
+            unix_timestamp,
+            label,
+        }
+    }
+
+    pub fn unpack(data: &[u8]) -> io::Result<Self> {
+        let snapshot = Self::deserialize(&mut &data[..])?;
+        snapshot
+            .header
+            .check(AccountType::Snapshot, Self::VERSION)?;
+        Ok(snapshot)
+    }
+
+    //Address(and bump seed) of the snapshot of `counter` labelled `label`
+    pub fn find_address(counter: &Pubkey, label: &str, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[SNAPSHOT_SEED, counter.as_ref(), label.as_bytes()],
+            program_id,
+        )
+    }
+}
+
+//Marks a voucher nonce as used - a voucher with the same nonce can not be redeemed
+//again. Lives at the program address derived from `VOUCHER_SEED`, the counter and the
+//nonce
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct RedeemedVoucher {
+    pub header: AccountHeader,
+    pub counter: Pubkey,
+    pub nonce: u64,
+    pub redeemer: Pubkey,
+    pub slot: u64,
+}
+
+impl RedeemedVoucher {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = AccountHeader::LEN + 32 + 8 + 32 + 8;
+
+    pub fn new(counter: Pubkey, nonce: u64, redeemer: Pubkey, slot: u64) -> Self {
+        Self {
+            header: AccountHeader {
+                account_type: AccountType::RedeemedVoucher,
+                version: Self::VERSION,
+            },
+            counter,
+            nonce,
+            redeemer,
+            slot,
+        }
+    }
+
+    pub fn unpack(data: &[u8]) -> io::Result<Self> {
+        let record = Self::deserialize(&mut &data[..])?;
+        record
+            .header
+            .check(AccountType::RedeemedVoucher, Self::VERSION)?;
+        Ok(record)
+    }
+
+    //Address(and bump seed) of the record of `nonce` redeemed against `counter`
+    pub fn find_address(counter: &Pubkey, nonce: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[VOUCHER_SEED, counter.as_ref(), &nonce.to_le_bytes()],
+            program_id,
+        )
+    }
+}
+
+//Rewards the contributor whose increament takes the counter to a multiple of `every`
+//with `reward` tokens of `mint`, minted by the mint authority of the counter. Lives at
+//the program address derived from `MILESTONES_SEED` and the counter
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct Milestones {
+    pub header: AccountHeader,
+    pub counter: Pubkey,
+    pub mint: Pubkey,
+    pub every: u64,
+    //Base units of the mint per milestone
+    pub reward: u64,
+}
+
+impl Milestones {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = AccountHeader::LEN + 32 + 32 + 8 + 8;
+
+    pub fn new(counter: Pubkey, mint: Pubkey, every: u64, reward: u64) -> Self {
+        Self {
+            header: AccountHeader {
+                account_type: AccountType::Milestones,
+                version: Self::VERSION,
+            },
+            counter,
+            mint,
+            every,
+            reward,
+        }
+    }
+
+    pub fn unpack(data: &[u8]) -> io::Result<Self> {
+        let milestones = Self::deserialize(&mut &data[..])?;
+        milestones
+            .header
+            .check(AccountType::Milestones, Self::VERSION)?;
+        if milestones.every == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Milestones must be apart",
+            ));
+        }
+        Ok(milestones)
+    }
+
+    //Whether going up from `previous` to `value` passes a milestone - a counter
+    //wrapping around to its lower bound does not
+    pub fn reached(&self, previous: u64, value: u64) -> bool {
+        previous < value && previous / self.every < value / self.every
+    }
+
+    //Address(and bump seed) of the milestones of `counter`
+    pub fn find_address(counter: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[MILESTONES_SEED, counter.as_ref()], program_id)
+    }
+
+    //Address(and bump seed) that must be the mint authority of the reward mint
+    pub fn find_mint_authority_address(counter: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[MINT_AUTHORITY_SEED, counter.as_ref()], program_id)
+    }
 }
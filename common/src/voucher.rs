@@ -0,0 +1,79 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+//Offsets into the data of an Ed25519 program instruction holding a single signature
+//with its public key, signature and message inline - as laid out by the Ed25519
+//program: signature count, padding, then the offsets(u16s) ahead of the data
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_DATA_START: usize = ED25519_OFFSETS_START + 14;
+const ED25519_PUBKEY_OFFSET: usize = ED25519_DATA_START;
+const ED25519_SIGNATURE_OFFSET: usize = ED25519_PUBKEY_OFFSET + 32;
+const ED25519_MESSAGE_OFFSET: usize = ED25519_SIGNATURE_OFFSET + 64;
+//Instruction index telling the Ed25519 program the data is within its own instruction
+const ED25519_THIS_INSTRUCTION: u16 = u16::MAX;
+
+//Approval to increament `counter` by `amount` - issued off-chain by the counter
+//authority, which signs the borsh encoded voucher. The Ed25519 program verifies the
+//signature in the instruction right ahead of `RedeemVoucher`. A nonce can be redeemed
+//once per counter, until the expiry(unix timestamp)
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Voucher {
+    pub counter: Pubkey,
+    pub amount: u64,
+    pub nonce: u64,
+    pub expiry: i64,
+}
+
+impl Voucher {
+    //The bytes the issuer signs
+    pub fn message(&self) -> Vec<u8> {
+        self.try_to_vec().unwrap()
+    }
+
+    //Ed25519 program instruction data verifying `signature` of the voucher by `issuer`
+    pub fn ed25519_instruction_data(&self, issuer: &Pubkey, signature: &[u8; 64]) -> Vec<u8> {
+        let message = self.message();
+        let offsets = [
+            ED25519_SIGNATURE_OFFSET as u16,
+            ED25519_THIS_INSTRUCTION,
+            ED25519_PUBKEY_OFFSET as u16,
+            ED25519_THIS_INSTRUCTION,
+            ED25519_MESSAGE_OFFSET as u16,
+            message.len() as u16,
+            ED25519_THIS_INSTRUCTION,
+        ];
+        let mut data = vec![1, 0];
+        for offset in offsets {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data.extend_from_slice(issuer.as_ref());
+        data.extend_from_slice(signature);
+        data.extend_from_slice(&message);
+        data
+    }
+
+    //Signer and message verified by an Ed25519 program instruction - None unless it
+    //holds exactly one signature with everything inline, as built by
+    //`ed25519_instruction_data`
+    pub fn verified_by(ed25519_instruction_data: &[u8]) -> Option<(Pubkey, &[u8])> {
+        let data = ed25519_instruction_data;
+        if data.len() < ED25519_DATA_START || data[0] != 1 {
+            return None;
+        }
+        let offset = |at: usize| {
+            let at = ED25519_OFFSETS_START + 2 * at;
+            u16::from_le_bytes([data[at], data[at + 1]])
+        };
+        //Signature, public key and message must come from this very instruction -
+        //otherwise the verified data could be anywhere in the transaction
+        if [offset(1), offset(3), offset(6)] != [ED25519_THIS_INSTRUCTION; 3] {
+            return None;
+        }
+        let pubkey_offset = offset(2) as usize;
+        let message_offset = offset(4) as usize;
+        let message_len = offset(5) as usize;
+        let pubkey = data.get(pubkey_offset..pubkey_offset + 32)?;
+        let message = data.get(message_offset..message_offset + message_len)?;
+        Some((Pubkey::new_from_array(pubkey.try_into().ok()?), message))
+    }
+}
@@ -0,0 +1,389 @@
+//Every counter layout ever written on-chain must remain readable and upgradeable.
+//Fixtures are raw bytes - as found in accounts - not produced by the structs under test
+
+use borsh::BorshSerialize;
+use common::{
+    AccessMode, AccountType, Bounds, Contribution, Counter, CounterV0, Delegate, History,
+    HistoryEntry, Leaderboard, Milestones, Multisig, OverflowPolicy, ProgramConfig, RateLimit,
+    RateUnit, RedeemedVoucher, Snapshot, TimeSeries, TokenGate, VersionedCounter,
+};
+use solana_program::pubkey::Pubkey;
+use std::io;
+
+//Version 0 - a bare little endian u64 count in an 8 byte account
+fn layout_v0(count: u64) -> Vec<u8> {
+    count.to_le_bytes().to_vec()
+}
+
+//Version 1 - the current layout: account type, version, count, authority, access
+//mode(authority only), rate limit(seconds, 30 second interval, at most 5 per 600
+//second window), increament clock, bounds(10 to 1000, wrap), fee(250 lamports),
+//treasury, token gate(at least 500 of `mint`), optional pending authority
+fn layout_v1(
+    count: u64,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    pending_authority: Option<&Pubkey>,
+) -> Vec<u8> {
+    let mut data = vec![1, 1];
+    data.extend_from_slice(&count.to_le_bytes());
+    data.extend_from_slice(authority.as_ref());
+    data.push(1);
+    data.push(1);
+    data.extend_from_slice(&30u64.to_le_bytes());
+    data.extend_from_slice(&600u64.to_le_bytes());
+    data.extend_from_slice(&5u32.to_le_bytes());
+    data.extend_from_slice(&77u64.to_le_bytes());
+    data.extend_from_slice(&1_650_000_000i64.to_le_bytes());
+    data.extend_from_slice(&1_649_999_400u64.to_le_bytes());
+    data.extend_from_slice(&2u32.to_le_bytes());
+    data.extend_from_slice(&10u64.to_le_bytes());
+    data.extend_from_slice(&1_000u64.to_le_bytes());
+    data.push(2);
+    data.extend_from_slice(&250u64.to_le_bytes());
+    data.extend_from_slice(authority.as_ref());
+    data.extend_from_slice(mint.as_ref());
+    data.extend_from_slice(&500u64.to_le_bytes());
+    match pending_authority {
+        Some(pending_authority) => {
+            data.push(1);
+            data.extend_from_slice(pending_authority.as_ref());
+        }
+        None => data.push(0),
+    }
+    data
+}
+
+#[test]
+fn reads_version_0() {
+    let owner = Pubkey::new_unique();
+    let versioned = VersionedCounter::unpack(&layout_v0(42)).unwrap();
+    assert_eq!(versioned, VersionedCounter::V0(CounterV0 { count: 42 }));
+    assert_eq!(versioned.version(), 0);
+    assert!(!versioned.has_authority());
+    //Open to anyone, unbounded and free to increament - as it was
+    let counter = versioned.upgrade(&owner);
+    assert_eq!(
+        counter,
+        Counter {
+            count: 42,
+            ..Counter::new(owner)
+        }
+    );
+    assert_eq!(counter.header.account_type, AccountType::Counter);
+    assert_eq!(counter.header.version, Counter::VERSION);
+    assert_eq!(counter.access_mode, AccessMode::Open);
+    assert_eq!(counter.bounds, Bounds::default());
+    assert_eq!(counter.fee, 0);
+    assert!(!counter.token_gate.is_enabled());
+}
+
+#[test]
+fn reads_version_1() {
+    let authority = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let pending_authority = Pubkey::new_unique();
+    let data = layout_v1(50, &authority, &mint, Some(&pending_authority));
+    let versioned = VersionedCounter::unpack(&data).unwrap();
+    assert_eq!(versioned.version(), 1);
+    assert!(versioned.has_authority());
+    //Authority recorded in the account wins over the migrating owner
+    let counter = versioned.upgrade(&Pubkey::new_unique());
+    assert_eq!(Counter::unpack(&data).unwrap(), counter);
+    assert_eq!(counter.count, 50);
+    assert_eq!(counter.authority, authority);
+    assert_eq!(counter.access_mode, AccessMode::AuthorityOnly);
+    assert_eq!(
+        counter.rate_limit,
+        RateLimit {
+            unit: RateUnit::Seconds,
+            min_interval: 30,
+            window: 600,
+            max_per_window: 5,
+        }
+    );
+    assert_eq!(counter.last_update_slot, 77);
+    assert_eq!(counter.last_update_timestamp, 1_650_000_000);
+    assert_eq!(counter.window_start, 1_649_999_400);
+    assert_eq!(counter.window_count, 2);
+    assert_eq!(
+        counter.bounds,
+        Bounds {
+            min: 10,
+            max: 1_000,
+            overflow_policy: OverflowPolicy::Wrap,
+        }
+    );
+    assert_eq!(counter.fee, 250);
+    assert_eq!(counter.treasury, authority);
+    assert_eq!(
+        counter.token_gate,
+        TokenGate {
+            mint,
+            min_balance: 500
+        }
+    );
+    assert_eq!(counter.pending_authority, Some(pending_authority));
+}
+
+#[test]
+fn ignores_trailing_account_space() {
+    let authority = Pubkey::new_unique();
+    let mut data = layout_v1(9, &authority, &Pubkey::new_unique(), None);
+    data.resize(Counter::LEN + 64, 0);
+    assert_eq!(VersionedCounter::unpack(&data).unwrap().version(), 1);
+    assert_eq!(Counter::unpack(&data).unwrap().count, 9);
+    //A cleared pending authority leaves its bytes behind in the account
+    let mut data = layout_v1(
+        3,
+        &authority,
+        &Pubkey::new_unique(),
+        Some(&Pubkey::new_unique()),
+    );
+    data.truncate(data.len() - 33);
+    data.push(0);
+    data.resize(Counter::LEN, u8::MAX);
+    let counter = Counter::unpack(&data).unwrap();
+    assert_eq!(counter.count, 3);
+    assert_eq!(counter.pending_authority, None);
+}
+
+#[test]
+fn current_layout_fits_account_space() {
+    let mut counter = Counter::new(Pubkey::new_unique());
+    counter.pending_authority = Some(Pubkey::new_unique());
+    let data = counter.try_to_vec().unwrap();
+    assert_eq!(data.len(), Counter::LEN);
+    assert_eq!(Counter::unpack(&data).unwrap(), counter);
+}
+
+//Reads a record(in its account data) and serializes it again
+type Reread = fn(&[u8]) -> io::Result<Vec<u8>>;
+
+fn reread<T: BorshSerialize>(record: io::Result<T>) -> io::Result<Vec<u8>> {
+    record?.try_to_vec()
+}
+
+//Counter history - the header and the entries(oldest first) behind the counter
+fn reread_history(data: &[u8]) -> io::Result<Vec<u8>> {
+    let account = [&[0; Counter::LEN][..], data].concat();
+    let history = History::unpack(&account)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "No history"))?;
+    let mut bytes = history.try_to_vec()?;
+    for entry in history.entries(&account)? {
+        bytes.extend(entry.try_to_vec()?);
+    }
+    Ok(bytes)
+}
+
+#[test]
+fn reads_records() {
+    let (counter, a, b) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let config = ProgramConfig {
+        paused: true,
+        pending_admin: Some(b),
+        ..ProgramConfig::new(a)
+    };
+    let contribution = Contribution {
+        total: 12,
+        last_slot: 345,
+        ..Contribution::new(counter, a)
+    };
+    let mut leaderboard = Leaderboard::new(counter, 3);
+    leaderboard.record(&a, 99);
+    //Capacity 2, both written, the next one goes first
+    let history = History {
+        capacity: 2,
+        len: 2,
+        next: 0,
+    };
+    let entries = [
+        HistoryEntry {
+            actor: a,
+            delta: 1,
+            slot: 7,
+            value: 3,
+        },
+        HistoryEntry {
+            actor: a,
+            delta: -3,
+            slot: 9,
+            value: 0,
+        },
+    ];
+    //Latest bucket first in the ring of 3
+    let time_series = TimeSeries {
+        latest_start: 2 * 86_400,
+        buckets: vec![5, 3, 4],
+        ..TimeSeries::new(counter, 86_400, 3, 0)
+    };
+    let snapshot = Snapshot::new(counter, 42, 77, 1_700_000_000, "epoch-1".to_string());
+    let padded = |mut data: Vec<u8>, len: usize| {
+        data.resize(len, 0);
+        data
+    };
+    //Name, raw bytes, the record they hold and how it is read
+    let records: Vec<(&str, Vec<u8>, Vec<u8>, Reread)> = vec![
+        (
+            "delegate",
+            [&[2, 1], counter.as_ref(), a.as_ref()].concat(),
+            Delegate::new(counter, a).try_to_vec().unwrap(),
+            |data| reread(Delegate::unpack(data)),
+        ),
+        (
+            "multisig",
+            padded(
+                [&[3, 1, 2, 2], a.as_ref(), b.as_ref()].concat(),
+                Multisig::LEN,
+            ),
+            Multisig::new(2, &[a, b]).try_to_vec().unwrap(),
+            |data| reread(Multisig::unpack(data)),
+        ),
+        (
+            "program config",
+            [&[4, 1], a.as_ref(), &[1, 1], b.as_ref()].concat(),
+            config.try_to_vec().unwrap(),
+            |data| reread(ProgramConfig::unpack(data)),
+        ),
+        (
+            "contribution",
+            [
+                &[5, 1],
+                counter.as_ref(),
+                a.as_ref(),
+                &12u64.to_le_bytes(),
+                &345u64.to_le_bytes(),
+            ]
+            .concat(),
+            contribution.try_to_vec().unwrap(),
+            |data| reread(Contribution::unpack(data)),
+        ),
+        (
+            "leaderboard",
+            padded(
+                [
+                    &[6, 1],
+                    counter.as_ref(),
+                    &[3, 1],
+                    a.as_ref(),
+                    &99u64.to_le_bytes(),
+                ]
+                .concat(),
+                Leaderboard::LEN,
+            ),
+            leaderboard.try_to_vec().unwrap(),
+            |data| reread(Leaderboard::unpack(data)),
+        ),
+        (
+            "history",
+            [
+                &[2, 0, 2, 0, 0, 0],
+                a.as_ref(),
+                &1i64.to_le_bytes(),
+                &7u64.to_le_bytes(),
+                &3u64.to_le_bytes(),
+                a.as_ref(),
+                &(-3i64).to_le_bytes(),
+                &9u64.to_le_bytes(),
+                &0u64.to_le_bytes(),
+            ]
+            .concat(),
+            [history.try_to_vec().unwrap(), entries.try_to_vec().unwrap()].concat(),
+            reread_history,
+        ),
+        (
+            "time series",
+            [
+                &[7, 1],
+                counter.as_ref(),
+                &86_400u32.to_le_bytes(),
+                &(2 * 86_400i64).to_le_bytes(),
+                &0u16.to_le_bytes(),
+                &3u32.to_le_bytes(),
+                &5u64.to_le_bytes(),
+                &3u64.to_le_bytes(),
+                &4u64.to_le_bytes(),
+            ]
+            .concat(),
+            time_series.try_to_vec().unwrap(),
+            |data| reread(TimeSeries::unpack(data)),
+        ),
+        (
+            "snapshot",
+            [
+                &[8, 1],
+                counter.as_ref(),
+                &42u64.to_le_bytes(),
+                &77u64.to_le_bytes(),
+                &1_700_000_000i64.to_le_bytes(),
+                &7u32.to_le_bytes(),
+                b"epoch-1",
+            ]
+            .concat(),
+            snapshot.try_to_vec().unwrap(),
+            |data| reread(Snapshot::unpack(data)),
+        ),
+        (
+            "redeemed voucher",
+            [
+                &[9, 1],
+                counter.as_ref(),
+                &3u64.to_le_bytes(),
+                a.as_ref(),
+                &77u64.to_le_bytes(),
+            ]
+            .concat(),
+            RedeemedVoucher::new(counter, 3, a, 77)
+                .try_to_vec()
+                .unwrap(),
+            |data| reread(RedeemedVoucher::unpack(data)),
+        ),
+        (
+            "milestones",
+            [
+                &[10, 1],
+                counter.as_ref(),
+                b.as_ref(),
+                &100u64.to_le_bytes(),
+                &5u64.to_le_bytes(),
+            ]
+            .concat(),
+            Milestones::new(counter, b, 100, 5).try_to_vec().unwrap(),
+            |data| reread(Milestones::unpack(data)),
+        ),
+    ];
+    for (name, data, record, reread) in &records {
+        assert_eq!(data, record, "{} layout", name);
+        assert_eq!(reread(data).unwrap(), *record, "{} read back", name);
+        assert!(
+            reread(&data[..data.len() - 1]).is_err(),
+            "{} cut short",
+            name
+        );
+        //No record passes for a counter - nor for a record of another kind
+        assert!(Counter::unpack(data).is_err(), "{} read as a counter", name);
+        for (other, _, _, reread) in records.iter().filter(|(other, ..)| other != name) {
+            assert!(reread(data).is_err(), "{} read as {}", name, other);
+        }
+    }
+}
+
+#[test]
+fn rejects_outdated_layout_without_migration() {
+    assert!(Counter::unpack(&layout_v0(42)).is_err());
+    let mut data = layout_v0(42);
+    data.resize(Counter::LEN, 0);
+    assert!(Counter::unpack(&data).is_err());
+}
+
+#[test]
+fn rejects_uninitialized_and_unknown_versions() {
+    assert!(VersionedCounter::unpack(&[0; Counter::LEN]).is_err());
+    let mut data = layout_v1(1, &Pubkey::new_unique(), &Pubkey::new_unique(), None);
+    data[1] = u8::MAX;
+    assert!(VersionedCounter::unpack(&data).is_err());
+}
@@ -0,0 +1,158 @@
+//Cross program invocation of the counter program
+//
+//Builders for the counter instructions other programs send along with helpers invoking
+//them. The counter program is not tied to an address - builders take its program id.
+//Every instruction passes the program config account last, as the counter program
+//expects. Increaments pass none of the optional accounts - counters charging a fee or
+//gated on a token can not be increamented this way, contributions are not tracked.
+//Programs signing for a program derived counter authority or actor pass its seeds
+use borsh::BorshSerialize;
+use common::{CounterInstruction, ProgramConfig};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+//Accounts an invocation on a counter passes along
+pub struct CounterAccounts<'a, 'info> {
+    pub counter_program: &'a AccountInfo<'info>,
+    pub counter: &'a AccountInfo<'info>,
+    //Actor of increaments, the counter authority otherwise - signing
+    pub signer: &'a AccountInfo<'info>,
+    //Program config of the counter program - address from `ProgramConfig::find_address`
+    pub config: &'a AccountInfo<'info>,
+}
+
+pub fn increament(
+    program_id: &Pubkey,
+    counter: &Pubkey,
+    actor: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    instruction(
+        program_id,
+        &CounterInstruction::Increament,
+        counter,
+        actor,
+        &ProgramConfig::find_address(program_id).0,
+    )
+}
+
+pub fn increment_if_equals(
+    program_id: &Pubkey,
+    counter: &Pubkey,
+    actor: &Pubkey,
+    expected: u64,
+) -> Result<Instruction, ProgramError> {
+    instruction(
+        program_id,
+        &CounterInstruction::IncrementIfEquals { expected },
+        counter,
+        actor,
+        &ProgramConfig::find_address(program_id).0,
+    )
+}
+
+pub fn reset(
+    program_id: &Pubkey,
+    counter: &Pubkey,
+    authority: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    instruction(
+        program_id,
+        &CounterInstruction::Reset,
+        counter,
+        authority,
+        &ProgramConfig::find_address(program_id).0,
+    )
+}
+
+//Take over as the counter authority - `new_authority` must be the pending authority
+pub fn accept_authority(
+    program_id: &Pubkey,
+    counter: &Pubkey,
+    new_authority: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    instruction(
+        program_id,
+        &CounterInstruction::AcceptAuthority,
+        counter,
+        new_authority,
+        &ProgramConfig::find_address(program_id).0,
+    )
+}
+
+pub fn invoke_increament(accounts: &CounterAccounts, signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+    invoke(accounts, &CounterInstruction::Increament, signers_seeds)
+}
+
+pub fn invoke_increment_if_equals(
+    accounts: &CounterAccounts,
+    expected: u64,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke(
+        accounts,
+        &CounterInstruction::IncrementIfEquals { expected },
+        signers_seeds,
+    )
+}
+
+pub fn invoke_reset(accounts: &CounterAccounts, signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+    invoke(accounts, &CounterInstruction::Reset, signers_seeds)
+}
+
+pub fn invoke_accept_authority(
+    accounts: &CounterAccounts,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke(
+        accounts,
+        &CounterInstruction::AcceptAuthority,
+        signers_seeds,
+    )
+}
+
+//The config account comes from the accounts - no need to derive its address on chain
+fn invoke(
+    accounts: &CounterAccounts,
+    counter_instruction: &CounterInstruction,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction(
+        accounts.counter_program.key,
+        counter_instruction,
+        accounts.counter.key,
+        accounts.signer.key,
+        accounts.config.key,
+    )?;
+    invoke_signed(
+        &instruction,
+        &[
+            accounts.counter.clone(),
+            accounts.signer.clone(),
+            accounts.config.clone(),
+            accounts.counter_program.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+fn instruction(
+    program_id: &Pubkey,
+    counter_instruction: &CounterInstruction,
+    counter: &Pubkey,
+    signer: &Pubkey,
+    config: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &counter_instruction.try_to_vec()?,
+        vec![
+            AccountMeta::new(*counter, false),
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(*config, false),
+        ],
+    ))
+}
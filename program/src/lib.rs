@@ -1,2 +1,4 @@
+#[cfg(feature = "no-entrypoint")]
+pub mod cpi;
 pub mod entrypoint;
 pub mod processor;
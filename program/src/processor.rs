@@ -1,36 +1,2119 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use common::error::CounterError;
+use common::event::CounterEvent;
 use common::instruction::CounterInstruction;
-use common::state::Counter;
+use common::legacy::VersionedCounter;
+use common::state::{
+    AccessMode, AccountHeader, AccountType, Bounds, Contribution, Counter, Delegate, History,
+    HistoryEntry, Leaderboard, Milestones, Multisig, ProgramConfig, RateLimit, RateUnit,
+    RedeemedVoucher, Snapshot, TimeSeries, TokenGate, CONFIG_SEED, CONTRIBUTION_SEED,
+    COUNTER_ACCOUNT_SEED, DELEGATE_SEED, LEADERBOARD_SEED, MAX_HISTORY_ENTRIES,
+    MAX_LEADERBOARD_ENTRIES, MAX_SIGNERS, MAX_SNAPSHOT_LABEL_LEN, MAX_TIME_BUCKETS,
+    MILESTONES_SEED, MINT_AUTHORITY_SEED, SNAPSHOT_SEED, TIME_SERIES_SEED, TREASURY_SEED,
+    VOUCHER_SEED,
+};
+use common::voucher::Voucher;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    ed25519_program,
     entrypoint::ProgramResult,
+    log::sol_log_data,
     msg,
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
     pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program,
+    sysvar::{
+        instructions::{load_current_index_checked, load_instruction_at_checked},
+        Sysvar,
+    },
 };
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::{Account as TokenAccount, Mint};
 
 pub struct Processor {}
 
+//Accounts an instruction raising the count passes along besides the counter - the fee
+//accounts, then in the order of `CounterInstruction::Increament` from the contribution
+//record on. Only those given are looked at
+struct IncreaseAccounts<'a, 'info> {
+    payer: Option<&'a AccountInfo<'info>>,
+    treasury: Option<&'a AccountInfo<'info>>,
+    system_program: Option<&'a AccountInfo<'info>>,
+    contribution: Option<&'a AccountInfo<'info>>,
+    leaderboard: Option<&'a AccountInfo<'info>>,
+    time_series: Option<&'a AccountInfo<'info>>,
+    token: Option<&'a AccountInfo<'info>>,
+    milestones: Option<&'a AccountInfo<'info>>,
+    //Reward mint, mint authority, token account of the actor and token program
+    rewards: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> IncreaseAccounts<'a, 'info> {
+    fn new(
+        payer: Option<&'a AccountInfo<'info>>,
+        treasury: Option<&'a AccountInfo<'info>>,
+        system_program: Option<&'a AccountInfo<'info>>,
+        accounts_iter: &mut std::slice::Iter<'a, AccountInfo<'info>>,
+    ) -> Self {
+        let mut next = || next_account_info(accounts_iter).ok();
+        let (contribution, leaderboard, time_series, token, milestones) =
+            (next(), next(), next(), next(), next());
+        Self {
+            payer,
+            treasury,
+            system_program,
+            contribution,
+            leaderboard,
+            time_series,
+            token,
+            milestones,
+            rewards: accounts_iter.as_slice(),
+        }
+    }
+}
+
 impl Processor {
     pub fn process_instruction(
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
         accounts: &[AccountInfo],
         instruction_data: &[u8],
     ) -> ProgramResult {
         let instruction = CounterInstruction::try_from_slice(instruction_data)
             .map_err(|_| ProgramError::InvalidInstructionData)?;
+        let accounts = match instruction.is_config_instruction() {
+            true => accounts,
+            //Every other instruction may change counters - the program config comes last
+            false => {
+                let (config_acc_info, accounts) = accounts
+                    .split_last()
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?;
+                Self::check_not_paused(program_id, config_acc_info)?;
+                accounts
+            }
+        };
         match instruction {
             CounterInstruction::Increament => {
                 msg!("Instruction increament");
-                let accounts_iter = &mut accounts.iter();
-                let counter_acc_info = next_account_info(accounts_iter)?;
-                let mut counter = Counter::try_from_slice(&counter_acc_info.data.borrow())?;
-                counter.count += 1;
-                msg!("Updating counter {}", counter.count);
-                counter.serialize(&mut *counter_acc_info.data.borrow_mut())?;
+                Self::process_increament(program_id, accounts, None)
+            }
+            CounterInstruction::Reset => {
+                msg!("Instruction reset");
+                Self::process_reset(program_id, accounts)
+            }
+            CounterInstruction::Close => {
+                msg!("Instruction close");
+                Self::process_close(program_id, accounts)
+            }
+            CounterInstruction::Initialize {
+                bounds,
+                fee,
+                history_capacity,
+            } => {
+                msg!("Instruction initialize");
+                Self::process_initialize(program_id, accounts, bounds, fee, history_capacity)
+            }
+            CounterInstruction::Migrate => {
+                msg!("Instruction migrate");
+                Self::process_migrate(program_id, accounts)
+            }
+            CounterInstruction::ProposeAuthority { new_authority } => {
+                msg!("Instruction propose authority");
+                Self::process_propose_authority(program_id, accounts, new_authority)
+            }
+            CounterInstruction::AcceptAuthority => {
+                msg!("Instruction accept authority");
+                Self::process_accept_authority(program_id, accounts)
+            }
+            CounterInstruction::CancelAuthorityTransfer => {
+                msg!("Instruction cancel authority transfer");
+                Self::process_cancel_authority_transfer(program_id, accounts)
+            }
+            CounterInstruction::SetAccessMode { access_mode } => {
+                msg!("Instruction set access mode");
+                Self::process_set_access_mode(program_id, accounts, access_mode)
+            }
+            CounterInstruction::AddDelegate { delegate } => {
+                msg!("Instruction add delegate");
+                Self::process_add_delegate(program_id, accounts, delegate)
+            }
+            CounterInstruction::RemoveDelegate { delegate } => {
+                msg!("Instruction remove delegate");
+                Self::process_remove_delegate(program_id, accounts, delegate)
+            }
+            CounterInstruction::InitializeMultisig { threshold } => {
+                msg!("Instruction initialize multisig");
+                Self::process_initialize_multisig(program_id, accounts, threshold)
+            }
+            CounterInstruction::SetRateLimit { rate_limit } => {
+                msg!("Instruction set rate limit");
+                Self::process_set_rate_limit(program_id, accounts, rate_limit)
+            }
+            CounterInstruction::CompareAndSet { expected, new } => {
+                msg!("Instruction compare and set");
+                Self::process_compare_and_set(program_id, accounts, expected, new)
+            }
+            CounterInstruction::IncrementIfEquals { expected } => {
+                msg!("Instruction increament if equals");
+                Self::process_increament(program_id, accounts, Some(expected))
+            }
+            CounterInstruction::InitializeConfig { admin } => {
+                msg!("Instruction initialize config");
+                Self::process_initialize_config(program_id, accounts, admin)
+            }
+            CounterInstruction::Pause => {
+                msg!("Instruction pause");
+                Self::process_set_paused(program_id, accounts, true)
+            }
+            CounterInstruction::Unpause => {
+                msg!("Instruction unpause");
+                Self::process_set_paused(program_id, accounts, false)
+            }
+            CounterInstruction::ProposeAdmin { new_admin } => {
+                msg!("Instruction propose admin");
+                Self::process_propose_admin(program_id, accounts, new_admin)
+            }
+            CounterInstruction::AcceptAdmin => {
+                msg!("Instruction accept admin");
+                Self::process_accept_admin(program_id, accounts)
+            }
+            CounterInstruction::WithdrawFees { lamports } => {
+                msg!("Instruction withdraw fees");
+                Self::process_withdraw_fees(program_id, accounts, lamports)
+            }
+            CounterInstruction::CreateLeaderboard { capacity } => {
+                msg!("Instruction create leaderboard");
+                Self::process_create_leaderboard(program_id, accounts, capacity)
+            }
+            CounterInstruction::CreateTimeSeries {
+                bucket_width,
+                bucket_count,
+            } => {
+                msg!("Instruction create time series");
+                Self::process_create_time_series(program_id, accounts, bucket_width, bucket_count)
+            }
+            CounterInstruction::Snapshot { label } => {
+                msg!("Instruction snapshot");
+                Self::process_snapshot(program_id, accounts, label)
+            }
+            CounterInstruction::RedeemVoucher {
+                amount,
+                nonce,
+                expiry,
+            } => {
+                msg!("Instruction redeem voucher");
+                Self::process_redeem_voucher(program_id, accounts, amount, nonce, expiry)
+            }
+            CounterInstruction::SetTokenGate { token_gate } => {
+                msg!("Instruction set token gate");
+                Self::process_set_token_gate(program_id, accounts, token_gate)
+            }
+            CounterInstruction::CreateMilestones {
+                mint,
+                every,
+                reward,
+            } => {
+                msg!("Instruction create milestones");
+                Self::process_create_milestones(program_id, accounts, mint, every, reward)
+            }
+        }
+    }
+
+    //Increament the counter - if `expected` is given, only when the counter is at
+    //that value
+    fn process_increament(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        expected: Option<u64>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let actor = next_account_info(accounts_iter)
+            .ok()
+            .filter(|actor_acc_info| actor_acc_info.is_signer)
+            .map(|actor_acc_info| *actor_acc_info.key);
+        let delegate_acc_info = next_account_info(accounts_iter).ok();
+        let payer_acc_info = next_account_info(accounts_iter).ok();
+        let treasury_acc_info = next_account_info(accounts_iter).ok();
+        let system_program_acc_info = next_account_info(accounts_iter).ok();
+        let increase_accounts = IncreaseAccounts::new(
+            payer_acc_info,
+            treasury_acc_info,
+            system_program_acc_info,
+            accounts_iter,
+        );
+        let mut counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_access(
+            program_id,
+            &counter,
+            counter_acc_info.key,
+            actor.as_ref(),
+            delegate_acc_info,
+        )?;
+        if let Some(expected) = expected {
+            Self::check_value(&counter, expected)?;
+        }
+        let previous_value = Self::increase(
+            program_id,
+            counter_acc_info.key,
+            &mut counter,
+            actor.as_ref(),
+            1,
+            &increase_accounts,
+        )?;
+        msg!("Updating counter {}", counter.count);
+        Self::record_history(
+            counter_acc_info,
+            actor.as_ref(),
+            previous_value,
+            counter.count,
+        )?;
+        counter.serialize(&mut &mut counter_acc_info.data.borrow_mut()[..])?;
+        Self::return_counter(&counter)?;
+        Self::emit(&CounterEvent::Incremented {
+            counter: *counter_acc_info.key,
+            by: 1,
+            new_value: counter.count,
+            actor,
+        })
+    }
+
+    //Raise the counter by `units` - what every instruction raising the count goes
+    //through: the token gate, the rate limit, the bounds, the fee, the contribution and
+    //its rank, the time series and the milestone reward. Returns the value before
+    fn increase(
+        program_id: &Pubkey,
+        counter_key: &Pubkey,
+        counter: &mut Counter,
+        actor: Option<&Pubkey>,
+        units: u64,
+        accounts: &IncreaseAccounts,
+    ) -> Result<u64, ProgramError> {
+        Self::check_token_holding(counter, actor, accounts.token)?;
+        let clock = Clock::get()?;
+        Self::record_increament(counter, &clock)?;
+        let previous_value = counter.count;
+        counter.count = counter.bounds.add(counter.count, units).ok_or_else(|| {
+            msg!(
+                "Counter would go past its upper bound {}",
+                counter.bounds.max
+            );
+            ProgramError::from(CounterError::OutOfBounds)
+        })?;
+        if counter.fee > 0 {
+            Self::charge_fee(
+                counter,
+                counter_key,
+                accounts.payer.ok_or(ProgramError::NotEnoughAccountKeys)?,
+                accounts
+                    .treasury
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?,
+                accounts
+                    .system_program
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?,
+                units,
+            )?;
+        }
+        if let Some(contribution_acc_info) = accounts.contribution {
+            let contributor = actor.ok_or_else(|| {
+                msg!("Contributions are tracked for signing actors only");
+                ProgramError::MissingRequiredSignature
+            })?;
+            let contribution = Self::record_contribution(
+                program_id,
+                counter_key,
+                contributor,
+                contribution_acc_info,
+                accounts.payer.ok_or(ProgramError::NotEnoughAccountKeys)?,
+                accounts
+                    .system_program
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?,
+                units,
+            )?;
+            if let Some(leaderboard_acc_info) = accounts.leaderboard {
+                Self::rank_contribution(program_id, &contribution, leaderboard_acc_info)?;
+            }
+        }
+        if let Some(time_series_acc_info) = accounts.time_series {
+            Self::count_in_time_series(
+                program_id,
+                counter_key,
+                time_series_acc_info,
+                clock.unix_timestamp,
+                units,
+            )?;
+        }
+        if let Some(milestones_acc_info) = accounts.milestones {
+            Self::reward_milestone(
+                program_id,
+                counter_key,
+                actor,
+                previous_value,
+                counter.count,
+                milestones_acc_info,
+                accounts.rewards,
+            )?;
+        }
+        Ok(previous_value)
+    }
+
+    //Transfer the fee for `units` increamented from the payer into the counter treasury
+    fn charge_fee<'a>(
+        counter: &Counter,
+        counter_key: &Pubkey,
+        payer_acc_info: &AccountInfo<'a>,
+        treasury_acc_info: &AccountInfo<'a>,
+        system_program_acc_info: &AccountInfo<'a>,
+        units: u64,
+    ) -> ProgramResult {
+        if !payer_acc_info.is_signer {
+            msg!("Counter charges a fee - the fee payer must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if *treasury_acc_info.key != counter.treasury {
+            msg!("Counter treasury {} expected", counter.treasury);
+            return Err(ProgramError::InvalidArgument);
+        }
+        if *system_program_acc_info.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let lamports = counter
+            .fee
+            .checked_mul(units)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        invoke(
+            &system_instruction::transfer(payer_acc_info.key, treasury_acc_info.key, lamports),
+            &[
+                payer_acc_info.clone(),
+                treasury_acc_info.clone(),
+                system_program_acc_info.clone(),
+            ],
+        )?;
+        msg!("Fee of {} lamports paid", lamports);
+        Self::emit(&CounterEvent::FeePaid {
+            counter: *counter_key,
+            payer: *payer_acc_info.key,
+            lamports,
+        })
+    }
+
+    //Add `units` to the tally of the contributor - creating its record, paid for by the
+    //payer, on the first contribution. Returns the updated tally
+    fn record_contribution<'a>(
+        program_id: &Pubkey,
+        counter_key: &Pubkey,
+        contributor: &Pubkey,
+        contribution_acc_info: &AccountInfo<'a>,
+        payer_acc_info: &AccountInfo<'a>,
+        system_program_acc_info: &AccountInfo<'a>,
+        units: u64,
+    ) -> Result<Contribution, ProgramError> {
+        let (address, bump) = Contribution::find_address(counter_key, contributor, program_id);
+        if address != *contribution_acc_info.key {
+            msg!("Contribution record of {} expected", contributor);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let mut contribution = match contribution_acc_info.owner == program_id {
+            true => Contribution::unpack(&contribution_acc_info.data.borrow()).map_err(|err| {
+                msg!("Invalid contribution record {}", err);
+                ProgramError::InvalidAccountData
+            })?,
+            false => {
+                if *system_program_acc_info.key != system_program::id() {
+                    return Err(ProgramError::IncorrectProgramId);
+                }
+                invoke_signed(
+                    &system_instruction::create_account(
+                        payer_acc_info.key,
+                        contribution_acc_info.key,
+                        Rent::get()?.minimum_balance(Contribution::LEN),
+                        Contribution::LEN as u64,
+                        program_id,
+                    ),
+                    &[
+                        payer_acc_info.clone(),
+                        contribution_acc_info.clone(),
+                        system_program_acc_info.clone(),
+                    ],
+                    &[&[
+                        CONTRIBUTION_SEED,
+                        counter_key.as_ref(),
+                        contributor.as_ref(),
+                        &[bump],
+                    ]],
+                )?;
+                msg!("First contribution of {}", contributor);
+                Contribution::new(*counter_key, *contributor)
+            }
+        };
+        contribution.total = contribution.total.saturating_add(units);
+        contribution.last_slot = Clock::get()?.slot;
+        contribution.serialize(&mut &mut contribution_acc_info.data.borrow_mut()[..])?;
+        Ok(contribution)
+    }
+
+    //Rank the contributor on the counter leaderboard - nothing to do until the
+    //leaderboard has been created. The update is bounded by `MAX_LEADERBOARD_ENTRIES`
+    fn rank_contribution(
+        program_id: &Pubkey,
+        contribution: &Contribution,
+        leaderboard_acc_info: &AccountInfo,
+    ) -> ProgramResult {
+        if *leaderboard_acc_info.key
+            != Leaderboard::find_address(&contribution.counter, program_id).0
+        {
+            msg!("Leaderboard of counter {} expected", contribution.counter);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if leaderboard_acc_info.owner != program_id {
+            return Ok(());
+        }
+        let mut leaderboard =
+            Leaderboard::unpack(&leaderboard_acc_info.data.borrow()).map_err(|err| {
+                msg!("Invalid leaderboard {}", err);
+                ProgramError::InvalidAccountData
+            })?;
+        if leaderboard.record(&contribution.contributor, contribution.total) {
+            leaderboard.serialize(&mut &mut leaderboard_acc_info.data.borrow_mut()[..])?;
+        }
+        Ok(())
+    }
+
+    //Count the increament in the current bucket of the counter time series, rolling
+    //over to it first - nothing to do until the time series has been created
+    fn count_in_time_series(
+        program_id: &Pubkey,
+        counter_key: &Pubkey,
+        time_series_acc_info: &AccountInfo,
+        unix_timestamp: i64,
+        units: u64,
+    ) -> ProgramResult {
+        if *time_series_acc_info.key != TimeSeries::find_address(counter_key, program_id).0 {
+            msg!("Time series of counter {} expected", counter_key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if time_series_acc_info.owner != program_id {
+            return Ok(());
+        }
+        let mut time_series =
+            TimeSeries::unpack(&time_series_acc_info.data.borrow()).map_err(|err| {
+                msg!("Invalid time series {}", err);
+                ProgramError::InvalidAccountData
+            })?;
+        time_series.record(unix_timestamp, units);
+        time_series.serialize(&mut &mut time_series_acc_info.data.borrow_mut()[..])?;
+        Ok(())
+    }
+
+    //Mint the reward to the actor if the increament from `previous` to `value` reached a
+    //milestone - if the counter has milestones
+    fn reward_milestone(
+        program_id: &Pubkey,
+        counter_key: &Pubkey,
+        actor: Option<&Pubkey>,
+        previous: u64,
+        value: u64,
+        milestones_acc_info: &AccountInfo,
+        reward_acc_infos: &[AccountInfo],
+    ) -> ProgramResult {
+        if *milestones_acc_info.key != Milestones::find_address(counter_key, program_id).0 {
+            msg!("Milestones of counter {} expected", counter_key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if milestones_acc_info.owner != program_id {
+            return Ok(());
+        }
+        let milestones = Milestones::unpack(&milestones_acc_info.data.borrow()).map_err(|err| {
+            msg!("Invalid milestones {}", err);
+            ProgramError::InvalidAccountData
+        })?;
+        if !milestones.reached(previous, value) {
+            return Ok(());
+        }
+        let contributor = actor.ok_or_else(|| {
+            msg!("Milestone rewards go to signing actors only");
+            ProgramError::MissingRequiredSignature
+        })?;
+        let accounts_iter = &mut reward_acc_infos.iter();
+        let mint_acc_info = next_account_info(accounts_iter)?;
+        let mint_authority_acc_info = next_account_info(accounts_iter)?;
+        let destination_acc_info = next_account_info(accounts_iter)?;
+        let token_program_acc_info = next_account_info(accounts_iter)?;
+        if *mint_acc_info.key != milestones.mint
+            || *destination_acc_info.key
+                != get_associated_token_address(contributor, &milestones.mint)
+        {
+            msg!(
+                "Rewards are minted to the associated token account of {} for mint {}",
+                contributor,
+                milestones.mint
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (mint_authority, bump) =
+            Milestones::find_mint_authority_address(counter_key, program_id);
+        if mint_authority != *mint_authority_acc_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if *token_program_acc_info.key != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        invoke_signed(
+            &spl_token::instruction::mint_to(
+                token_program_acc_info.key,
+                mint_acc_info.key,
+                destination_acc_info.key,
+                mint_authority_acc_info.key,
+                &[],
+                milestones.reward,
+            )?,
+            &[
+                mint_acc_info.clone(),
+                destination_acc_info.clone(),
+                mint_authority_acc_info.clone(),
+                token_program_acc_info.clone(),
+            ],
+            &[&[MINT_AUTHORITY_SEED, counter_key.as_ref(), &[bump]]],
+        )?;
+        msg!("Milestone {} reached by {}", value, contributor);
+        Self::emit(&CounterEvent::MilestoneReached {
+            counter: *counter_key,
+            value,
+            contributor: *contributor,
+            reward: milestones.reward,
+        })
+    }
+
+    fn process_compare_and_set(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        expected: u64,
+        new: u64,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let mut counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        Self::check_value(&counter, expected)?;
+        if !counter.bounds.contains(new) {
+            msg!(
+                "{} is outside the counter bounds {} to {}",
+                new,
+                counter.bounds.min,
+                counter.bounds.max
+            );
+            return Err(CounterError::OutOfBounds.into());
+        }
+        let previous_value = counter.count;
+        counter.count = new;
+        msg!("Counter set to {}", new);
+        Self::record_history(
+            counter_acc_info,
+            Some(authority_acc_info.key),
+            previous_value,
+            new,
+        )?;
+        counter.serialize(&mut &mut counter_acc_info.data.borrow_mut()[..])?;
+        Self::return_counter(&counter)?;
+        Self::emit(&CounterEvent::Set {
+            counter: *counter_acc_info.key,
+            previous_value,
+            new_value: new,
+            actor: *authority_acc_info.key,
+        })
+    }
+
+    //Add the change to the counter history - if the counter keeps one. Comes before the
+    //counter is serialized, which moves the account data reference past the counter
+    fn record_history(
+        counter_acc_info: &AccountInfo,
+        actor: Option<&Pubkey>,
+        previous_value: u64,
+        value: u64,
+    ) -> ProgramResult {
+        let mut data = counter_acc_info.data.borrow_mut();
+        let mut history = match History::unpack(&data).map_err(|err| {
+            msg!("Invalid counter history {}", err);
+            ProgramError::InvalidAccountData
+        })? {
+            Some(history) => history,
+            None => return Ok(()),
+        };
+        let delta =
+            (value as i128 - previous_value as i128).clamp(i64::MIN as i128, i64::MAX as i128);
+        let entry = HistoryEntry {
+            actor: actor.copied().unwrap_or_default(),
+            delta: delta as i64,
+            slot: Clock::get()?.slot,
+            value,
+        };
+        history.push(&mut data, &entry)?;
+        Ok(())
+    }
+
+    //The counter must still be at the value the caller based its update on
+    fn check_value(counter: &Counter, expected: u64) -> ProgramResult {
+        if counter.count != expected {
+            msg!("Counter is at {}, expected {}", counter.count, expected);
+            return Err(CounterError::ValueMismatch.into());
+        }
+        Ok(())
+    }
+
+    fn process_reset(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let mut counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        let previous_value = counter.count;
+        counter.count = counter.bounds.min;
+        msg!("Counter reset");
+        Self::record_history(
+            counter_acc_info,
+            Some(authority_acc_info.key),
+            previous_value,
+            counter.count,
+        )?;
+        counter.serialize(&mut &mut counter_acc_info.data.borrow_mut()[..])?;
+        Self::return_counter(&counter)?;
+        Self::emit(&CounterEvent::Reset {
+            counter: *counter_acc_info.key,
+            previous_value,
+            actor: *authority_acc_info.key,
+        })
+    }
+
+    fn process_close(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let destination_acc_info = next_account_info(accounts_iter)?;
+        //Draining the counter into itself would leave it open with its lamports
+        if destination_acc_info.key == counter_acc_info.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        //Drain the lamports - the runtime purges accounts left with zero lamports
+        let lamports = counter_acc_info.lamports();
+        **destination_acc_info.lamports.borrow_mut() = destination_acc_info
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(ProgramError::InvalidArgument)?;
+        **counter_acc_info.lamports.borrow_mut() = 0;
+        counter_acc_info.data.borrow_mut().fill(0);
+        msg!("Counter closed, {} lamports reclaimed", lamports);
+        Self::emit(&CounterEvent::Closed {
+            counter: *counter_acc_info.key,
+            lamports,
+            actor: *authority_acc_info.key,
+        })
+    }
+
+    //Hand the post-operation counter state back to the caller as return data - saves
+    //callers(off-chain or cpi) from re-reading the account
+    fn return_counter(counter: &Counter) -> ProgramResult {
+        set_return_data(&counter.try_to_vec()?);
+        Ok(())
+    }
+
+    //Log the borsh encoded event - clients pick it up from the `Program data:` log lines
+    fn emit(event: &CounterEvent) -> ProgramResult {
+        sol_log_data(&[&event.try_to_vec()?]);
+        Ok(())
+    }
+
+    fn process_initialize(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        bounds: Bounds,
+        fee: u64,
+        history_capacity: u16,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let treasury_acc_info = next_account_info(accounts_iter).ok();
+        let system_program_acc_info = next_account_info(accounts_iter).ok();
+        if counter_acc_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if !authority_acc_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if counter_acc_info.data_len() < Counter::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let header = AccountHeader::deserialize(&mut &counter_acc_info.data.borrow()[..])?;
+        if header.account_type != AccountType::Uninitialized {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if !bounds.is_valid() {
+            msg!(
+                "Lower bound {} is above the upper bound {}",
+                bounds.min,
+                bounds.max
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if history_capacity > MAX_HISTORY_ENTRIES {
+            msg!("History can hold at most {} changes", MAX_HISTORY_ENTRIES);
+            return Err(ProgramError::InvalidArgument);
+        }
+        let mut counter = Counter::with_bounds(*authority_acc_info.key, bounds);
+        if fee > 0 {
+            counter.fee = fee;
+            counter.treasury = Self::fund_treasury(
+                program_id,
+                counter_acc_info.key,
+                authority_acc_info,
+                treasury_acc_info.ok_or(ProgramError::NotEnoughAccountKeys)?,
+                system_program_acc_info.ok_or(ProgramError::NotEnoughAccountKeys)?,
+            )?;
+            msg!("Fee of {} lamports per increament", fee);
+        }
+        if history_capacity > 0 {
+            Self::allocate_history(
+                counter_acc_info,
+                authority_acc_info,
+                system_program_acc_info.ok_or(ProgramError::NotEnoughAccountKeys)?,
+                history_capacity,
+            )?;
+            msg!("Keeping the last {} changes", history_capacity);
+        }
+        counter.serialize(&mut &mut counter_acc_info.data.borrow_mut()[..])?;
+        Self::emit(&CounterEvent::Initialized {
+            counter: *counter_acc_info.key,
+        })
+    }
+
+    //Bring the treasury of a new counter up to the rent exempt minimum - fees paid into
+    //it are then never lost to rent
+    fn fund_treasury<'a>(
+        program_id: &Pubkey,
+        counter_key: &Pubkey,
+        funder_acc_info: &AccountInfo<'a>,
+        treasury_acc_info: &AccountInfo<'a>,
+        system_program_acc_info: &AccountInfo<'a>,
+    ) -> Result<Pubkey, ProgramError> {
+        if *treasury_acc_info.key != Counter::find_treasury_address(counter_key, program_id).0 {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if *system_program_acc_info.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let shortfall = Rent::get()?
+            .minimum_balance(0)
+            .saturating_sub(treasury_acc_info.lamports());
+        if shortfall > 0 {
+            invoke(
+                &system_instruction::transfer(
+                    funder_acc_info.key,
+                    treasury_acc_info.key,
+                    shortfall,
+                ),
+                &[
+                    funder_acc_info.clone(),
+                    treasury_acc_info.clone(),
+                    system_program_acc_info.clone(),
+                ],
+            )?;
+        }
+        Ok(*treasury_acc_info.key)
+    }
+
+    //Grow a new counter account to hold a history of `capacity` changes - the funder
+    //tops up the rent exempt balance for the extra space
+    fn allocate_history<'a>(
+        counter_acc_info: &AccountInfo<'a>,
+        funder_acc_info: &AccountInfo<'a>,
+        system_program_acc_info: &AccountInfo<'a>,
+        capacity: u16,
+    ) -> ProgramResult {
+        if *system_program_acc_info.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let space = Counter::LEN + History::space(capacity);
+        if counter_acc_info.data_len() < space {
+            counter_acc_info.realloc(space, true)?;
+        }
+        let rent_exempt_lamports = Rent::get()?.minimum_balance(counter_acc_info.data_len());
+        let shortfall = rent_exempt_lamports.saturating_sub(counter_acc_info.lamports());
+        if shortfall > 0 {
+            invoke(
+                &system_instruction::transfer(funder_acc_info.key, counter_acc_info.key, shortfall),
+                &[
+                    funder_acc_info.clone(),
+                    counter_acc_info.clone(),
+                    system_program_acc_info.clone(),
+                ],
+            )?;
+        }
+        let history = History {
+            capacity,
+            ..History::default()
+        };
+        history.serialize(&mut &mut counter_acc_info.data.borrow_mut()[Counter::LEN..])?;
+        Ok(())
+    }
+
+    fn process_withdraw_fees(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        lamports: u64,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let treasury_acc_info = next_account_info(accounts_iter)?;
+        let destination_acc_info = next_account_info(accounts_iter)?;
+        let system_program_acc_info = next_account_info(accounts_iter)?;
+        let counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        if counter.treasury == Pubkey::default() || *treasury_acc_info.key != counter.treasury {
+            msg!("Not the treasury of the counter");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if *system_program_acc_info.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let available = treasury_acc_info
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+        let lamports = match lamports {
+            0 => available,
+            lamports => lamports,
+        };
+        if lamports > available {
+            msg!("Treasury holds {} lamports of fees", available);
+            return Err(ProgramError::InsufficientFunds);
+        }
+        let (_, bump) = Counter::find_treasury_address(counter_acc_info.key, program_id);
+        invoke_signed(
+            &system_instruction::transfer(
+                treasury_acc_info.key,
+                destination_acc_info.key,
+                lamports,
+            ),
+            &[
+                treasury_acc_info.clone(),
+                destination_acc_info.clone(),
+                system_program_acc_info.clone(),
+            ],
+            &[&[TREASURY_SEED, counter_acc_info.key.as_ref(), &[bump]]],
+        )?;
+        msg!("{} lamports of fees withdrawn", lamports);
+        Self::emit(&CounterEvent::FeesWithdrawn {
+            counter: *counter_acc_info.key,
+            destination: *destination_acc_info.key,
+            lamports,
+        })
+    }
+
+    fn process_migrate(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let payer_acc_info = next_account_info(accounts_iter)?;
+        let system_program_acc_info = next_account_info(accounts_iter)?;
+        if counter_acc_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if !payer_acc_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if *system_program_acc_info.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let versioned = VersionedCounter::unpack(&counter_acc_info.data.borrow())?;
+        let from_version = versioned.version();
+        if from_version == Counter::VERSION {
+            msg!("Counter layout is already current");
+            return Ok(());
+        }
+        //Whoever derived the counter address becomes the authority of counters that
+        //predate authorities
+        if !versioned.has_authority() {
+            Self::check_owner(program_id, counter_acc_info, payer_acc_info)?;
+        }
+        let counter = versioned.upgrade(payer_acc_info.key);
+        //Grow the account and top up the rent exempt balance for the extra space
+        if counter_acc_info.data_len() < Counter::LEN {
+            counter_acc_info.realloc(Counter::LEN, true)?;
+        }
+        let rent_exempt_lamports = Rent::get()?.minimum_balance(counter_acc_info.data_len());
+        let shortfall = rent_exempt_lamports.saturating_sub(counter_acc_info.lamports());
+        if shortfall > 0 {
+            invoke(
+                &system_instruction::transfer(payer_acc_info.key, counter_acc_info.key, shortfall),
+                &[
+                    payer_acc_info.clone(),
+                    counter_acc_info.clone(),
+                    system_program_acc_info.clone(),
+                ],
+            )?;
+        }
+        counter.serialize(&mut &mut counter_acc_info.data.borrow_mut()[..])?;
+        msg!(
+            "Counter migrated from layout {} to {}",
+            from_version,
+            Counter::VERSION
+        );
+        Self::emit(&CounterEvent::Migrated {
+            counter: *counter_acc_info.key,
+            from_version,
+            to_version: Counter::VERSION,
+        })
+    }
+
+    fn process_propose_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        new_authority: Pubkey,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let mut counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        counter.pending_authority = Some(new_authority);
+        msg!("Authority {} proposed", new_authority);
+        counter.serialize(&mut &mut counter_acc_info.data.borrow_mut()[..])?;
+        Self::emit(&CounterEvent::AuthorityProposed {
+            counter: *counter_acc_info.key,
+            authority: counter.authority,
+            proposed: new_authority,
+        })
+    }
+
+    fn process_accept_authority(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let new_authority_acc_info = next_account_info(accounts_iter)?;
+        let mut counter = Self::load_counter(program_id, counter_acc_info)?;
+        let pending_authority = counter.pending_authority.ok_or_else(|| {
+            msg!("No authority transfer pending");
+            ProgramError::InvalidArgument
+        })?;
+        Self::check_signed_by(
+            program_id,
+            &pending_authority,
+            new_authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        let previous = counter.authority;
+        counter.authority = *new_authority_acc_info.key;
+        counter.pending_authority = None;
+        msg!("Authority transferred to {}", counter.authority);
+        counter.serialize(&mut &mut counter_acc_info.data.borrow_mut()[..])?;
+        Self::emit(&CounterEvent::AuthorityTransferred {
+            counter: *counter_acc_info.key,
+            previous,
+            authority: counter.authority,
+        })
+    }
+
+    fn process_cancel_authority_transfer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let mut counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        let cancelled = counter.pending_authority.take().ok_or_else(|| {
+            msg!("No authority transfer pending");
+            ProgramError::InvalidArgument
+        })?;
+        msg!("Authority transfer to {} cancelled", cancelled);
+        counter.serialize(&mut &mut counter_acc_info.data.borrow_mut()[..])?;
+        Self::emit(&CounterEvent::AuthorityTransferCancelled {
+            counter: *counter_acc_info.key,
+            authority: counter.authority,
+            cancelled,
+        })
+    }
+
+    fn process_set_access_mode(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        access_mode: AccessMode,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let mut counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        counter.access_mode = access_mode;
+        msg!("Access mode set to {:?}", access_mode);
+        counter.serialize(&mut &mut counter_acc_info.data.borrow_mut()[..])?;
+        Self::emit(&CounterEvent::AccessModeChanged {
+            counter: *counter_acc_info.key,
+            access_mode,
+        })
+    }
+
+    fn process_add_delegate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        delegate: Pubkey,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let payer_acc_info = next_account_info(accounts_iter)?;
+        let delegate_acc_info = next_account_info(accounts_iter)?;
+        let system_program_acc_info = next_account_info(accounts_iter)?;
+        let counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        if *system_program_acc_info.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let (address, bump) = Delegate::find_address(counter_acc_info.key, &delegate, program_id);
+        if address != *delegate_acc_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if delegate_acc_info.owner == program_id {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_acc_info.key,
+                delegate_acc_info.key,
+                Rent::get()?.minimum_balance(Delegate::LEN),
+                Delegate::LEN as u64,
+                program_id,
+            ),
+            &[
+                payer_acc_info.clone(),
+                delegate_acc_info.clone(),
+                system_program_acc_info.clone(),
+            ],
+            &[&[
+                DELEGATE_SEED,
+                counter_acc_info.key.as_ref(),
+                delegate.as_ref(),
+                &[bump],
+            ]],
+        )?;
+        Delegate::new(*counter_acc_info.key, delegate)
+            .serialize(&mut &mut delegate_acc_info.data.borrow_mut()[..])?;
+        msg!("Delegate {} added", delegate);
+        Self::emit(&CounterEvent::DelegateAdded {
+            counter: *counter_acc_info.key,
+            delegate,
+        })
+    }
+
+    fn process_create_leaderboard(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        capacity: u8,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let payer_acc_info = next_account_info(accounts_iter)?;
+        let leaderboard_acc_info = next_account_info(accounts_iter)?;
+        let system_program_acc_info = next_account_info(accounts_iter)?;
+        let counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        if capacity == 0 || capacity as usize > MAX_LEADERBOARD_ENTRIES {
+            msg!(
+                "Leaderboard capacity must be 1 to {}",
+                MAX_LEADERBOARD_ENTRIES
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if *system_program_acc_info.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let (address, bump) = Leaderboard::find_address(counter_acc_info.key, program_id);
+        if address != *leaderboard_acc_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if leaderboard_acc_info.owner == program_id {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_acc_info.key,
+                leaderboard_acc_info.key,
+                Rent::get()?.minimum_balance(Leaderboard::LEN),
+                Leaderboard::LEN as u64,
+                program_id,
+            ),
+            &[
+                payer_acc_info.clone(),
+                leaderboard_acc_info.clone(),
+                system_program_acc_info.clone(),
+            ],
+            &[&[LEADERBOARD_SEED, counter_acc_info.key.as_ref(), &[bump]]],
+        )?;
+        Leaderboard::new(*counter_acc_info.key, capacity)
+            .serialize(&mut &mut leaderboard_acc_info.data.borrow_mut()[..])?;
+        msg!("Leaderboard of the top {} contributors created", capacity);
+        Self::emit(&CounterEvent::LeaderboardCreated {
+            counter: *counter_acc_info.key,
+            capacity,
+        })
+    }
+
+    fn process_create_time_series(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        bucket_width: u32,
+        bucket_count: u16,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let payer_acc_info = next_account_info(accounts_iter)?;
+        let time_series_acc_info = next_account_info(accounts_iter)?;
+        let system_program_acc_info = next_account_info(accounts_iter)?;
+        let counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        if bucket_width == 0 || bucket_count == 0 || bucket_count > MAX_TIME_BUCKETS {
+            msg!(
+                "Time series needs buckets at least a second wide, 1 to {} of them",
+                MAX_TIME_BUCKETS
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if *system_program_acc_info.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let (address, bump) = TimeSeries::find_address(counter_acc_info.key, program_id);
+        if address != *time_series_acc_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if time_series_acc_info.owner == program_id {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        let space = TimeSeries::space(bucket_count);
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_acc_info.key,
+                time_series_acc_info.key,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer_acc_info.clone(),
+                time_series_acc_info.clone(),
+                system_program_acc_info.clone(),
+            ],
+            &[&[TIME_SERIES_SEED, counter_acc_info.key.as_ref(), &[bump]]],
+        )?;
+        TimeSeries::new(
+            *counter_acc_info.key,
+            bucket_width,
+            bucket_count,
+            Clock::get()?.unix_timestamp,
+        )
+        .serialize(&mut &mut time_series_acc_info.data.borrow_mut()[..])?;
+        msg!(
+            "Counting increaments per {} seconds over {} buckets",
+            bucket_width,
+            bucket_count
+        );
+        Self::emit(&CounterEvent::TimeSeriesCreated {
+            counter: *counter_acc_info.key,
+            bucket_width,
+            bucket_count,
+        })
+    }
+
+    fn process_snapshot(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        label: String,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let payer_acc_info = next_account_info(accounts_iter)?;
+        let snapshot_acc_info = next_account_info(accounts_iter)?;
+        let system_program_acc_info = next_account_info(accounts_iter)?;
+        let counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        if label.is_empty() || label.len() > MAX_SNAPSHOT_LABEL_LEN {
+            msg!(
+                "Snapshot label must be 1 to {} bytes",
+                MAX_SNAPSHOT_LABEL_LEN
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if *system_program_acc_info.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let (address, bump) = Snapshot::find_address(counter_acc_info.key, &label, program_id);
+        if address != *snapshot_acc_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if snapshot_acc_info.owner == program_id {
+            msg!("Snapshot {} already taken", label);
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        let space = Snapshot::space(&label);
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_acc_info.key,
+                snapshot_acc_info.key,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer_acc_info.clone(),
+                snapshot_acc_info.clone(),
+                system_program_acc_info.clone(),
+            ],
+            &[&[
+                SNAPSHOT_SEED,
+                counter_acc_info.key.as_ref(),
+                label.as_bytes(),
+                &[bump],
+            ]],
+        )?;
+        let clock = Clock::get()?;
+        let snapshot = Snapshot::new(
+            *counter_acc_info.key,
+            counter.count,
+            clock.slot,
+            clock.unix_timestamp,
+            label,
+        );
+        snapshot.serialize(&mut &mut snapshot_acc_info.data.borrow_mut()[..])?;
+        msg!(
+            "Snapshot {} of {} at slot {}",
+            snapshot.label,
+            snapshot.value,
+            snapshot.slot
+        );
+        Self::emit(&CounterEvent::SnapshotTaken {
+            counter: *counter_acc_info.key,
+            label: snapshot.label,
+            value: snapshot.value,
+            slot: snapshot.slot,
+        })
+    }
+
+    fn process_redeem_voucher(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        nonce: u64,
+        expiry: i64,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let redeemer_acc_info = next_account_info(accounts_iter)?;
+        let record_acc_info = next_account_info(accounts_iter)?;
+        let instructions_acc_info = next_account_info(accounts_iter)?;
+        let system_program_acc_info = next_account_info(accounts_iter)?;
+        let treasury_acc_info = next_account_info(accounts_iter).ok();
+        let increase_accounts = IncreaseAccounts::new(
+            Some(redeemer_acc_info),
+            treasury_acc_info,
+            Some(system_program_acc_info),
+            accounts_iter,
+        );
+        let mut counter = Self::load_counter(program_id, counter_acc_info)?;
+        if !redeemer_acc_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if amount == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let voucher = Voucher {
+            counter: *counter_acc_info.key,
+            amount,
+            nonce,
+            expiry,
+        };
+        Self::check_voucher_approved(&counter, &voucher, instructions_acc_info)?;
+        let clock = Clock::get()?;
+        if clock.unix_timestamp > expiry {
+            msg!("Voucher expired at {}", expiry);
+            return Err(CounterError::VoucherExpired.into());
+        }
+        if *system_program_acc_info.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let (address, bump) =
+            RedeemedVoucher::find_address(counter_acc_info.key, nonce, program_id);
+        if address != *record_acc_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if record_acc_info.owner == program_id {
+            msg!("Voucher nonce {} already redeemed", nonce);
+            return Err(CounterError::VoucherRedeemed.into());
+        }
+        invoke_signed(
+            &system_instruction::create_account(
+                redeemer_acc_info.key,
+                record_acc_info.key,
+                Rent::get()?.minimum_balance(RedeemedVoucher::LEN),
+                RedeemedVoucher::LEN as u64,
+                program_id,
+            ),
+            &[
+                redeemer_acc_info.clone(),
+                record_acc_info.clone(),
+                system_program_acc_info.clone(),
+            ],
+            &[&[
+                VOUCHER_SEED,
+                counter_acc_info.key.as_ref(),
+                &nonce.to_le_bytes(),
+                &[bump],
+            ]],
+        )?;
+        RedeemedVoucher::new(
+            *counter_acc_info.key,
+            nonce,
+            *redeemer_acc_info.key,
+            clock.slot,
+        )
+        .serialize(&mut &mut record_acc_info.data.borrow_mut()[..])?;
+        let previous_value = Self::increase(
+            program_id,
+            counter_acc_info.key,
+            &mut counter,
+            Some(redeemer_acc_info.key),
+            amount,
+            &increase_accounts,
+        )?;
+        msg!("Voucher {} redeemed for {}", nonce, amount);
+        Self::record_history(
+            counter_acc_info,
+            Some(redeemer_acc_info.key),
+            previous_value,
+            counter.count,
+        )?;
+        counter.serialize(&mut &mut counter_acc_info.data.borrow_mut()[..])?;
+        Self::return_counter(&counter)?;
+        Self::emit(&CounterEvent::VoucherRedeemed {
+            counter: *counter_acc_info.key,
+            redeemer: *redeemer_acc_info.key,
+            nonce,
+            amount,
+            new_value: counter.count,
+        })
+    }
+
+    //The instruction ahead of the current one must have the Ed25519 program verify
+    //the counter authority's signature of the voucher
+    fn check_voucher_approved(
+        counter: &Counter,
+        voucher: &Voucher,
+        instructions_acc_info: &AccountInfo,
+    ) -> ProgramResult {
+        let current = load_current_index_checked(instructions_acc_info)?;
+        let ed25519_instruction = match current.checked_sub(1) {
+            Some(index) => load_instruction_at_checked(index as usize, instructions_acc_info)?,
+            None => return Err(CounterError::VoucherNotApproved.into()),
+        };
+        let approved = ed25519_instruction.program_id == ed25519_program::id()
+            && Voucher::verified_by(&ed25519_instruction.data).is_some_and(|(signer, message)| {
+                signer == counter.authority && message == voucher.message().as_slice()
+            });
+        if !approved {
+            msg!(
+                "Voucher must be signed by the counter authority {}",
+                counter.authority
+            );
+            return Err(CounterError::VoucherNotApproved.into());
+        }
+        Ok(())
+    }
+
+    fn process_remove_delegate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        delegate: Pubkey,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let delegate_acc_info = next_account_info(accounts_iter)?;
+        let destination_acc_info = next_account_info(accounts_iter)?;
+        //Draining the record into itself would leave the delegate in place
+        if destination_acc_info.key == delegate_acc_info.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        if !Self::is_delegate(
+            program_id,
+            counter_acc_info.key,
+            &delegate,
+            delegate_acc_info,
+        ) {
+            msg!("{} is not a delegate of the counter", delegate);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        //The account is purged once drained
+        let lamports = delegate_acc_info.lamports();
+        **destination_acc_info.lamports.borrow_mut() = destination_acc_info
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(ProgramError::InvalidArgument)?;
+        **delegate_acc_info.lamports.borrow_mut() = 0;
+        delegate_acc_info.data.borrow_mut().fill(0);
+        msg!("Delegate {} removed", delegate);
+        Self::emit(&CounterEvent::DelegateRemoved {
+            counter: *counter_acc_info.key,
+            delegate,
+        })
+    }
+
+    fn process_initialize_multisig(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        threshold: u8,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let multisig_acc_info = next_account_info(accounts_iter)?;
+        let signers: Vec<Pubkey> = accounts_iter.map(|signer| *signer.key).collect();
+        if multisig_acc_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if multisig_acc_info.data_len() < Multisig::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let header = AccountHeader::deserialize(&mut &multisig_acc_info.data.borrow()[..])?;
+        if header.account_type != AccountType::Uninitialized {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if signers.is_empty() || signers.len() > MAX_SIGNERS {
+            msg!("A multisig takes 1 to {} signers", MAX_SIGNERS);
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        if threshold == 0 || threshold as usize > signers.len() {
+            msg!("Threshold must be between 1 and {}", signers.len());
+            return Err(ProgramError::InvalidArgument);
+        }
+        if (1..signers.len()).any(|i| signers[..i].contains(&signers[i])) {
+            msg!("Multisig signers must be distinct");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Multisig::new(threshold, &signers)
+            .serialize(&mut &mut multisig_acc_info.data.borrow_mut()[..])?;
+        msg!("{} of {} multisig initialized", threshold, signers.len());
+        Self::emit(&CounterEvent::MultisigInitialized {
+            multisig: *multisig_acc_info.key,
+            threshold,
+            signers,
+        })
+    }
+
+    fn process_set_rate_limit(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        rate_limit: RateLimit,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let mut counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        if rate_limit.max_per_window > 0 && rate_limit.window == 0 {
+            msg!("A per window cap needs a window");
+            return Err(ProgramError::InvalidArgument);
+        }
+        counter.rate_limit = rate_limit;
+        //Windows are counted afresh under the new limit
+        counter.window_start = 0;
+        counter.window_count = 0;
+        msg!("Rate limit set to {:?}", rate_limit);
+        counter.serialize(&mut &mut counter_acc_info.data.borrow_mut()[..])?;
+        Self::emit(&CounterEvent::RateLimitChanged {
+            counter: *counter_acc_info.key,
+            rate_limit,
+        })
+    }
+
+    fn process_set_token_gate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        token_gate: TokenGate,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let mut counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        if token_gate.is_enabled() && token_gate.min_balance == 0 {
+            msg!("A token gate needs a minimum balance");
+            return Err(ProgramError::InvalidArgument);
+        }
+        counter.token_gate = token_gate;
+        msg!("Token gate set to {:?}", token_gate);
+        counter.serialize(&mut &mut counter_acc_info.data.borrow_mut()[..])?;
+        Self::emit(&CounterEvent::TokenGateChanged {
+            counter: *counter_acc_info.key,
+            token_gate,
+        })
+    }
+
+    fn process_create_milestones(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        mint: Pubkey,
+        every: u64,
+        reward: u64,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let counter_acc_info = next_account_info(accounts_iter)?;
+        let authority_acc_info = next_account_info(accounts_iter)?;
+        let payer_acc_info = next_account_info(accounts_iter)?;
+        let milestones_acc_info = next_account_info(accounts_iter)?;
+        let system_program_acc_info = next_account_info(accounts_iter)?;
+        let mint_acc_info = next_account_info(accounts_iter)?;
+        let counter = Self::load_counter(program_id, counter_acc_info)?;
+        Self::check_authority(
+            program_id,
+            &counter,
+            authority_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        if every == 0 || reward == 0 {
+            msg!("Milestones need a distance and a reward");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if *system_program_acc_info.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        //Only a mint the counter's mint authority mints for can pay the rewards
+        if *mint_acc_info.key != mint || *mint_acc_info.owner != spl_token::id() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let mint_authority =
+            Milestones::find_mint_authority_address(counter_acc_info.key, program_id).0;
+        if Mint::unpack(&mint_acc_info.data.borrow())?.mint_authority
+            != COption::Some(mint_authority)
+        {
+            msg!(
+                "Mint authority of the reward mint must be {}",
+                mint_authority
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (address, bump) = Milestones::find_address(counter_acc_info.key, program_id);
+        if address != *milestones_acc_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if milestones_acc_info.owner == program_id {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_acc_info.key,
+                milestones_acc_info.key,
+                Rent::get()?.minimum_balance(Milestones::LEN),
+                Milestones::LEN as u64,
+                program_id,
+            ),
+            &[
+                payer_acc_info.clone(),
+                milestones_acc_info.clone(),
+                system_program_acc_info.clone(),
+            ],
+            &[&[MILESTONES_SEED, counter_acc_info.key.as_ref(), &[bump]]],
+        )?;
+        Milestones::new(*counter_acc_info.key, mint, every, reward)
+            .serialize(&mut &mut milestones_acc_info.data.borrow_mut()[..])?;
+        msg!("Reward of {} every {} increaments", reward, every);
+        Self::emit(&CounterEvent::MilestonesCreated {
+            counter: *counter_acc_info.key,
+            mint,
+            every,
+            reward,
+        })
+    }
+
+    fn process_initialize_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        admin: Pubkey,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let config_acc_info = next_account_info(accounts_iter)?;
+        let upgrade_authority_acc_info = next_account_info(accounts_iter)?;
+        let program_data_acc_info = next_account_info(accounts_iter)?;
+        let system_program_acc_info = next_account_info(accounts_iter)?;
+        Self::check_upgrade_authority(
+            program_id,
+            program_data_acc_info,
+            upgrade_authority_acc_info,
+        )?;
+        if *system_program_acc_info.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let (address, bump) = ProgramConfig::find_address(program_id);
+        if address != *config_acc_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if config_acc_info.owner == program_id {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        invoke_signed(
+            &system_instruction::create_account(
+                upgrade_authority_acc_info.key,
+                config_acc_info.key,
+                Rent::get()?.minimum_balance(ProgramConfig::LEN),
+                ProgramConfig::LEN as u64,
+                program_id,
+            ),
+            &[
+                upgrade_authority_acc_info.clone(),
+                config_acc_info.clone(),
+                system_program_acc_info.clone(),
+            ],
+            &[&[CONFIG_SEED, &[bump]]],
+        )?;
+        ProgramConfig::new(admin).serialize(&mut &mut config_acc_info.data.borrow_mut()[..])?;
+        msg!("Program config initialized - admin {}", admin);
+        Self::emit(&CounterEvent::ConfigInitialized { admin })
+    }
+
+    fn process_set_paused(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        paused: bool,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let config_acc_info = next_account_info(accounts_iter)?;
+        let admin_acc_info = next_account_info(accounts_iter)?;
+        let mut config = Self::load_config(program_id, config_acc_info)?;
+        Self::check_signed_by(
+            program_id,
+            &config.admin,
+            admin_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        config.paused = paused;
+        msg!("Program paused {}", paused);
+        config.serialize(&mut &mut config_acc_info.data.borrow_mut()[..])?;
+        let admin = config.admin;
+        Self::emit(&match paused {
+            true => CounterEvent::Paused { admin },
+            false => CounterEvent::Unpaused { admin },
+        })
+    }
+
+    fn process_propose_admin(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        new_admin: Pubkey,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let config_acc_info = next_account_info(accounts_iter)?;
+        let admin_acc_info = next_account_info(accounts_iter)?;
+        let mut config = Self::load_config(program_id, config_acc_info)?;
+        Self::check_signed_by(
+            program_id,
+            &config.admin,
+            admin_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        config.pending_admin = Some(new_admin);
+        msg!("Admin {} proposed", new_admin);
+        config.serialize(&mut &mut config_acc_info.data.borrow_mut()[..])?;
+        Self::emit(&CounterEvent::AdminProposed {
+            admin: config.admin,
+            proposed: new_admin,
+        })
+    }
+
+    fn process_accept_admin(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let config_acc_info = next_account_info(accounts_iter)?;
+        let new_admin_acc_info = next_account_info(accounts_iter)?;
+        let mut config = Self::load_config(program_id, config_acc_info)?;
+        let pending_admin = config.pending_admin.ok_or_else(|| {
+            msg!("No admin rotation pending");
+            ProgramError::InvalidArgument
+        })?;
+        Self::check_signed_by(
+            program_id,
+            &pending_admin,
+            new_admin_acc_info,
+            accounts_iter.as_slice(),
+        )?;
+        let previous = config.admin;
+        config.admin = pending_admin;
+        config.pending_admin = None;
+        msg!("Admin rotated to {}", config.admin);
+        config.serialize(&mut &mut config_acc_info.data.borrow_mut()[..])?;
+        Self::emit(&CounterEvent::AdminTransferred {
+            previous,
+            admin: config.admin,
+        })
+    }
+
+    //Enforce the rate limit of the counter and record the increament against it
+    fn record_increament(counter: &mut Counter, clock: &Clock) -> ProgramResult {
+        let rate_limit = counter.rate_limit;
+        let (now, last_update) = match rate_limit.unit {
+            RateUnit::Slots => (clock.slot, counter.last_update_slot),
+            RateUnit::Seconds => (
+                clock.unix_timestamp.max(0) as u64,
+                counter.last_update_timestamp.max(0) as u64,
+            ),
+        };
+        let increamented_before =
+            counter.last_update_slot != 0 || counter.last_update_timestamp != 0;
+        if rate_limit.min_interval > 0
+            && increamented_before
+            && now.saturating_sub(last_update) < rate_limit.min_interval
+        {
+            msg!(
+                "Next increament allowed at {:?} {}",
+                rate_limit.unit,
+                last_update.saturating_add(rate_limit.min_interval)
+            );
+            return Err(CounterError::IncreamentTooSoon.into());
+        }
+        if rate_limit.window > 0 {
+            let window_start = now - now % rate_limit.window;
+            if window_start != counter.window_start {
+                counter.window_start = window_start;
+                counter.window_count = 0;
+            }
+            if rate_limit.max_per_window > 0 && counter.window_count >= rate_limit.max_per_window {
+                msg!(
+                    "{} increaments in the window starting at {:?} {}",
+                    counter.window_count,
+                    rate_limit.unit,
+                    window_start
+                );
+                return Err(CounterError::WindowLimitReached.into());
+            }
+            counter.window_count = counter.window_count.saturating_add(1);
+        }
+        counter.last_update_slot = clock.slot;
+        counter.last_update_timestamp = clock.unix_timestamp;
+        Ok(())
+    }
+
+    //The counter access mode must allow the actor to increament it. Delegates prove
+    //themselves with their delegate record
+    fn check_access(
+        program_id: &Pubkey,
+        counter: &Counter,
+        counter_pubkey: &Pubkey,
+        actor: Option<&Pubkey>,
+        delegate_acc_info: Option<&AccountInfo>,
+    ) -> ProgramResult {
+        let is_authority = actor == Some(&counter.authority);
+        let allowed = match counter.access_mode {
+            AccessMode::Open => true,
+            AccessMode::AuthorityOnly => is_authority,
+            AccessMode::Allowlist => {
+                is_authority
+                    || match (actor, delegate_acc_info) {
+                        (Some(actor), Some(delegate_acc_info)) => {
+                            Self::is_delegate(program_id, counter_pubkey, actor, delegate_acc_info)
+                        }
+                        _ => false,
+                    }
+            }
+        };
+        if !allowed {
+            msg!("Counter access mode is {:?}", counter.access_mode);
+            return Err(CounterError::IncreamentNotAllowed.into());
+        }
+        Ok(())
+    }
+
+    //Actors of token gated counters - increamenters and voucher redeemers alike - must
+    //hold at least the minimum balance of the gate mint, in a token account of their own
+    fn check_token_holding(
+        counter: &Counter,
+        actor: Option<&Pubkey>,
+        token_acc_info: Option<&AccountInfo>,
+    ) -> ProgramResult {
+        let token_gate = counter.token_gate;
+        if !token_gate.is_enabled() {
+            return Ok(());
+        }
+        let actor = actor.ok_or_else(|| {
+            msg!("Token gated counters are increamented by signing holders only");
+            ProgramError::MissingRequiredSignature
+        })?;
+        let token_acc_info = token_acc_info.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if *token_acc_info.owner != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_account = TokenAccount::unpack(&token_acc_info.data.borrow())?;
+        if token_account.owner != *actor
+            || token_account.mint != token_gate.mint
+            || token_account.amount < token_gate.min_balance
+        {
+            msg!(
+                "At least {} of mint {} required",
+                token_gate.min_balance,
+                token_gate.mint
+            );
+            return Err(CounterError::TokenHoldingRequired.into());
+        }
+        Ok(())
+    }
+
+    //Only this program writes accounts it owns - so a record naming the counter and
+    //the delegate can only have been written by `AddDelegate`
+    fn is_delegate(
+        program_id: &Pubkey,
+        counter_pubkey: &Pubkey,
+        delegate: &Pubkey,
+        delegate_acc_info: &AccountInfo,
+    ) -> bool {
+        delegate_acc_info.owner == program_id
+            && Delegate::unpack(&delegate_acc_info.data.borrow())
+                .map(|record| record.counter == *counter_pubkey && record.delegate == *delegate)
+                .unwrap_or(false)
+    }
+
+    //Counter account must be owned by this program and be in the current layout
+    fn load_counter(
+        program_id: &Pubkey,
+        counter_acc_info: &AccountInfo,
+    ) -> Result<Counter, ProgramError> {
+        if counter_acc_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Counter::unpack(&counter_acc_info.data.borrow()).map_err(|err| {
+            msg!("Invalid counter account {}", err);
+            ProgramError::InvalidAccountData
+        })
+    }
+
+    //The counter authority must have signed
+    fn check_authority(
+        program_id: &Pubkey,
+        counter: &Counter,
+        authority_acc_info: &AccountInfo,
+        signer_acc_infos: &[AccountInfo],
+    ) -> ProgramResult {
+        Self::check_signed_by(
+            program_id,
+            &counter.authority,
+            authority_acc_info,
+            signer_acc_infos,
+        )
+    }
+
+    //`expected` must have signed - either itself, or for a multisig account of this
+    //program, through at least the threshold of its signers among `signer_acc_infos`
+    fn check_signed_by(
+        program_id: &Pubkey,
+        expected: &Pubkey,
+        acc_info: &AccountInfo,
+        signer_acc_infos: &[AccountInfo],
+    ) -> ProgramResult {
+        if acc_info.key != expected {
+            msg!("{} must sign", expected);
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if acc_info.owner == program_id {
+            if let Ok(multisig) = Multisig::unpack(&acc_info.data.borrow()) {
+                //Each multisig signer counts once - however often it is passed in
+                let mut signed = [false; MAX_SIGNERS];
+                for signer_acc_info in signer_acc_infos.iter().filter(|info| info.is_signer) {
+                    if let Some(position) = multisig
+                        .signers()
+                        .iter()
+                        .position(|signer| signer == signer_acc_info.key)
+                    {
+                        signed[position] = true;
+                    }
+                }
+                let signatures = signed.iter().filter(|signed| **signed).count();
+                if signatures < multisig.threshold as usize {
+                    msg!(
+                        "{} of {} multisig signatures present",
+                        signatures,
+                        multisig.threshold
+                    );
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                return Ok(());
             }
         }
+        if !acc_info.is_signer {
+            msg!("{} must sign", expected);
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(())
+    }
 
+    //The counter account must be owned by this program and its address must have
+    //been derived from the signing owner key
+    fn check_owner(
+        program_id: &Pubkey,
+        counter_acc_info: &AccountInfo,
+        owner_acc_info: &AccountInfo,
+    ) -> ProgramResult {
+        if counter_acc_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if !owner_acc_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let expected =
+            Pubkey::create_with_seed(owner_acc_info.key, COUNTER_ACCOUNT_SEED, program_id)?;
+        if expected != *counter_acc_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
+    }
+
+    fn load_config(
+        program_id: &Pubkey,
+        config_acc_info: &AccountInfo,
+    ) -> Result<ProgramConfig, ProgramError> {
+        if *config_acc_info.key != ProgramConfig::find_address(program_id).0 {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if config_acc_info.owner != program_id {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        ProgramConfig::unpack(&config_acc_info.data.borrow()).map_err(|err| {
+            msg!("Invalid program config account {}", err);
+            ProgramError::InvalidAccountData
+        })
+    }
+
+    //Counters can only change while the program is not paused. Before the program
+    //config is created - at its address nothing but lamports can be - it is not
+    fn check_not_paused(program_id: &Pubkey, config_acc_info: &AccountInfo) -> ProgramResult {
+        if *config_acc_info.key != ProgramConfig::find_address(program_id).0 {
+            msg!("Program config account expected last");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if config_acc_info.owner != program_id {
+            return Ok(());
+        }
+        if Self::load_config(program_id, config_acc_info)?.paused {
+            msg!("Program is paused");
+            return Err(CounterError::Paused.into());
+        }
+        Ok(())
+    }
+
+    //The signer must be the upgrade authority recorded in the program data account
+    //of this(upgradeable) program. Program data starts with the loader state tag(3),
+    //the deployment slot and the optional upgrade authority
+    fn check_upgrade_authority(
+        program_id: &Pubkey,
+        program_data_acc_info: &AccountInfo,
+        authority_acc_info: &AccountInfo,
+    ) -> ProgramResult {
+        let (program_data, _) =
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+        if *program_data_acc_info.key != program_data
+            || *program_data_acc_info.owner != bpf_loader_upgradeable::id()
+        {
+            msg!("Program data account of the program expected");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = program_data_acc_info.data.borrow();
+        let upgrade_authority = match data
+            .get(..UpgradeableLoaderState::size_of_programdata_metadata())
+        {
+            Some([3, 0, 0, 0, _, _, _, _, _, _, _, _, 1, upgrade_authority @ ..]) => {
+                Pubkey::try_from(upgrade_authority).map_err(|_| ProgramError::InvalidAccountData)?
+            }
+            _ => {
+                msg!("Program is not upgradeable");
+                return Err(ProgramError::InvalidAccountData);
+            }
+        };
+        if *authority_acc_info.key != upgrade_authority || !authority_acc_info.is_signer {
+            msg!("Upgrade authority {} must sign", upgrade_authority);
+            return Err(ProgramError::MissingRequiredSignature);
+        }
         Ok(())
     }
 }
@@ -0,0 +1,189 @@
+//Who may increament the counter - by its access mode
+
+mod support;
+
+use common::{AccessMode, Counter, CounterError, CounterInstruction, Delegate};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use support::{rejected_with, setup, TestAccount, TestCounter};
+
+fn restricted(access_mode: AccessMode) -> TestCounter {
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    assert_eq!(
+        counter.process(CounterInstruction::SetAccessMode { access_mode }, true),
+        Ok(())
+    );
+    counter
+}
+
+//Increament by `actor` passing `delegate_record` as its delegate record
+fn increament_as(
+    counter: &mut TestCounter,
+    actor: Pubkey,
+    delegate_record: &TestAccount,
+) -> Result<(), ProgramError> {
+    counter.process_with(
+        CounterInstruction::Increament,
+        &mut [TestAccount::funded(actor, 0, true), delegate_record.clone()],
+    )
+}
+
+//Add `delegate` through the record account at `record` - owned by the program once
+//created, which the system program stub leaves out
+fn add_delegate(
+    counter: &mut TestCounter,
+    delegate: Pubkey,
+    record: Pubkey,
+) -> (Result<(), ProgramError>, TestAccount) {
+    let mut accounts = [
+        TestAccount::funded(counter.authority, 0, true),
+        TestAccount::funded(Pubkey::new_unique(), 10_000_000, true),
+        TestAccount::funded(record, 0, false),
+        TestAccount::new(system_program::id()),
+    ];
+    accounts[2].data = vec![0; Delegate::LEN];
+    let result = counter.process_with(CounterInstruction::AddDelegate { delegate }, &mut accounts);
+    let mut record = accounts[2].clone();
+    if result.is_ok() {
+        record.owner = counter.program_id;
+    }
+    (result, record)
+}
+
+#[test]
+fn open_counters_take_anyone() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    assert_eq!(counter.counter().access_mode, AccessMode::Open);
+    //Not even a signature needed
+    assert_eq!(counter.increament(), Ok(()));
+    let stranger = TestAccount::new(Pubkey::new_unique());
+    assert_eq!(increament_as(&mut counter, stranger.key, &stranger), Ok(()));
+    assert_eq!(counter.counter().count, 2);
+}
+
+#[test]
+fn authority_only_counters_take_the_authority() {
+    let _turn = setup();
+    let mut counter = restricted(AccessMode::AuthorityOnly);
+    assert_eq!(
+        counter.increament(),
+        rejected_with(CounterError::IncreamentNotAllowed)
+    );
+    let stranger = TestAccount::new(Pubkey::new_unique());
+    assert_eq!(
+        increament_as(&mut counter, stranger.key, &stranger),
+        rejected_with(CounterError::IncreamentNotAllowed)
+    );
+    assert_eq!(
+        counter.process(CounterInstruction::Increament, true),
+        Ok(())
+    );
+    assert_eq!(counter.counter().count, 1);
+}
+
+#[test]
+fn only_the_authority_sets_the_access_mode() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let access_mode = AccessMode::AuthorityOnly;
+    assert_eq!(
+        counter.process(CounterInstruction::SetAccessMode { access_mode }, false),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(counter.counter().access_mode, AccessMode::Open);
+}
+
+#[test]
+fn allowlisted_counters_take_delegates_until_removed() {
+    let _turn = setup();
+    let mut counter = restricted(AccessMode::Allowlist);
+    assert_eq!(
+        counter.process(CounterInstruction::Increament, true),
+        Ok(())
+    );
+    let delegate = Pubkey::new_unique();
+    let address = Delegate::find_address(&counter.key, &delegate, &counter.program_id).0;
+    //Not a delegate yet
+    let none = TestAccount::new(address);
+    assert_eq!(
+        increament_as(&mut counter, delegate, &none),
+        rejected_with(CounterError::IncreamentNotAllowed)
+    );
+    let (result, mut record) = add_delegate(&mut counter, delegate, address);
+    assert_eq!(result, Ok(()));
+    assert_eq!(increament_as(&mut counter, delegate, &record), Ok(()));
+    //The record of a delegate is no use to anyone else
+    assert_eq!(
+        increament_as(&mut counter, Pubkey::new_unique(), &record),
+        rejected_with(CounterError::IncreamentNotAllowed)
+    );
+    assert_eq!(counter.counter().count, 2);
+    record.is_writable = true;
+    let mut accounts = [
+        TestAccount::funded(counter.authority, 0, true),
+        record,
+        TestAccount::funded(Pubkey::new_unique(), 0, false),
+    ];
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::RemoveDelegate { delegate },
+            &mut accounts
+        ),
+        Ok(())
+    );
+    let revoked = accounts[1].clone();
+    assert_eq!(revoked.lamports, 0);
+    assert_eq!(
+        increament_as(&mut counter, delegate, &revoked),
+        rejected_with(CounterError::IncreamentNotAllowed)
+    );
+    assert_eq!(counter.counter().count, 2);
+}
+
+#[test]
+fn delegate_records_live_at_their_address() {
+    let _turn = setup();
+    let mut counter = restricted(AccessMode::Allowlist);
+    let delegate = Pubkey::new_unique();
+    //The record address of another delegate
+    let address =
+        Delegate::find_address(&counter.key, &Pubkey::new_unique(), &counter.program_id).0;
+    let (result, _) = add_delegate(&mut counter, delegate, address);
+    assert_eq!(result, Err(ProgramError::InvalidSeeds));
+    //Nor will a program owned account written some other way pass for a record
+    let mut forged = TestAccount::new(address);
+    forged.owner = counter.program_id;
+    forged.data = vec![0; Delegate::LEN];
+    assert_eq!(
+        increament_as(&mut counter, delegate, &forged),
+        rejected_with(CounterError::IncreamentNotAllowed)
+    );
+    assert_eq!(counter.counter().count, 0);
+}
+
+#[test]
+fn delegate_records_do_not_close_into_themselves() {
+    let _turn = setup();
+    let mut counter = restricted(AccessMode::Allowlist);
+    let delegate = Pubkey::new_unique();
+    let address = Delegate::find_address(&counter.key, &delegate, &counter.program_id).0;
+    let (result, mut record) = add_delegate(&mut counter, delegate, address);
+    assert_eq!(result, Ok(()));
+    record.is_writable = true;
+    let mut accounts = [
+        TestAccount::funded(counter.authority, 0, true),
+        record.clone(),
+        record,
+    ];
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::RemoveDelegate { delegate },
+            &mut accounts
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+    //Still a delegate
+    assert_eq!(increament_as(&mut counter, delegate, &accounts[1]), Ok(()));
+}
@@ -0,0 +1,121 @@
+//Two step counter authority transfer
+
+mod support;
+
+use common::{Counter, CounterInstruction};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use support::{setup, TestAccount, TestCounter};
+
+fn signing(key: Pubkey) -> TestAccount {
+    let mut account = TestAccount::new(key);
+    account.is_signer = true;
+    account
+}
+
+fn propose(new_authority: Pubkey) -> CounterInstruction {
+    CounterInstruction::ProposeAuthority { new_authority }
+}
+
+#[test]
+fn only_the_authority_proposes() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let proposed = Pubkey::new_unique();
+    assert_eq!(
+        counter.process(propose(proposed), false),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    //Signed - by someone else
+    assert_eq!(
+        counter.process_with(propose(proposed), &mut [signing(Pubkey::new_unique())]),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(counter.counter().pending_authority, None);
+    assert_eq!(counter.process(propose(proposed), true), Ok(()));
+    assert_eq!(counter.counter().pending_authority, Some(proposed));
+}
+
+#[test]
+fn only_the_proposed_authority_accepts() {
+    let _turn = setup();
+    let authority = Pubkey::new_unique();
+    let mut counter = TestCounter::new(Counter::new(authority));
+    let proposed = Pubkey::new_unique();
+    assert_eq!(counter.process(propose(proposed), true), Ok(()));
+    //The current authority can not accept on its behalf, nor anyone else
+    for key in [authority, Pubkey::new_unique()] {
+        assert_eq!(
+            counter.process_with(CounterInstruction::AcceptAuthority, &mut [signing(key)]),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+    //The proposed authority must sign
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::AcceptAuthority,
+            &mut [TestAccount::new(proposed)]
+        ),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(counter.counter().authority, authority);
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::AcceptAuthority,
+            &mut [signing(proposed)]
+        ),
+        Ok(())
+    );
+    let state = counter.counter();
+    assert_eq!(state.authority, proposed);
+    assert_eq!(state.pending_authority, None);
+    //The former authority is locked out
+    assert_eq!(
+        counter.process(CounterInstruction::Reset, true),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+}
+
+#[test]
+fn nothing_to_accept_without_a_proposal() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::AcceptAuthority,
+            &mut [signing(Pubkey::new_unique())]
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn cancel_clears_the_proposal() {
+    let _turn = setup();
+    let authority = Pubkey::new_unique();
+    let mut counter = TestCounter::new(Counter::new(authority));
+    let proposed = Pubkey::new_unique();
+    assert_eq!(counter.process(propose(proposed), true), Ok(()));
+    assert_eq!(
+        counter.process(CounterInstruction::CancelAuthorityTransfer, false),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(
+        counter.process(CounterInstruction::CancelAuthorityTransfer, true),
+        Ok(())
+    );
+    assert_eq!(counter.counter().pending_authority, None);
+    //Too late to accept - and nothing left to cancel
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::AcceptAuthority,
+            &mut [signing(proposed)]
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        counter.process(CounterInstruction::CancelAuthorityTransfer, true),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(counter.counter().authority, authority);
+}
@@ -0,0 +1,219 @@
+//Test harness running the program in a bank(solana-program-test) rather than calling
+//the processor directly - the runtime serializes the accounts, keeps the clock and
+//carries out the invocations of other programs. The SPL token and associated token
+//account programs are loaded natively along with the counter program. The bank
+//installs syscall stubs of its own, process wide - bank tests go in test files apart
+//from those using `support`
+#![allow(dead_code)]
+
+use borsh::BorshSerialize;
+use common::{Counter, CounterError, CounterInstruction, ProgramConfig};
+use program::processor::Processor;
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::program_option::COption;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Mint;
+
+//The counter program along with the SPL token programs it invokes
+pub fn program_test(program_id: Pubkey) -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "program",
+        program_id,
+        processor!(Processor::process_instruction),
+    );
+    program_test.add_program(
+        "spl_token",
+        spl_token::id(),
+        processor!(spl_token::processor::Processor::process),
+    );
+    program_test.add_program(
+        "spl_associated_token_account",
+        spl_associated_token_account::id(),
+        processor!(spl_associated_token_account::processor::process_instruction),
+    );
+    program_test
+}
+
+//Add the counter account holding `counter` - rent exempt and owned by the program - at
+//a new address
+pub fn add_counter(
+    program_test: &mut ProgramTest,
+    program_id: &Pubkey,
+    counter: &Counter,
+) -> Pubkey {
+    let key = Pubkey::new_unique();
+    let mut data = counter.try_to_vec().unwrap();
+    data.resize(Counter::LEN, 0);
+    program_test.add_account(
+        key,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: *program_id,
+            ..Account::default()
+        },
+    );
+    key
+}
+
+//Add a mint(no decimals) whose tokens `mint_authority` mints, at a new address
+pub fn add_mint(program_test: &mut ProgramTest, mint_authority: &Pubkey) -> Pubkey {
+    let key = Pubkey::new_unique();
+    let mut data = vec![0; Mint::LEN];
+    Mint {
+        mint_authority: COption::Some(*mint_authority),
+        is_initialized: true,
+        ..Mint::default()
+    }
+    .pack_into_slice(&mut data);
+    program_test.add_account(
+        key,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: spl_token::id(),
+            ..Account::default()
+        },
+    );
+    key
+}
+
+pub struct TestBank {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+}
+
+impl TestBank {
+    pub async fn start(program_test: ProgramTest, program_id: Pubkey) -> Self {
+        Self {
+            context: program_test.start_with_context().await,
+            program_id,
+        }
+    }
+
+    //Counter instruction against `counter` followed by `accounts` - the program config
+    //account is appended
+    pub fn instruction(
+        &self,
+        instruction: CounterInstruction,
+        counter: &Pubkey,
+        accounts: Vec<AccountMeta>,
+    ) -> Instruction {
+        let mut metas = vec![AccountMeta::new(*counter, false)];
+        metas.extend(accounts);
+        metas.push(AccountMeta::new_readonly(
+            ProgramConfig::find_address(&self.program_id).0,
+            false,
+        ));
+        Instruction::new_with_bytes(self.program_id, &instruction.try_to_vec().unwrap(), metas)
+    }
+
+    //Run the instructions in a transaction of their own - paid for by the context payer
+    //and signed by `signers` as well. Every transaction waits for a new blockhash, so
+    //that repeating one is not taken for a duplicate
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransactionError> {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let payer = self.context.payer.insecure_clone();
+        let mut all_signers = vec![&payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|err| err.unwrap())
+    }
+
+    pub async fn account(&mut self, key: &Pubkey) -> Option<Account> {
+        self.context.banks_client.get_account(*key).await.unwrap()
+    }
+
+    pub async fn counter(&mut self, key: &Pubkey) -> Counter {
+        Counter::unpack(&self.account(key).await.unwrap().data).unwrap()
+    }
+
+    //Create the associated token account of `owner` for `mint` through the associated
+    //token account program
+    pub async fn create_token_account(&mut self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let create = spl_associated_token_account::instruction::create_associated_token_account(
+            &self.context.payer.pubkey(),
+            owner,
+            mint,
+            &spl_token::id(),
+        );
+        self.process(&[create], &[]).await.unwrap();
+        get_associated_token_address(owner, mint)
+    }
+
+    pub async fn mint_to(
+        &mut self,
+        mint: &Pubkey,
+        mint_authority: &Keypair,
+        token_account: &Pubkey,
+        amount: u64,
+    ) {
+        let mint_to = spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint,
+            token_account,
+            &mint_authority.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap();
+        self.process(&[mint_to], &[mint_authority]).await.unwrap();
+    }
+
+    //Token balance of the token account
+    pub async fn balance(&mut self, token_account: &Pubkey) -> u64 {
+        let account = self.account(token_account).await.unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    pub async fn clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar().await.unwrap()
+    }
+
+    //Warp the bank to `slot` and the clock to `unix_timestamp` - warping slots leaves
+    //the wall clock where it was
+    pub async fn warp(&mut self, slot: u64, unix_timestamp: i64) {
+        if slot > self.clock().await.slot {
+            self.context.warp_to_slot(slot).unwrap();
+        }
+        let mut clock = self.clock().await;
+        clock.unix_timestamp = unix_timestamp;
+        self.context.set_sysvar(&clock);
+    }
+}
+
+//What a transaction failing in its first instruction with `err` comes to
+pub fn failed_with(err: ProgramError) -> Result<(), TransactionError> {
+    Err(TransactionError::InstructionError(
+        0,
+        InstructionError::from(u64::from(err)),
+    ))
+}
+
+pub fn rejected_with(err: CounterError) -> Result<(), TransactionError> {
+    failed_with(err.into())
+}
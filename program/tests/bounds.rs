@@ -0,0 +1,109 @@
+//Bounded counters under each overflow policy
+
+mod support;
+
+use common::{Bounds, Counter, CounterError, CounterInstruction, OverflowPolicy};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use support::{rejected_with, setup, warp, TestCounter};
+
+fn bounded(min: u64, max: u64, overflow_policy: OverflowPolicy) -> TestCounter {
+    let bounds = Bounds {
+        min,
+        max,
+        overflow_policy,
+    };
+    TestCounter::new(Counter::with_bounds(Pubkey::new_unique(), bounds))
+}
+
+fn counts(counter: &mut TestCounter, increaments: usize) -> Vec<u64> {
+    (0..increaments)
+        .map(|_| {
+            counter.increament().unwrap();
+            counter.counter().count
+        })
+        .collect()
+}
+
+#[test]
+fn initialize_starts_at_the_lower_bound() {
+    let _turn = setup();
+    let bounds = Bounds {
+        min: 10,
+        max: 1_000,
+        overflow_policy: OverflowPolicy::Fail,
+    };
+    let mut counter = TestCounter::uninitialized(Pubkey::new_unique());
+    let inverted = Bounds {
+        min: 1_001,
+        ..bounds
+    };
+    assert_eq!(
+        counter.process(
+            CounterInstruction::Initialize {
+                bounds: inverted,
+                fee: 0,
+                history_capacity: 0,
+            },
+            true
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        counter.process(
+            CounterInstruction::Initialize {
+                bounds,
+                fee: 0,
+                history_capacity: 0,
+            },
+            true
+        ),
+        Ok(())
+    );
+    let state = counter.counter();
+    assert_eq!(state.count, 10);
+    assert_eq!(state.bounds, bounds);
+}
+
+#[test]
+fn fail_policy_rejects_going_past_the_upper_bound() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = bounded(0, 2, OverflowPolicy::Fail);
+    assert_eq!(counts(&mut counter, 2), [1, 2]);
+    assert_eq!(
+        counter.increament(),
+        rejected_with(CounterError::OutOfBounds)
+    );
+    assert_eq!(counter.counter().count, 2);
+}
+
+#[test]
+fn saturate_policy_stays_at_the_upper_bound() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = bounded(0, 2, OverflowPolicy::Saturate);
+    assert_eq!(counts(&mut counter, 4), [1, 2, 2, 2]);
+}
+
+#[test]
+fn wrap_policy_starts_over_from_the_lower_bound() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = bounded(10, 12, OverflowPolicy::Wrap);
+    assert_eq!(counts(&mut counter, 4), [11, 12, 10, 11]);
+    //The whole u64 range wraps too
+    let mut counter = bounded(0, u64::MAX, OverflowPolicy::Wrap);
+    counter.data[2..10].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert_eq!(counts(&mut counter, 1), [0]);
+}
+
+#[test]
+fn reset_goes_back_to_the_lower_bound() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = bounded(5, 100, OverflowPolicy::Fail);
+    assert_eq!(counts(&mut counter, 3), [6, 7, 8]);
+    assert_eq!(counter.process(CounterInstruction::Reset, true), Ok(()));
+    assert_eq!(counter.counter().count, 5);
+}
@@ -0,0 +1,81 @@
+//Resetting and closing counters
+
+mod support;
+
+use common::{Bounds, Counter, CounterInstruction, OverflowPolicy};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use support::{setup, TestAccount, TestCounter};
+
+//Close the counter into `destination`, signed by the authority
+fn close(counter: &mut TestCounter, destination: &mut TestAccount) -> Result<(), ProgramError> {
+    let mut accounts = [
+        TestAccount::funded(counter.authority, 0, true),
+        destination.clone(),
+    ];
+    let result = counter.process_with(CounterInstruction::Close, &mut accounts);
+    *destination = accounts[1].clone();
+    result
+}
+
+#[test]
+fn reset_goes_back_to_the_lower_bound() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter {
+        count: 7,
+        bounds: Bounds {
+            min: 2,
+            max: u64::MAX,
+            overflow_policy: OverflowPolicy::Fail,
+        },
+        ..Counter::new(Pubkey::new_unique())
+    });
+    assert_eq!(
+        counter.process(CounterInstruction::Reset, false),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(counter.counter().count, 7);
+    assert_eq!(counter.process(CounterInstruction::Reset, true), Ok(()));
+    assert_eq!(counter.counter().count, 2);
+}
+
+#[test]
+fn closing_drains_the_counter_into_the_destination() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let lamports = counter.lamports;
+    let mut destination = TestAccount::funded(Pubkey::new_unique(), 5, false);
+    assert_eq!(close(&mut counter, &mut destination), Ok(()));
+    assert_eq!(destination.lamports, lamports + 5);
+    assert_eq!(counter.lamports, 0);
+    assert!(counter.data.iter().all(|byte| *byte == 0));
+}
+
+#[test]
+fn counters_do_not_close_into_themselves() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let (lamports, data) = (counter.lamports, counter.data.clone());
+    let mut destination = TestAccount::funded(counter.key, 0, false);
+    assert_eq!(
+        close(&mut counter, &mut destination),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!((counter.lamports, &counter.data), (lamports, &data));
+}
+
+#[test]
+fn only_the_authority_closes() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let mut accounts = [
+        TestAccount::funded(Pubkey::new_unique(), 0, true),
+        TestAccount::funded(Pubkey::new_unique(), 0, false),
+    ];
+    assert_eq!(
+        counter.process_with(CounterInstruction::Close, &mut accounts),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(accounts[1].lamports, 0);
+    assert_eq!(counter.lamports, 1_000_000);
+}
@@ -0,0 +1,100 @@
+//Conditional updates - applied only while the counter holds the expected value
+
+mod support;
+
+use common::{Bounds, Counter, CounterError, CounterInstruction, OverflowPolicy};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use support::{rejected_with, setup, warp, TestCounter};
+
+fn at(count: u64) -> TestCounter {
+    let mut counter = Counter::new(Pubkey::new_unique());
+    counter.count = count;
+    TestCounter::new(counter)
+}
+
+#[test]
+fn compare_and_set_applies_to_the_expected_value() {
+    let _turn = setup();
+    let mut counter = at(7);
+    let instruction = CounterInstruction::CompareAndSet {
+        expected: 7,
+        new: 100,
+    };
+    assert_eq!(counter.process(instruction.clone(), true), Ok(()));
+    assert_eq!(counter.counter().count, 100);
+    //The same update again conflicts - the counter moved on
+    assert_eq!(
+        counter.process(instruction, true),
+        rejected_with(CounterError::ValueMismatch)
+    );
+    assert_eq!(counter.counter().count, 100);
+}
+
+#[test]
+fn compare_and_set_is_authority_gated() {
+    let _turn = setup();
+    let mut counter = at(7);
+    assert_eq!(
+        counter.process(
+            CounterInstruction::CompareAndSet {
+                expected: 7,
+                new: 8
+            },
+            false
+        ),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(counter.counter().count, 7);
+}
+
+#[test]
+fn compare_and_set_stays_within_bounds() {
+    let _turn = setup();
+    let bounds = Bounds {
+        min: 10,
+        max: 20,
+        overflow_policy: OverflowPolicy::Saturate,
+    };
+    let mut counter = TestCounter::new(Counter::with_bounds(Pubkey::new_unique(), bounds));
+    for new in [9, 21] {
+        assert_eq!(
+            counter.process(
+                CounterInstruction::CompareAndSet { expected: 10, new },
+                true
+            ),
+            rejected_with(CounterError::OutOfBounds)
+        );
+    }
+    assert_eq!(
+        counter.process(
+            CounterInstruction::CompareAndSet {
+                expected: 10,
+                new: 20
+            },
+            true
+        ),
+        Ok(())
+    );
+    assert_eq!(counter.counter().count, 20);
+}
+
+#[test]
+fn increment_if_equals_checks_the_value_first() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = at(3);
+    assert_eq!(
+        counter.process(CounterInstruction::IncrementIfEquals { expected: 2 }, false),
+        rejected_with(CounterError::ValueMismatch)
+    );
+    //A rejected increament is not recorded against the rate limit
+    assert_eq!(counter.counter().last_update_slot, 0);
+    assert_eq!(
+        counter.process(CounterInstruction::IncrementIfEquals { expected: 3 }, false),
+        Ok(())
+    );
+    let state = counter.counter();
+    assert_eq!(state.count, 4);
+    assert_eq!(state.last_update_slot, 1);
+}
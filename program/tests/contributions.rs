@@ -0,0 +1,92 @@
+//Per contributor tallies kept along with the counter
+
+mod support;
+
+use borsh::BorshSerialize;
+use common::{Contribution, Counter, CounterInstruction};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use support::{setup, warp, TestAccount, TestCounter};
+
+//Increament accounts passing the contribution record of `contributor` - who also pays
+fn contributing(counter: &TestCounter, contributor: &Pubkey) -> Vec<TestAccount> {
+    let record = Contribution::find_address(&counter.key, contributor, &counter.program_id).0;
+    let mut contribution = TestAccount::funded(record, 0, false);
+    contribution.data = vec![0; Contribution::LEN];
+    vec![
+        TestAccount::funded(*contributor, 0, true),
+        TestAccount::new(Pubkey::new_unique()),
+        TestAccount::funded(*contributor, 10_000_000, true),
+        TestAccount::new(Pubkey::new_unique()),
+        TestAccount::new(system_program::id()),
+        contribution,
+    ]
+}
+
+fn contribution(accounts: &[TestAccount]) -> Contribution {
+    Contribution::unpack(&accounts[5].data).unwrap()
+}
+
+#[test]
+fn first_contribution_creates_the_record() {
+    let _turn = setup();
+    warp(7, 1);
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let contributor = Pubkey::new_unique();
+    let mut accounts = contributing(&counter, &contributor);
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Ok(())
+    );
+    assert!(accounts[5].lamports > 0);
+    let record = contribution(&accounts);
+    assert_eq!(record.counter, counter.key);
+    assert_eq!(record.contributor, contributor);
+    assert_eq!(record.total, 1);
+    assert_eq!(record.last_slot, 7);
+    assert_eq!(counter.counter().count, 1);
+}
+
+#[test]
+fn later_contributions_add_up() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let contributor = Pubkey::new_unique();
+    let mut accounts = contributing(&counter, &contributor);
+    let mut record = Contribution::new(counter.key, contributor);
+    record.total = 41;
+    accounts[5].owner = counter.program_id;
+    accounts[5].data = record.try_to_vec().unwrap();
+    warp(9, 1);
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::IncrementIfEquals { expected: 0 },
+            &mut accounts
+        ),
+        Ok(())
+    );
+    let record = contribution(&accounts);
+    assert_eq!(record.total, 42);
+    assert_eq!(record.last_slot, 9);
+}
+
+#[test]
+fn contribution_record_must_be_the_actors() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let mut accounts = contributing(&counter, &Pubkey::new_unique());
+    accounts[0].key = Pubkey::new_unique();
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::InvalidSeeds)
+    );
+    //An actor that does not sign has no contribution to record
+    accounts[0].is_signer = false;
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(counter.counter().count, 0);
+}
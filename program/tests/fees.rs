@@ -0,0 +1,163 @@
+//Increament fees paid into the counter treasury
+
+mod support;
+
+use common::{Bounds, Counter, CounterInstruction};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use support::{setup, warp, TestAccount, TestCounter};
+
+const FEE: u64 = 5_000;
+
+//A counter charging `FEE`, initialized through the program - along with its treasury
+fn paid() -> (TestCounter, TestAccount) {
+    let mut counter = TestCounter::uninitialized(Pubkey::new_unique());
+    let treasury_key = Counter::find_treasury_address(&counter.key, &counter.program_id).0;
+    let mut accounts = vec![
+        TestAccount::funded(counter.authority, 10_000_000, true),
+        TestAccount::funded(treasury_key, 0, false),
+        TestAccount::new(system_program::id()),
+    ];
+    let initialize = CounterInstruction::Initialize {
+        bounds: Bounds::default(),
+        fee: FEE,
+        history_capacity: 0,
+    };
+    assert_eq!(counter.process_with(initialize, &mut accounts), Ok(()));
+    let treasury = accounts.remove(1);
+    assert_eq!(treasury.lamports, Rent::default().minimum_balance(0));
+    (counter, treasury)
+}
+
+//Increament accounts of a paid counter - actor, delegate record, payer, treasury and
+//system program
+fn increament_accounts(payer: &TestAccount, treasury: &TestAccount) -> Vec<TestAccount> {
+    vec![
+        TestAccount::new(payer.key),
+        TestAccount::new(Pubkey::new_unique()),
+        payer.clone(),
+        treasury.clone(),
+        TestAccount::new(system_program::id()),
+    ]
+}
+
+#[test]
+fn initialize_funds_the_treasury() {
+    let _turn = setup();
+    let (counter, treasury) = paid();
+    let state = counter.counter();
+    assert_eq!(state.fee, FEE);
+    assert_eq!(state.treasury, treasury.key);
+    //Free counters have no treasury
+    let mut free = TestCounter::uninitialized(Pubkey::new_unique());
+    let initialize = CounterInstruction::Initialize {
+        bounds: Bounds::default(),
+        fee: 0,
+        history_capacity: 0,
+    };
+    assert_eq!(free.process(initialize, true), Ok(()));
+    assert_eq!(free.counter().treasury, Pubkey::default());
+}
+
+#[test]
+fn increament_transfers_the_fee() {
+    let _turn = setup();
+    warp(1, 1);
+    let (mut counter, treasury) = paid();
+    let payer = TestAccount::funded(Pubkey::new_unique(), 12_000, true);
+    let mut accounts = increament_accounts(&payer, &treasury);
+    for _ in 0..2 {
+        assert_eq!(
+            counter.process_with(CounterInstruction::Increament, &mut accounts),
+            Ok(())
+        );
+    }
+    assert_eq!(accounts[2].lamports, 2_000);
+    assert_eq!(accounts[3].lamports, treasury.lamports + 2 * FEE);
+    assert_eq!(counter.counter().count, 2);
+    //Not enough left for another fee
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::InsufficientFunds)
+    );
+}
+
+#[test]
+fn increament_needs_the_fee_accounts() {
+    let _turn = setup();
+    warp(1, 1);
+    let (mut counter, treasury) = paid();
+    assert_eq!(
+        counter.increament(),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+    let payer = TestAccount::funded(Pubkey::new_unique(), 12_000, false);
+    let mut accounts = increament_accounts(&payer, &treasury);
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    accounts[2].is_signer = true;
+    accounts[3].key = Pubkey::new_unique();
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(counter.counter().count, 0);
+}
+
+#[test]
+fn only_the_authority_withdraws_fees() {
+    let _turn = setup();
+    let (mut counter, mut treasury) = paid();
+    let rent_exempt = treasury.lamports;
+    treasury.lamports += 3 * FEE;
+    let authority = counter.authority;
+    let withdraw_accounts = |authority_signs: bool| {
+        let mut authority = TestAccount::new(authority);
+        authority.is_signer = authority_signs;
+        vec![
+            authority,
+            treasury.clone(),
+            TestAccount::funded(Pubkey::new_unique(), 0, false),
+            TestAccount::new(system_program::id()),
+        ]
+    };
+    let mut accounts = withdraw_accounts(false);
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::WithdrawFees { lamports: 0 },
+            &mut accounts
+        ),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    let mut accounts = withdraw_accounts(true);
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::WithdrawFees {
+                lamports: 3 * FEE + 1
+            },
+            &mut accounts
+        ),
+        Err(ProgramError::InsufficientFunds)
+    );
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::WithdrawFees { lamports: FEE },
+            &mut accounts
+        ),
+        Ok(())
+    );
+    //All that is left - the treasury keeps its rent exempt minimum
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::WithdrawFees { lamports: 0 },
+            &mut accounts
+        ),
+        Ok(())
+    );
+    assert_eq!(accounts[1].lamports, rent_exempt);
+    assert_eq!(accounts[2].lamports, 3 * FEE);
+}
@@ -0,0 +1,140 @@
+//Latest changes kept in a ring buffer at the end of the counter account
+
+mod support;
+
+use common::{
+    Bounds, Counter, CounterInstruction, History, HistoryEntry, OverflowPolicy, MAX_HISTORY_ENTRIES,
+};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use support::{setup, warp, TestAccount, TestCounter};
+
+//A fresh counter account already spacious enough for a history of `capacity` - the
+//stubbed runtime can not grow accounts
+fn spacious(capacity: u16) -> TestCounter {
+    let mut counter = TestCounter::uninitialized(Pubkey::new_unique());
+    counter
+        .data
+        .resize(Counter::LEN + History::space(capacity), 0);
+    counter
+}
+
+fn initialize(
+    counter: &mut TestCounter,
+    bounds: Bounds,
+    history_capacity: u16,
+) -> Vec<TestAccount> {
+    let mut accounts = vec![
+        TestAccount::funded(counter.authority, 1_000_000_000, true),
+        TestAccount::new(Pubkey::new_unique()),
+        TestAccount::new(system_program::id()),
+    ];
+    let initialize = CounterInstruction::Initialize {
+        bounds,
+        fee: 0,
+        history_capacity,
+    };
+    assert_eq!(counter.process_with(initialize, &mut accounts), Ok(()));
+    accounts
+}
+
+fn history(counter: &TestCounter) -> Vec<HistoryEntry> {
+    History::unpack(&counter.data)
+        .unwrap()
+        .unwrap()
+        .entries(&counter.data)
+        .unwrap()
+}
+
+#[test]
+fn initialize_sets_up_the_history() {
+    let _turn = setup();
+    let mut counter = spacious(4);
+    let accounts = initialize(&mut counter, Bounds::default(), 4);
+    //The authority topped up the rent for the extra space
+    let rent_exempt = Rent::default().minimum_balance(counter.data.len());
+    assert_eq!(counter.lamports, rent_exempt);
+    assert_eq!(
+        accounts[0].lamports,
+        1_000_000_000 - (rent_exempt - 1_000_000)
+    );
+    assert_eq!(
+        History::unpack(&counter.data).unwrap(),
+        Some(History {
+            capacity: 4,
+            len: 0,
+            next: 0
+        })
+    );
+    //Counters without a history have none to read
+    let mut plain = TestCounter::uninitialized(Pubkey::new_unique());
+    initialize(&mut plain, Bounds::default(), 0);
+    assert_eq!(History::unpack(&plain.data).unwrap(), None);
+}
+
+#[test]
+fn history_capacity_is_limited() {
+    let _turn = setup();
+    let mut counter = spacious(MAX_HISTORY_ENTRIES + 1);
+    let mut accounts = vec![
+        TestAccount::funded(counter.authority, 1_000_000_000, true),
+        TestAccount::new(Pubkey::new_unique()),
+        TestAccount::new(system_program::id()),
+    ];
+    let initialize = CounterInstruction::Initialize {
+        bounds: Bounds::default(),
+        fee: 0,
+        history_capacity: MAX_HISTORY_ENTRIES + 1,
+    };
+    assert_eq!(
+        counter.process_with(initialize, &mut accounts),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn history_keeps_the_latest_changes() {
+    let _turn = setup();
+    let bounds = Bounds {
+        min: 0,
+        max: 2,
+        overflow_policy: OverflowPolicy::Wrap,
+    };
+    let mut counter = spacious(3);
+    initialize(&mut counter, bounds, 3);
+    let authority = counter.authority;
+    for slot in 1..=3 {
+        warp(slot, slot as i64);
+        assert_eq!(counter.increament(), Ok(()));
+    }
+    warp(4, 4);
+    assert_eq!(
+        counter.process(
+            CounterInstruction::CompareAndSet {
+                expected: 0,
+                new: 2
+            },
+            true
+        ),
+        Ok(())
+    );
+    warp(5, 5);
+    assert_eq!(counter.process(CounterInstruction::Reset, true), Ok(()));
+    //The first two increaments were written over - the third one wrapped around
+    let entry = |delta, slot, value, actor| HistoryEntry {
+        actor,
+        delta,
+        slot,
+        value,
+    };
+    assert_eq!(
+        history(&counter),
+        [
+            entry(-2, 3, 0, Pubkey::default()),
+            entry(2, 4, 2, authority),
+            entry(-2, 5, 0, authority),
+        ]
+    );
+}
@@ -0,0 +1,157 @@
+//Top contributors ranked on the counter leaderboard
+
+mod support;
+
+use borsh::BorshSerialize;
+use common::{Contribution, Counter, CounterInstruction, Leaderboard, LeaderboardEntry};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use support::{setup, warp, TestAccount, TestCounter};
+
+//Accounts creating the leaderboard of the counter, paid for by its authority
+fn creating(counter: &TestCounter, authority_signs: bool) -> Vec<TestAccount> {
+    let leaderboard = Leaderboard::find_address(&counter.key, &counter.program_id).0;
+    let mut leaderboard = TestAccount::funded(leaderboard, 0, false);
+    leaderboard.data = vec![0; Leaderboard::LEN];
+    vec![
+        TestAccount::funded(counter.authority, 0, authority_signs),
+        TestAccount::funded(counter.authority, 10_000_000, authority_signs),
+        leaderboard,
+        TestAccount::new(system_program::id()),
+    ]
+}
+
+//Increament accounts of `contributor` whose tally so far is `total`, along with the
+//leaderboard
+fn contributing(
+    counter: &TestCounter,
+    contributor: &Pubkey,
+    total: u64,
+    leaderboard: &TestAccount,
+) -> Vec<TestAccount> {
+    let record = Contribution::find_address(&counter.key, contributor, &counter.program_id).0;
+    let mut contribution = TestAccount::new(record);
+    contribution.owner = counter.program_id;
+    contribution.data = Contribution {
+        total,
+        ..Contribution::new(counter.key, *contributor)
+    }
+    .try_to_vec()
+    .unwrap();
+    vec![
+        TestAccount::funded(*contributor, 0, true),
+        TestAccount::new(Pubkey::new_unique()),
+        TestAccount::funded(*contributor, 10_000_000, true),
+        TestAccount::new(Pubkey::new_unique()),
+        TestAccount::new(system_program::id()),
+        contribution,
+        leaderboard.clone(),
+    ]
+}
+
+#[test]
+fn authority_creates_the_leaderboard() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let mut accounts = creating(&counter, false);
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::CreateLeaderboard { capacity: 3 },
+            &mut accounts
+        ),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    let mut accounts = creating(&counter, true);
+    for capacity in [0, 11] {
+        assert_eq!(
+            counter.process_with(
+                CounterInstruction::CreateLeaderboard { capacity },
+                &mut accounts
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::CreateLeaderboard { capacity: 3 },
+            &mut accounts
+        ),
+        Ok(())
+    );
+    assert!(accounts[2].lamports > 0);
+    let leaderboard = Leaderboard::unpack(&accounts[2].data).unwrap();
+    assert_eq!(leaderboard.counter, counter.key);
+    assert_eq!(leaderboard.capacity, 3);
+    assert!(leaderboard.entries().is_empty());
+    //The stubbed system program leaves the owner as it was
+    accounts[2].owner = counter.program_id;
+    assert_eq!(
+        counter.process_with(
+            CounterInstruction::CreateLeaderboard { capacity: 3 },
+            &mut accounts
+        ),
+        Err(ProgramError::AccountAlreadyInitialized)
+    );
+}
+
+#[test]
+fn increaments_rank_the_top_contributors() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let mut leaderboard =
+        TestAccount::new(Leaderboard::find_address(&counter.key, &counter.program_id).0);
+    leaderboard.owner = counter.program_id;
+    leaderboard.data = Leaderboard::new(counter.key, 2).try_to_vec().unwrap();
+    let (first, second, third) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    //The third contributor pushes the second off the full leaderboard, which then
+    //only ties its way back - not enough to get on again
+    for (contributor, total) in [(first, 5), (second, 2), (third, 3), (second, 3)] {
+        let mut accounts = contributing(&counter, &contributor, total, &leaderboard);
+        assert_eq!(
+            counter.process_with(CounterInstruction::Increament, &mut accounts),
+            Ok(())
+        );
+        leaderboard = accounts.remove(6);
+    }
+    let leaderboard = Leaderboard::unpack(&leaderboard.data).unwrap();
+    assert_eq!(
+        leaderboard.entries(),
+        [
+            LeaderboardEntry {
+                contributor: first,
+                total: 6
+            },
+            LeaderboardEntry {
+                contributor: third,
+                total: 4
+            },
+        ]
+    );
+}
+
+#[test]
+fn leaderboard_must_be_the_counters() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    //Until it is created the leaderboard is passed over
+    let leaderboard =
+        TestAccount::new(Leaderboard::find_address(&counter.key, &counter.program_id).0);
+    let mut accounts = contributing(&counter, &Pubkey::new_unique(), 0, &leaderboard);
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Ok(())
+    );
+    accounts[6].key = Pubkey::new_unique();
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::InvalidSeeds)
+    );
+    assert_eq!(counter.counter().count, 1);
+}
@@ -0,0 +1,86 @@
+//Counters written by the original program version brought to the current layout
+
+mod support;
+
+use borsh::BorshSerialize;
+use common::{Counter, CounterInstruction, CounterV0, COUNTER_ACCOUNT_SEED};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use support::{setup, TestAccount, TestCounter};
+
+//A counter account holding `data` - rent exempt for just that - at the address derived
+//from `owner`
+fn outdated(data: Vec<u8>, owner: &Pubkey) -> TestCounter {
+    let mut counter = TestCounter::new(Counter::new(*owner));
+    counter.key =
+        Pubkey::create_with_seed(owner, COUNTER_ACCOUNT_SEED, &counter.program_id).unwrap();
+    counter.lamports = Rent::default().minimum_balance(data.len());
+    counter.data = data;
+    counter
+}
+
+fn migrate(counter: &mut TestCounter, payer: &mut TestAccount) -> Result<(), ProgramError> {
+    let mut accounts = [payer.clone(), TestAccount::new(system_program::id())];
+    let result = counter.process_with(CounterInstruction::Migrate, &mut accounts);
+    *payer = accounts[0].clone();
+    result
+}
+
+fn layout_v0(count: u64) -> Vec<u8> {
+    CounterV0 { count }.try_to_vec().unwrap()
+}
+
+#[test]
+fn headerless_counters_grow_and_go_to_their_owner() {
+    let _turn = setup();
+    let owner = Pubkey::new_unique();
+    let mut counter = outdated(layout_v0(9), &owner);
+    let lamports = counter.lamports;
+    let mut payer = TestAccount::funded(owner, 10_000_000, true);
+    assert_eq!(migrate(&mut counter, &mut payer), Ok(()));
+    assert_eq!(counter.data.len(), Counter::LEN);
+    let state = counter.counter();
+    assert_eq!(state.count, 9);
+    assert_eq!(state.authority, owner);
+    //The payer tops up the rent for the extra space
+    let rent_exempt_lamports = Rent::default().minimum_balance(Counter::LEN);
+    assert_eq!(counter.lamports, rent_exempt_lamports);
+    assert_eq!(
+        payer.lamports,
+        10_000_000 - (rent_exempt_lamports - lamports)
+    );
+}
+
+#[test]
+fn only_the_owner_migrates_headerless_counters() {
+    let _turn = setup();
+    let owner = Pubkey::new_unique();
+    let mut counter = outdated(layout_v0(9), &owner);
+    let mut payer = TestAccount::funded(Pubkey::new_unique(), 10_000_000, true);
+    assert_eq!(
+        migrate(&mut counter, &mut payer),
+        Err(ProgramError::InvalidSeeds)
+    );
+    let mut payer = TestAccount::funded(owner, 10_000_000, false);
+    assert_eq!(
+        migrate(&mut counter, &mut payer),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(counter.data, layout_v0(9));
+}
+
+#[test]
+fn current_counters_are_left_alone() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let (data, lamports) = (counter.data.clone(), counter.lamports);
+    let mut payer = TestAccount::funded(Pubkey::new_unique(), 10_000_000, true);
+    for _ in 0..2 {
+        assert_eq!(migrate(&mut counter, &mut payer), Ok(()));
+        assert_eq!(counter.data, data);
+        assert_eq!(counter.lamports, lamports);
+        assert_eq!(payer.lamports, 10_000_000);
+    }
+}
@@ -0,0 +1,225 @@
+//Token rewards for reaching counter milestones
+
+mod support;
+
+use borsh::BorshSerialize;
+use common::{
+    Bounds, Contribution, Counter, CounterInstruction, Leaderboard, Milestones, OverflowPolicy,
+    TimeSeries,
+};
+use solana_program::program_error::ProgramError;
+use solana_program::program_option::COption;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Mint;
+use support::{invoked, setup, warp, TestAccount, TestCounter};
+
+const EVERY: u64 = 10;
+const REWARD: u64 = 100;
+
+fn at(count: u64) -> TestCounter {
+    let mut counter = Counter::new(Pubkey::new_unique());
+    counter.count = count;
+    TestCounter::new(counter)
+}
+
+//A mint whose tokens `mint_authority` mints
+fn mint(mint_authority: &Pubkey) -> TestAccount {
+    let mut mint = TestAccount::new(Pubkey::new_unique());
+    mint.owner = spl_token::id();
+    mint.is_writable = true;
+    mint.data = vec![0; Mint::LEN];
+    Mint {
+        mint_authority: COption::Some(*mint_authority),
+        decimals: 0,
+        is_initialized: true,
+        ..Mint::default()
+    }
+    .pack_into_slice(&mut mint.data);
+    mint
+}
+
+fn mint_authority(counter: &TestCounter) -> Pubkey {
+    Milestones::find_mint_authority_address(&counter.key, &counter.program_id).0
+}
+
+//Accounts creating the milestones of the counter, rewarded in `mint`
+fn creating(counter: &TestCounter, mint: &TestAccount) -> Vec<TestAccount> {
+    let address = Milestones::find_address(&counter.key, &counter.program_id).0;
+    let mut milestones = TestAccount::funded(address, 0, false);
+    milestones.data = vec![0; Milestones::LEN];
+    vec![
+        TestAccount::funded(counter.authority, 0, true),
+        TestAccount::funded(counter.authority, 10_000_000, true),
+        milestones,
+        TestAccount::new(system_program::id()),
+        mint.clone(),
+    ]
+}
+
+//Increament accounts of `actor` on a counter with milestones - the reward accounts
+//follow its contribution record, the leaderboard, the time series and the(ungated)
+//token account
+fn increamenting(counter: &TestCounter, actor: &Pubkey, mint: &TestAccount) -> Vec<TestAccount> {
+    let record = Contribution::find_address(&counter.key, actor, &counter.program_id).0;
+    let mut contribution = TestAccount::funded(record, 0, false);
+    contribution.data = vec![0; Contribution::LEN];
+    let mut milestones =
+        TestAccount::new(Milestones::find_address(&counter.key, &counter.program_id).0);
+    milestones.owner = counter.program_id;
+    milestones.data = Milestones::new(counter.key, mint.key, EVERY, REWARD)
+        .try_to_vec()
+        .unwrap();
+    let mut destination = TestAccount::new(get_associated_token_address(actor, &mint.key));
+    destination.is_writable = true;
+    vec![
+        TestAccount::funded(*actor, 0, true),
+        TestAccount::new(Pubkey::new_unique()),
+        TestAccount::funded(*actor, 10_000_000, true),
+        TestAccount::new(Pubkey::new_unique()),
+        TestAccount::new(system_program::id()),
+        contribution,
+        TestAccount::new(Leaderboard::find_address(&counter.key, &counter.program_id).0),
+        TestAccount::new(TimeSeries::find_address(&counter.key, &counter.program_id).0),
+        TestAccount::new(system_program::id()),
+        milestones,
+        mint.clone(),
+        TestAccount::new(mint_authority(counter)),
+        destination,
+        TestAccount::new(spl_token::id()),
+    ]
+}
+
+#[test]
+fn authority_creates_the_milestones() {
+    let _turn = setup();
+    let mut counter = at(0);
+    let reward_mint = mint(&mint_authority(&counter));
+    let mut accounts = creating(&counter, &reward_mint);
+    let create = |mint: &TestAccount, every| CounterInstruction::CreateMilestones {
+        mint: mint.key,
+        every,
+        reward: REWARD,
+    };
+    assert_eq!(
+        counter.process_with(create(&reward_mint, 0), &mut accounts),
+        Err(ProgramError::InvalidArgument)
+    );
+    accounts[0].is_signer = false;
+    assert_eq!(
+        counter.process_with(create(&reward_mint, EVERY), &mut accounts),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    accounts[0].is_signer = true;
+    //Rewards come from a mint the counter mints for
+    let foreign_mint = mint(&Pubkey::new_unique());
+    let mut foreign = creating(&counter, &foreign_mint);
+    assert_eq!(
+        counter.process_with(create(&foreign_mint, EVERY), &mut foreign),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        counter.process_with(create(&reward_mint, EVERY), &mut accounts),
+        Ok(())
+    );
+    let milestones = Milestones::unpack(&accounts[2].data).unwrap();
+    assert_eq!(milestones.counter, counter.key);
+    assert_eq!(milestones.mint, reward_mint.key);
+    assert_eq!(milestones.every, EVERY);
+    assert_eq!(milestones.reward, REWARD);
+    //The stubbed system program leaves the owner as it was
+    accounts[2].owner = counter.program_id;
+    assert_eq!(
+        counter.process_with(create(&reward_mint, EVERY), &mut accounts),
+        Err(ProgramError::AccountAlreadyInitialized)
+    );
+}
+
+#[test]
+fn reaching_a_milestone_mints_the_reward() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = at(EVERY - 1);
+    let reward_mint = mint(&mint_authority(&counter));
+    let actor = Pubkey::new_unique();
+    let mut accounts = increamenting(&counter, &actor, &reward_mint);
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Ok(())
+    );
+    assert_eq!(counter.counter().count, EVERY);
+    let mint_to = spl_token::instruction::mint_to(
+        &spl_token::id(),
+        &reward_mint.key,
+        &get_associated_token_address(&actor, &reward_mint.key),
+        &mint_authority(&counter),
+        &[],
+        REWARD,
+    )
+    .unwrap();
+    assert_eq!(invoked(), vec![mint_to]);
+    //Nothing more until the next milestone
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Ok(())
+    );
+    assert_eq!(invoked().len(), 1);
+}
+
+#[test]
+fn rewards_go_to_the_actor() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = at(EVERY - 1);
+    let reward_mint = mint(&mint_authority(&counter));
+    let actor = Pubkey::new_unique();
+    let mut accounts = increamenting(&counter, &actor, &reward_mint);
+    accounts[12].key = get_associated_token_address(&Pubkey::new_unique(), &reward_mint.key);
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::InvalidArgument)
+    );
+    let mut accounts = increamenting(&counter, &actor, &reward_mint);
+    accounts[11].key = Pubkey::new_unique();
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::InvalidSeeds)
+    );
+    //Reward accounts are only needed once a milestone is reached
+    let mut accounts = increamenting(&counter, &actor, &reward_mint);
+    accounts.truncate(10);
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+    counter.data = at(EVERY).data;
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Ok(())
+    );
+    assert!(invoked().is_empty());
+}
+
+#[test]
+fn wrapping_around_is_no_milestone() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut state = Counter::new(Pubkey::new_unique());
+    state.count = 2 * EVERY + 5;
+    state.bounds = Bounds {
+        min: 0,
+        max: 2 * EVERY + 5,
+        overflow_policy: OverflowPolicy::Wrap,
+    };
+    let mut counter = TestCounter::new(state);
+    let reward_mint = mint(&mint_authority(&counter));
+    let mut accounts = increamenting(&counter, &Pubkey::new_unique(), &reward_mint);
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Ok(())
+    );
+    assert_eq!(counter.counter().count, 0);
+    assert!(invoked().is_empty());
+}
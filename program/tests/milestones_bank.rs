@@ -0,0 +1,93 @@
+//Milestone rewards in a bank - minted by the SPL token program, invoked by the
+//counter program signing for the mint authority of the counter
+
+mod bank;
+
+use bank::{add_counter, add_mint, program_test, TestBank};
+use common::{Contribution, Counter, CounterInstruction, Leaderboard, Milestones, TimeSeries};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_program;
+
+const EVERY: u64 = 10;
+const REWARD: u64 = 100;
+
+//Increament by `actor` on a counter with milestones - the reward accounts follow its
+//contribution record, the leaderboard, the time series and the(ungated) token account
+fn increament(bank: &TestBank, counter: &Pubkey, actor: &Pubkey, mint: &Pubkey) -> Instruction {
+    let program_id = &bank.program_id;
+    bank.instruction(
+        CounterInstruction::Increament,
+        counter,
+        vec![
+            AccountMeta::new_readonly(*actor, true),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            AccountMeta::new(bank.context.payer.pubkey(), true),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(
+                Contribution::find_address(counter, actor, program_id).0,
+                false,
+            ),
+            AccountMeta::new_readonly(Leaderboard::find_address(counter, program_id).0, false),
+            AccountMeta::new_readonly(TimeSeries::find_address(counter, program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(Milestones::find_address(counter, program_id).0, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(
+                Milestones::find_mint_authority_address(counter, program_id).0,
+                false,
+            ),
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address(actor, mint),
+                false,
+            ),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+#[tokio::test]
+async fn reaching_a_milestone_mints_the_reward() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+    let authority = Keypair::new();
+    let counter = Counter {
+        count: EVERY - 1,
+        ..Counter::new(authority.pubkey())
+    };
+    let counter = add_counter(&mut program_test, &program_id, &counter);
+    let mint_authority = Milestones::find_mint_authority_address(&counter, &program_id).0;
+    let mint = add_mint(&mut program_test, &mint_authority);
+    let mut bank = TestBank::start(program_test, program_id).await;
+    let create = bank.instruction(
+        CounterInstruction::CreateMilestones {
+            mint,
+            every: EVERY,
+            reward: REWARD,
+        },
+        &counter,
+        vec![
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(bank.context.payer.pubkey(), true),
+            AccountMeta::new(Milestones::find_address(&counter, &program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(mint, false),
+        ],
+    );
+    assert_eq!(bank.process(&[create], &[&authority]).await, Ok(()));
+    let actor = Keypair::new();
+    let token = bank.create_token_account(&actor.pubkey(), &mint).await;
+    let increament = increament(&bank, &counter, &actor.pubkey(), &mint);
+    assert_eq!(
+        bank.process(std::slice::from_ref(&increament), &[&actor])
+            .await,
+        Ok(())
+    );
+    assert_eq!(bank.counter(&counter).await.count, EVERY);
+    assert_eq!(bank.balance(&token).await, REWARD);
+    //Nothing more until the next milestone
+    assert_eq!(bank.process(&[increament], &[&actor]).await, Ok(()));
+    assert_eq!(bank.balance(&token).await, REWARD);
+}
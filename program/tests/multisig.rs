@@ -0,0 +1,107 @@
+//Counters whose authority is a multisig of the program
+
+mod support;
+
+use borsh::BorshSerialize;
+use common::{Counter, CounterInstruction, Multisig};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use support::{setup, TestAccount, TestCounter};
+
+//A counter at 5 with a 2 of 3 multisig authority - and the multisig signers
+fn multisig_counter() -> (TestCounter, TestAccount, Vec<Pubkey>) {
+    let signers = vec![
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let counter = TestCounter::new(Counter {
+        count: 5,
+        ..Counter::new(Pubkey::new_unique())
+    });
+    //The multisig itself never signs
+    let mut multisig = TestAccount::new(counter.authority);
+    multisig.owner = counter.program_id;
+    multisig.data = Multisig::new(2, &signers).try_to_vec().unwrap();
+    (counter, multisig, signers)
+}
+
+//Reset the counter with the multisig followed by `keys` - signing unless listed in
+//`unsigned`
+fn reset(
+    counter: &mut TestCounter,
+    multisig: &TestAccount,
+    keys: &[Pubkey],
+    unsigned: &[Pubkey],
+) -> Result<(), ProgramError> {
+    let mut accounts = vec![multisig.clone()];
+    accounts.extend(keys.iter().map(|key| {
+        let mut account = TestAccount::new(*key);
+        account.is_signer = !unsigned.contains(key);
+        account
+    }));
+    counter.process_with(CounterInstruction::Reset, &mut accounts)
+}
+
+#[test]
+fn threshold_of_signers_resets() {
+    let _turn = setup();
+    let (mut counter, multisig, signers) = multisig_counter();
+    assert_eq!(
+        reset(&mut counter, &multisig, &signers[..1], &[]),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(counter.counter().count, 5);
+    assert_eq!(reset(&mut counter, &multisig, &signers[1..], &[]), Ok(()));
+    assert_eq!(counter.counter().count, 0);
+}
+
+#[test]
+fn repeated_signers_count_once() {
+    let _turn = setup();
+    let (mut counter, multisig, signers) = multisig_counter();
+    assert_eq!(
+        reset(&mut counter, &multisig, &[signers[0], signers[0]], &[]),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(counter.counter().count, 5);
+}
+
+#[test]
+fn only_signing_members_count() {
+    let _turn = setup();
+    let (mut counter, multisig, signers) = multisig_counter();
+    //Listed without signing
+    assert_eq!(
+        reset(&mut counter, &multisig, &signers, &signers[1..]),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    //Signing without being listed
+    assert_eq!(
+        reset(
+            &mut counter,
+            &multisig,
+            &[signers[0], Pubkey::new_unique()],
+            &[]
+        ),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(counter.counter().count, 5);
+    assert_eq!(
+        reset(&mut counter, &multisig, &signers, &signers[..1]),
+        Ok(())
+    );
+    assert_eq!(counter.counter().count, 0);
+}
+
+#[test]
+fn multisig_of_another_program_must_sign() {
+    let _turn = setup();
+    let (mut counter, mut multisig, signers) = multisig_counter();
+    multisig.owner = Pubkey::new_unique();
+    assert_eq!(
+        reset(&mut counter, &multisig, &signers, &[]),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(counter.counter().count, 5);
+}
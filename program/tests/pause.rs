@@ -0,0 +1,95 @@
+//Global pause through the program config
+
+mod support;
+
+use common::{Counter, CounterError, CounterInstruction, ProgramConfig};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use support::{rejected_with, setup, warp, TestCounter};
+
+fn administered(admin: Pubkey) -> TestCounter {
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    counter.config = Some(ProgramConfig::new(admin));
+    counter
+}
+
+#[test]
+fn counters_change_until_paused() {
+    let _turn = setup();
+    warp(1, 1);
+    //No program config yet - nothing is paused
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    assert_eq!(counter.increament(), Ok(()));
+    let admin = Pubkey::new_unique();
+    counter.config = Some(ProgramConfig::new(admin));
+    assert_eq!(counter.increament(), Ok(()));
+    assert_eq!(
+        counter.process_config(CounterInstruction::Pause, &admin, true),
+        Ok(())
+    );
+    assert!(counter.config.as_ref().unwrap().paused);
+    assert_eq!(counter.increament(), rejected_with(CounterError::Paused));
+    assert_eq!(
+        counter.process(CounterInstruction::Reset, true),
+        rejected_with(CounterError::Paused)
+    );
+    assert_eq!(counter.counter().count, 2);
+    assert_eq!(
+        counter.process_config(CounterInstruction::Unpause, &admin, true),
+        Ok(())
+    );
+    assert_eq!(counter.increament(), Ok(()));
+    assert_eq!(counter.counter().count, 3);
+}
+
+#[test]
+fn only_the_admin_pauses() {
+    let _turn = setup();
+    let admin = Pubkey::new_unique();
+    let mut counter = administered(admin);
+    assert_eq!(
+        counter.process_config(CounterInstruction::Pause, &admin, false),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(
+        counter.process_config(CounterInstruction::Pause, &Pubkey::new_unique(), true),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert!(!counter.config.as_ref().unwrap().paused);
+}
+
+#[test]
+fn admin_rotates_in_two_steps() {
+    let _turn = setup();
+    let admin = Pubkey::new_unique();
+    let new_admin = Pubkey::new_unique();
+    let mut counter = administered(admin);
+    assert_eq!(
+        counter.process_config(CounterInstruction::AcceptAdmin, &new_admin, true),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        counter.process_config(CounterInstruction::ProposeAdmin { new_admin }, &admin, true),
+        Ok(())
+    );
+    //Still up to the current admin until accepted
+    assert_eq!(
+        counter.process_config(CounterInstruction::Pause, &new_admin, true),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(
+        counter.process_config(CounterInstruction::AcceptAdmin, &new_admin, true),
+        Ok(())
+    );
+    let config = counter.config.clone().unwrap();
+    assert_eq!(config.admin, new_admin);
+    assert_eq!(config.pending_admin, None);
+    assert_eq!(
+        counter.process_config(CounterInstruction::Pause, &admin, true),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(
+        counter.process_config(CounterInstruction::Pause, &new_admin, true),
+        Ok(())
+    );
+}
@@ -0,0 +1,154 @@
+//Rate limited increaments against the clock of a bank
+
+mod bank;
+
+use bank::{add_counter, failed_with, program_test, rejected_with, TestBank};
+use common::{Counter, CounterError, CounterInstruction, RateLimit, RateUnit};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+
+//A bank holding a counter under `rate_limit` - open to anyone - along with the counter
+//address and its authority
+async fn limited(rate_limit: RateLimit) -> (TestBank, Pubkey, Keypair) {
+    let program_id = Pubkey::new_unique();
+    let authority = Keypair::new();
+    let mut program_test = program_test(program_id);
+    let counter = Counter {
+        rate_limit,
+        ..Counter::new(authority.pubkey())
+    };
+    let counter = add_counter(&mut program_test, &program_id, &counter);
+    (
+        TestBank::start(program_test, program_id).await,
+        counter,
+        authority,
+    )
+}
+
+async fn increament(bank: &mut TestBank, counter: &Pubkey) -> Result<(), TransactionError> {
+    let instruction = bank.instruction(CounterInstruction::Increament, counter, vec![]);
+    bank.process(&[instruction], &[]).await
+}
+
+#[tokio::test]
+async fn unlimited_by_default() {
+    let (mut bank, counter, _) = limited(RateLimit::default()).await;
+    bank.warp(100, 1_000).await;
+    for _ in 0..3 {
+        assert_eq!(increament(&mut bank, &counter).await, Ok(()));
+    }
+    let state = bank.counter(&counter).await;
+    assert_eq!(state.count, 3);
+    assert_eq!(state.last_update_slot, 100);
+    assert_eq!(state.last_update_timestamp, 1_000);
+}
+
+#[tokio::test]
+async fn enforces_minimum_interval_in_slots() {
+    let (mut bank, counter, _) = limited(RateLimit {
+        unit: RateUnit::Slots,
+        min_interval: 5,
+        ..RateLimit::default()
+    })
+    .await;
+    bank.warp(10, 1_000).await;
+    assert_eq!(increament(&mut bank, &counter).await, Ok(()));
+    bank.warp(14, 1_100).await;
+    assert_eq!(
+        increament(&mut bank, &counter).await,
+        rejected_with(CounterError::IncreamentTooSoon)
+    );
+    bank.warp(15, 1_100).await;
+    assert_eq!(increament(&mut bank, &counter).await, Ok(()));
+    let state = bank.counter(&counter).await;
+    assert_eq!(state.count, 2);
+    assert_eq!(state.last_update_slot, 15);
+}
+
+#[tokio::test]
+async fn enforces_minimum_interval_in_seconds() {
+    let (mut bank, counter, _) = limited(RateLimit {
+        unit: RateUnit::Seconds,
+        min_interval: 30,
+        ..RateLimit::default()
+    })
+    .await;
+    bank.warp(10, 1_000).await;
+    assert_eq!(increament(&mut bank, &counter).await, Ok(()));
+    //Slots move on, the wall clock does not
+    bank.warp(500, 1_029).await;
+    assert_eq!(
+        increament(&mut bank, &counter).await,
+        rejected_with(CounterError::IncreamentTooSoon)
+    );
+    bank.warp(501, 1_030).await;
+    assert_eq!(increament(&mut bank, &counter).await, Ok(()));
+}
+
+#[tokio::test]
+async fn caps_increaments_per_window() {
+    let (mut bank, counter, _) = limited(RateLimit {
+        unit: RateUnit::Seconds,
+        window: 60,
+        max_per_window: 2,
+        ..RateLimit::default()
+    })
+    .await;
+    bank.warp(10, 1_020).await;
+    assert_eq!(increament(&mut bank, &counter).await, Ok(()));
+    assert_eq!(increament(&mut bank, &counter).await, Ok(()));
+    bank.warp(11, 1_079).await;
+    assert_eq!(
+        increament(&mut bank, &counter).await,
+        rejected_with(CounterError::WindowLimitReached)
+    );
+    //Next window
+    bank.warp(12, 1_080).await;
+    assert_eq!(increament(&mut bank, &counter).await, Ok(()));
+    let state = bank.counter(&counter).await;
+    assert_eq!(state.count, 3);
+    assert_eq!(state.window_start, 1_080);
+    assert_eq!(state.window_count, 1);
+}
+
+#[tokio::test]
+async fn rate_limit_is_set_by_the_authority() {
+    let (mut bank, counter, authority) = limited(RateLimit::default()).await;
+    let rate_limit = RateLimit {
+        unit: RateUnit::Slots,
+        min_interval: 10,
+        ..RateLimit::default()
+    };
+    let set_rate_limit = |rate_limit, signs| {
+        bank.instruction(
+            CounterInstruction::SetRateLimit { rate_limit },
+            &counter,
+            vec![AccountMeta::new_readonly(authority.pubkey(), signs)],
+        )
+    };
+    let (unsigned, signed) = (
+        set_rate_limit(rate_limit, false),
+        set_rate_limit(rate_limit, true),
+    );
+    //A cap needs a window to count in
+    let capped = set_rate_limit(
+        RateLimit {
+            max_per_window: 1,
+            ..RateLimit::default()
+        },
+        true,
+    );
+    assert_eq!(
+        bank.process(&[unsigned], &[]).await,
+        failed_with(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(
+        bank.process(&[capped], &[&authority]).await,
+        failed_with(ProgramError::InvalidArgument)
+    );
+    assert_eq!(bank.process(&[signed], &[&authority]).await, Ok(()));
+    assert_eq!(bank.counter(&counter).await.rate_limit, rate_limit);
+}
@@ -0,0 +1,90 @@
+//Counter values recorded as of a slot, never changed afterwards
+
+mod support;
+
+use common::{Counter, CounterInstruction, Snapshot};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use support::{setup, warp, TestAccount, TestCounter};
+
+fn at(count: u64) -> TestCounter {
+    let mut counter = Counter::new(Pubkey::new_unique());
+    counter.count = count;
+    TestCounter::new(counter)
+}
+
+//Accounts taking the snapshot labelled `label`, paid for by the authority
+fn snapshotting(counter: &TestCounter, label: &str, authority_signs: bool) -> Vec<TestAccount> {
+    let address = Snapshot::find_address(&counter.key, label, &counter.program_id).0;
+    let mut snapshot = TestAccount::funded(address, 0, false);
+    snapshot.data = vec![0; Snapshot::space(label)];
+    vec![
+        TestAccount::funded(counter.authority, 0, authority_signs),
+        TestAccount::funded(counter.authority, 10_000_000, authority_signs),
+        snapshot,
+        TestAccount::new(system_program::id()),
+    ]
+}
+
+fn snapshot(label: &str) -> CounterInstruction {
+    CounterInstruction::Snapshot {
+        label: label.to_string(),
+    }
+}
+
+#[test]
+fn snapshot_records_the_value_as_of_the_slot() {
+    let _turn = setup();
+    warp(77, 1_700_000_000);
+    let mut counter = at(42);
+    let mut accounts = snapshotting(&counter, "epoch-1", true);
+    assert_eq!(
+        counter.process_with(snapshot("epoch-1"), &mut accounts),
+        Ok(())
+    );
+    assert!(accounts[2].lamports > 0);
+    let recorded = Snapshot::unpack(&accounts[2].data).unwrap();
+    assert_eq!(recorded.counter, counter.key);
+    assert_eq!(recorded.label, "epoch-1");
+    assert_eq!(recorded.value, 42);
+    assert_eq!(recorded.slot, 77);
+    assert_eq!(recorded.unix_timestamp, 1_700_000_000);
+    //Once taken it stays as it is - the stubbed system program leaves the owner as it was
+    accounts[2].owner = counter.program_id;
+    assert_eq!(
+        counter.process_with(snapshot("epoch-1"), &mut accounts),
+        Err(ProgramError::AccountAlreadyInitialized)
+    );
+}
+
+#[test]
+fn only_the_authority_takes_snapshots() {
+    let _turn = setup();
+    let mut counter = at(1);
+    let mut accounts = snapshotting(&counter, "epoch-1", false);
+    assert_eq!(
+        counter.process_with(snapshot("epoch-1"), &mut accounts),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    //Under a label of its own
+    let mut accounts = snapshotting(&counter, "epoch-1", true);
+    assert_eq!(
+        counter.process_with(snapshot("epoch-2"), &mut accounts),
+        Err(ProgramError::InvalidSeeds)
+    );
+}
+
+#[test]
+fn snapshot_labels_are_limited() {
+    let _turn = setup();
+    let mut counter = at(1);
+    let long = "x".repeat(33);
+    for label in ["", long.as_str()] {
+        let mut accounts = snapshotting(&counter, "epoch-1", true);
+        assert_eq!(
+            counter.process_with(snapshot(label), &mut accounts),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+}
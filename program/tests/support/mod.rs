@@ -0,0 +1,316 @@
+//Test harness driving the processor directly with hand built accounts. The clock
+//sysvar is stubbed out so that tests can warp it, and system program transfers and
+//account creations are carried out by the stubs - syscall stubs are process wide,
+//hence tests take turns. The counter account is laid out as the runtime serializes
+//it, so that the program may realloc it. Counter instructions get the program config
+//account appended - not created unless `TestCounter::config` is set
+#![allow(dead_code)]
+
+use borsh::BorshSerialize;
+use common::{Counter, CounterError, CounterInstruction, ProgramConfig};
+use program::processor::Processor;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE, SUCCESS};
+use solana_program::instruction::Instruction;
+use solana_program::program_error::ProgramError;
+use solana_program::program_stubs::{self, SyscallStubs};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use std::slice;
+use std::sync::{Mutex, MutexGuard, RwLock};
+
+static CLOCK: RwLock<Clock> = RwLock::new(Clock {
+    slot: 0,
+    epoch_start_timestamp: 0,
+    epoch: 0,
+    leader_schedule_epoch: 0,
+    unix_timestamp: 0,
+});
+
+static TURN: Mutex<()> = Mutex::new(());
+static INVOKED: Mutex<Vec<Instruction>> = Mutex::new(Vec::new());
+
+pub struct TestStubs;
+
+impl SyscallStubs for TestStubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe {
+            *(var_addr as *mut Clock) = CLOCK.read().unwrap().clone();
+        }
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe {
+            *(var_addr as *mut Rent) = Rent::default();
+        }
+        SUCCESS
+    }
+
+    //System program transfers and account creations move the lamports - nothing else
+    //is invoked for real, other programs' instructions are only recorded. Created
+    //accounts must come with their data allocated
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if instruction.program_id != system_program::id() {
+            INVOKED.lock().unwrap().push(instruction.clone());
+            return Ok(());
+        }
+        let lamports = match instruction.data.as_slice() {
+            [0 | 2, 0, 0, 0, rest @ ..] => u64::from_le_bytes(rest[..8].try_into().unwrap()),
+            _ => return Ok(()),
+        };
+        let find = |key: &Pubkey| {
+            account_infos
+                .iter()
+                .find(|account_info| account_info.key == key)
+                .unwrap()
+        };
+        let from = find(&instruction.accounts[0].pubkey);
+        let to = find(&instruction.accounts[1].pubkey);
+        if !from.is_signer && signers_seeds.is_empty() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let remaining = from
+            .lamports()
+            .checked_sub(lamports)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        **from.try_borrow_mut_lamports()? = remaining;
+        **to.try_borrow_mut_lamports()? += lamports;
+        Ok(())
+    }
+}
+
+pub fn setup() -> MutexGuard<'static, ()> {
+    let turn = TURN.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    program_stubs::set_syscall_stubs(Box::new(TestStubs));
+    INVOKED.lock().unwrap().clear();
+    turn
+}
+
+//Instructions invoked on programs other than the system program since `setup`
+pub fn invoked() -> Vec<Instruction> {
+    INVOKED.lock().unwrap().clone()
+}
+
+pub fn warp(slot: u64, unix_timestamp: i64) {
+    let mut clock = CLOCK.write().unwrap();
+    clock.slot = slot;
+    clock.unix_timestamp = unix_timestamp;
+}
+
+pub struct TestCounter {
+    pub program_id: Pubkey,
+    pub key: Pubkey,
+    pub authority: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub config: Option<ProgramConfig>,
+}
+
+impl TestCounter {
+    //The counter account holding `counter`, owned by a made up program id
+    pub fn new(counter: Counter) -> Self {
+        let authority = counter.authority;
+        let mut data = counter.try_to_vec().unwrap();
+        data.resize(Counter::LEN, 0);
+        Self {
+            program_id: Pubkey::new_unique(),
+            key: Pubkey::new_unique(),
+            authority,
+            lamports: 1_000_000,
+            data,
+            config: None,
+        }
+    }
+
+    //A freshly created(zeroed) counter account, waiting for `Initialize`
+    pub fn uninitialized(authority: Pubkey) -> Self {
+        Self {
+            data: vec![0; Counter::LEN],
+            ..Self::new(Counter::new(authority))
+        }
+    }
+
+    //Run the instruction against the counter - with the counter authority as the
+    //second account
+    pub fn process(
+        &mut self,
+        instruction: CounterInstruction,
+        authority_signs: bool,
+    ) -> ProgramResult {
+        let mut authority = TestAccount::new(self.authority);
+        authority.is_signer = authority_signs;
+        self.process_with(instruction, &mut [authority])
+    }
+
+    //Run the instruction against the counter followed by `accounts`
+    pub fn process_with(
+        &mut self,
+        instruction: CounterInstruction,
+        accounts: &mut [TestAccount],
+    ) -> ProgramResult {
+        let mut config = TestAccount::new(ProgramConfig::find_address(&self.program_id).0);
+        if let Some(ref program_config) = self.config {
+            config.owner = self.program_id;
+            config.data = program_config.try_to_vec().unwrap();
+            config.data.resize(ProgramConfig::LEN, 0);
+        }
+        let key = SerializedKey {
+            original_data_len: self.data.len() as u32,
+            key: self.key,
+        };
+        let mut data = SerializedData::new(&self.data);
+        let mut account_infos = vec![AccountInfo::new(
+            &key.key,
+            false,
+            true,
+            &mut self.lamports,
+            data.data(),
+            &self.program_id,
+            false,
+            0,
+        )];
+        account_infos.extend(accounts.iter_mut().map(TestAccount::info));
+        if !instruction.is_config_instruction() {
+            account_infos.push(config.info());
+        }
+        let result = Processor::process_instruction(
+            &self.program_id,
+            &account_infos,
+            &instruction.try_to_vec().unwrap(),
+        );
+        drop(account_infos);
+        self.data = data.data().to_vec();
+        result
+    }
+
+    //Run a program config instruction - the config must be set - signed by `admin`
+    pub fn process_config(
+        &mut self,
+        instruction: CounterInstruction,
+        admin: &Pubkey,
+        admin_signs: bool,
+    ) -> ProgramResult {
+        let mut config = TestAccount::new(ProgramConfig::find_address(&self.program_id).0);
+        config.owner = self.program_id;
+        config.is_writable = true;
+        config.lamports = 1_000_000;
+        config.data = self.config.as_ref().unwrap().try_to_vec().unwrap();
+        config.data.resize(ProgramConfig::LEN, 0);
+        let mut admin = TestAccount::new(*admin);
+        admin.is_signer = admin_signs;
+        let result = Processor::process_instruction(
+            &self.program_id,
+            &[config.info(), admin.info()],
+            &instruction.try_to_vec().unwrap(),
+        );
+        self.config = Some(ProgramConfig::unpack(&config.data).unwrap());
+        result
+    }
+
+    pub fn increament(&mut self) -> ProgramResult {
+        self.process(CounterInstruction::Increament, false)
+    }
+
+    pub fn counter(&self) -> Counter {
+        Counter::unpack(&self.data).unwrap()
+    }
+}
+
+//The counter account key the way the runtime serializes it - right behind the
+//original data length, which `AccountInfo::realloc` looks up
+#[repr(C)]
+struct SerializedKey {
+    original_data_len: u32,
+    key: Pubkey,
+}
+
+//The counter account data the way the runtime serializes it - behind its length and
+//ahead of the room it may grow into - so that the program can realloc it
+struct SerializedData {
+    //Words keep the length aligned
+    buffer: Vec<u64>,
+}
+
+impl SerializedData {
+    fn new(data: &[u8]) -> Self {
+        let mut serialized = Self {
+            buffer: vec![0; 1 + (data.len() + MAX_PERMITTED_DATA_INCREASE) / 8 + 1],
+        };
+        let bytes = serialized.bytes();
+        bytes[..8].copy_from_slice(&(data.len() as u64).to_le_bytes());
+        bytes[8..8 + data.len()].copy_from_slice(data);
+        serialized
+    }
+
+    fn bytes(&mut self) -> &mut [u8] {
+        let len = self.buffer.len() * 8;
+        unsafe { slice::from_raw_parts_mut(self.buffer.as_mut_ptr() as *mut u8, len) }
+    }
+
+    //The data - as long as the serialized length says
+    fn data(&mut self) -> &mut [u8] {
+        let bytes = self.bytes();
+        let len = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+        &mut bytes[8..8 + len]
+    }
+}
+
+//Any account other than the counter - a read only, non signing system account
+//unless changed
+#[derive(Clone)]
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl TestAccount {
+    pub fn new(key: Pubkey) -> Self {
+        Self {
+            key,
+            owner: system_program::id(),
+            lamports: 0,
+            data: vec![],
+            is_signer: false,
+            is_writable: false,
+        }
+    }
+
+    //A writable system account holding `lamports` - signing if `is_signer`
+    pub fn funded(key: Pubkey, lamports: u64, is_signer: bool) -> Self {
+        Self {
+            lamports,
+            is_signer,
+            is_writable: true,
+            ..Self::new(key)
+        }
+    }
+
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            self.is_writable,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+pub fn rejected_with(err: CounterError) -> ProgramResult {
+    Err(ProgramError::from(err))
+}
@@ -0,0 +1,135 @@
+//Increaments counted per time bucket
+
+mod support;
+
+use borsh::BorshSerialize;
+use common::{
+    Contribution, Counter, CounterInstruction, Leaderboard, TimeSeries, MAX_TIME_BUCKETS,
+};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use support::{setup, warp, TestAccount, TestCounter};
+
+const HOUR: i64 = 3_600;
+
+//Increament accounts of a new contributor - the time series follows its contribution
+//record and the leaderboard
+fn counting(counter: &TestCounter, time_series: &TestAccount) -> Vec<TestAccount> {
+    let contributor = Pubkey::new_unique();
+    let record = Contribution::find_address(&counter.key, &contributor, &counter.program_id).0;
+    let mut contribution = TestAccount::funded(record, 0, false);
+    contribution.data = vec![0; Contribution::LEN];
+    vec![
+        TestAccount::funded(contributor, 0, true),
+        TestAccount::new(Pubkey::new_unique()),
+        TestAccount::funded(contributor, 10_000_000, true),
+        TestAccount::new(Pubkey::new_unique()),
+        TestAccount::new(system_program::id()),
+        contribution,
+        TestAccount::new(Leaderboard::find_address(&counter.key, &counter.program_id).0),
+        time_series.clone(),
+    ]
+}
+
+fn hourly(counter: &TestCounter, bucket_count: u16, unix_timestamp: i64) -> TestAccount {
+    let mut time_series =
+        TestAccount::new(TimeSeries::find_address(&counter.key, &counter.program_id).0);
+    time_series.owner = counter.program_id;
+    time_series.data = TimeSeries::new(counter.key, HOUR as u32, bucket_count, unix_timestamp)
+        .try_to_vec()
+        .unwrap();
+    time_series
+}
+
+#[test]
+fn authority_creates_the_time_series() {
+    let _turn = setup();
+    warp(1, 10 * HOUR + 5);
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let address = TimeSeries::find_address(&counter.key, &counter.program_id).0;
+    let mut time_series = TestAccount::funded(address, 0, false);
+    time_series.data = vec![0; TimeSeries::space(24)];
+    let mut accounts = vec![
+        TestAccount::funded(counter.authority, 0, true),
+        TestAccount::funded(counter.authority, 10_000_000, true),
+        time_series,
+        TestAccount::new(system_program::id()),
+    ];
+    for (bucket_width, bucket_count) in [(0, 24), (HOUR as u32, 0), (1, MAX_TIME_BUCKETS + 1)] {
+        assert_eq!(
+            counter.process_with(
+                CounterInstruction::CreateTimeSeries {
+                    bucket_width,
+                    bucket_count
+                },
+                &mut accounts
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+    let create = CounterInstruction::CreateTimeSeries {
+        bucket_width: HOUR as u32,
+        bucket_count: 24,
+    };
+    accounts[0].is_signer = false;
+    assert_eq!(
+        counter.process_with(create.clone(), &mut accounts),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    accounts[0].is_signer = true;
+    assert_eq!(counter.process_with(create, &mut accounts), Ok(()));
+    assert!(accounts[2].lamports > 0);
+    let time_series = TimeSeries::unpack(&accounts[2].data).unwrap();
+    assert_eq!(time_series.counter, counter.key);
+    assert_eq!(time_series.latest_start, 10 * HOUR);
+    assert_eq!(time_series.buckets, vec![0; 24]);
+}
+
+#[test]
+fn increaments_roll_over_to_new_buckets() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let mut time_series = hourly(&counter, 3, 0);
+    //Two in the first hour, one in the second, one three hours later - by then the
+    //first two hours are gone
+    for (slot, unix_timestamp) in [(1, 10), (2, HOUR - 1), (3, HOUR), (4, 4 * HOUR + 1)] {
+        warp(slot, unix_timestamp);
+        let mut accounts = counting(&counter, &time_series);
+        assert_eq!(
+            counter.process_with(CounterInstruction::Increament, &mut accounts),
+            Ok(())
+        );
+        time_series = accounts.remove(7);
+    }
+    let time_series = TimeSeries::unpack(&time_series.data).unwrap();
+    assert_eq!(
+        time_series.series(4 * HOUR + 1),
+        [(2 * HOUR, 0), (3 * HOUR, 0), (4 * HOUR, 1)]
+    );
+    //Reading later rolls over too - without writing
+    assert_eq!(
+        time_series.series(5 * HOUR),
+        [(3 * HOUR, 0), (4 * HOUR, 1), (5 * HOUR, 0)]
+    );
+    assert_eq!(counter.counter().count, 4);
+}
+
+#[test]
+fn time_series_is_optional_but_must_be_the_counters() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let uncreated = TestAccount::new(TimeSeries::find_address(&counter.key, &counter.program_id).0);
+    let mut accounts = counting(&counter, &uncreated);
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Ok(())
+    );
+    accounts[7].key = Pubkey::new_unique();
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::InvalidSeeds)
+    );
+    assert_eq!(counter.counter().count, 1);
+}
@@ -0,0 +1,163 @@
+//Counters only holders of a token may increament
+
+mod support;
+
+use common::{
+    Contribution, Counter, CounterError, CounterInstruction, Leaderboard, TimeSeries, TokenGate,
+};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use spl_token::state::{Account as TokenAccount, AccountState};
+use support::{rejected_with, setup, warp, TestAccount, TestCounter};
+
+const MIN_BALANCE: u64 = 500;
+
+//A counter gated on holding `MIN_BALANCE` of a made up mint
+fn gated() -> TestCounter {
+    let mut counter = Counter::new(Pubkey::new_unique());
+    counter.token_gate = TokenGate {
+        mint: Pubkey::new_unique(),
+        min_balance: MIN_BALANCE,
+    };
+    TestCounter::new(counter)
+}
+
+//Token account of `owner` holding `amount` of `mint`
+fn token_account(owner: &Pubkey, mint: &Pubkey, amount: u64) -> TestAccount {
+    let mut account = TestAccount::new(Pubkey::new_unique());
+    account.owner = spl_token::id();
+    account.data = vec![0; TokenAccount::LEN];
+    TokenAccount {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: AccountState::Initialized,
+        ..TokenAccount::default()
+    }
+    .pack_into_slice(&mut account.data);
+    account
+}
+
+//Increament accounts of `holder` - the token account follows its contribution record,
+//the leaderboard and the time series
+fn increamenting(counter: &TestCounter, holder: &Pubkey, token: TestAccount) -> Vec<TestAccount> {
+    let record = Contribution::find_address(&counter.key, holder, &counter.program_id).0;
+    let mut contribution = TestAccount::funded(record, 0, false);
+    contribution.data = vec![0; Contribution::LEN];
+    vec![
+        TestAccount::funded(*holder, 0, true),
+        TestAccount::new(Pubkey::new_unique()),
+        TestAccount::funded(*holder, 10_000_000, true),
+        TestAccount::new(Pubkey::new_unique()),
+        TestAccount::new(system_program::id()),
+        contribution,
+        TestAccount::new(Leaderboard::find_address(&counter.key, &counter.program_id).0),
+        TestAccount::new(TimeSeries::find_address(&counter.key, &counter.program_id).0),
+        token,
+    ]
+}
+
+#[test]
+fn authority_sets_the_token_gate() {
+    let _turn = setup();
+    let mut counter = TestCounter::new(Counter::new(Pubkey::new_unique()));
+    let token_gate = TokenGate {
+        mint: Pubkey::new_unique(),
+        min_balance: MIN_BALANCE,
+    };
+    assert_eq!(
+        counter.process(CounterInstruction::SetTokenGate { token_gate }, false),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    let without_balance = TokenGate {
+        min_balance: 0,
+        ..token_gate
+    };
+    assert_eq!(
+        counter.process(
+            CounterInstruction::SetTokenGate {
+                token_gate: without_balance
+            },
+            true
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        counter.process(CounterInstruction::SetTokenGate { token_gate }, true),
+        Ok(())
+    );
+    assert_eq!(counter.counter().token_gate, token_gate);
+    //Lifted again
+    assert_eq!(
+        counter.process(
+            CounterInstruction::SetTokenGate {
+                token_gate: TokenGate::default()
+            },
+            true
+        ),
+        Ok(())
+    );
+    assert!(!counter.counter().token_gate.is_enabled());
+}
+
+#[test]
+fn holders_increament() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = gated();
+    let mint = counter.counter().token_gate.mint;
+    let holder = Pubkey::new_unique();
+    let token = token_account(&holder, &mint, MIN_BALANCE);
+    let mut accounts = increamenting(&counter, &holder, token);
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Ok(())
+    );
+    assert_eq!(counter.counter().count, 1);
+}
+
+#[test]
+fn non_holders_are_turned_away() {
+    let _turn = setup();
+    warp(1, 1);
+    let mut counter = gated();
+    let mint = counter.counter().token_gate.mint;
+    let holder = Pubkey::new_unique();
+    for token in [
+        token_account(&holder, &mint, MIN_BALANCE - 1),
+        token_account(&holder, &Pubkey::new_unique(), MIN_BALANCE),
+        token_account(&Pubkey::new_unique(), &mint, MIN_BALANCE),
+    ] {
+        let mut accounts = increamenting(&counter, &holder, token);
+        assert_eq!(
+            counter.process_with(CounterInstruction::Increament, &mut accounts),
+            rejected_with(CounterError::TokenHoldingRequired)
+        );
+    }
+    let mut accounts = increamenting(
+        &counter,
+        &holder,
+        token_account(&holder, &mint, MIN_BALANCE),
+    );
+    //Not a token program account
+    accounts[8].owner = system_program::id();
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::IncorrectProgramId)
+    );
+    //Holders sign for their token account
+    accounts[0].is_signer = false;
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    accounts[0].is_signer = true;
+    accounts.truncate(8);
+    assert_eq!(
+        counter.process_with(CounterInstruction::Increament, &mut accounts),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+    assert_eq!(counter.counter().count, 0);
+}
@@ -0,0 +1,90 @@
+//Token gated counters in a bank - holdings minted through the SPL token program to
+//associated token accounts
+
+mod bank;
+
+use bank::{add_counter, add_mint, program_test, rejected_with, TestBank};
+use common::{
+    Contribution, Counter, CounterError, CounterInstruction, Leaderboard, TimeSeries, TokenGate,
+};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_program;
+
+const MIN_BALANCE: u64 = 500;
+
+//A bank holding a counter gated on `MIN_BALANCE` of a new mint - along with the
+//counter address, the mint and its mint authority
+async fn gated() -> (TestBank, Pubkey, Pubkey, Keypair) {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+    let mint_authority = Keypair::new();
+    let mint = add_mint(&mut program_test, &mint_authority.pubkey());
+    let mut counter = Counter::new(Pubkey::new_unique());
+    counter.token_gate = TokenGate {
+        mint,
+        min_balance: MIN_BALANCE,
+    };
+    let counter = add_counter(&mut program_test, &program_id, &counter);
+    let bank = TestBank::start(program_test, program_id).await;
+    (bank, counter, mint, mint_authority)
+}
+
+//Increament by `holder` passing `token` - the token account follows its contribution
+//record(paid for by the bank payer), the leaderboard and the time series
+fn increament(bank: &TestBank, counter: &Pubkey, holder: &Pubkey, token: &Pubkey) -> Instruction {
+    let program_id = &bank.program_id;
+    bank.instruction(
+        CounterInstruction::Increament,
+        counter,
+        vec![
+            AccountMeta::new_readonly(*holder, true),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            AccountMeta::new(bank.context.payer.pubkey(), true),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(
+                Contribution::find_address(counter, holder, program_id).0,
+                false,
+            ),
+            AccountMeta::new_readonly(Leaderboard::find_address(counter, program_id).0, false),
+            AccountMeta::new_readonly(TimeSeries::find_address(counter, program_id).0, false),
+            AccountMeta::new_readonly(*token, false),
+        ],
+    )
+}
+
+#[tokio::test]
+async fn holders_of_minted_tokens_increament() {
+    let (mut bank, counter, mint, mint_authority) = gated().await;
+    let holder = Keypair::new();
+    let token = bank.create_token_account(&holder.pubkey(), &mint).await;
+    bank.mint_to(&mint, &mint_authority, &token, MIN_BALANCE - 1)
+        .await;
+    let increament = increament(&bank, &counter, &holder.pubkey(), &token);
+    assert_eq!(
+        bank.process(std::slice::from_ref(&increament), &[&holder])
+            .await,
+        rejected_with(CounterError::TokenHoldingRequired)
+    );
+    bank.mint_to(&mint, &mint_authority, &token, 1).await;
+    assert_eq!(bank.balance(&token).await, MIN_BALANCE);
+    assert_eq!(bank.process(&[increament], &[&holder]).await, Ok(()));
+    assert_eq!(bank.counter(&counter).await.count, 1);
+}
+
+#[tokio::test]
+async fn holdings_of_others_do_not_count() {
+    let (mut bank, counter, mint, mint_authority) = gated().await;
+    let (holder, stranger) = (Keypair::new(), Keypair::new());
+    let token = bank.create_token_account(&holder.pubkey(), &mint).await;
+    bank.mint_to(&mint, &mint_authority, &token, MIN_BALANCE)
+        .await;
+    let increament = increament(&bank, &counter, &stranger.pubkey(), &token);
+    assert_eq!(
+        bank.process(&[increament], &[&stranger]).await,
+        rejected_with(CounterError::TokenHoldingRequired)
+    );
+    assert_eq!(bank.counter(&counter).await.count, 0);
+}
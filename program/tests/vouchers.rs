@@ -0,0 +1,216 @@
+//Increaments approved off-chain by the counter authority and redeemed once
+
+mod support;
+
+use borsh::BorshSerialize;
+use common::{
+    Contribution, Counter, CounterError, CounterInstruction, Leaderboard, RateLimit, RateUnit,
+    RedeemedVoucher, TimeSeries, TokenGate, Voucher,
+};
+use solana_program::ed25519_program;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use solana_program::sysvar::instructions::{
+    self, construct_instructions_data, store_current_index, BorrowedInstruction,
+};
+use spl_token::state::{Account as TokenAccount, AccountState};
+use support::{rejected_with, setup, warp, TestAccount, TestCounter};
+
+const EXPIRY: i64 = 1_000;
+
+fn at(count: u64) -> TestCounter {
+    let mut counter = Counter::new(Pubkey::new_unique());
+    counter.count = count;
+    TestCounter::new(counter)
+}
+
+fn redeem(voucher: &Voucher) -> CounterInstruction {
+    CounterInstruction::RedeemVoucher {
+        amount: voucher.amount,
+        nonce: voucher.nonce,
+        expiry: voucher.expiry,
+    }
+}
+
+//Accounts redeeming `voucher` - the instructions sysvar holds an Ed25519 program
+//instruction verifying `signer` ahead of the redeeming one. The stubs verify no
+//signature, only what the instruction claims to have verified
+fn redeeming(counter: &TestCounter, voucher: &Voucher, signer: &Pubkey) -> Vec<TestAccount> {
+    let ed25519_data = voucher.ed25519_instruction_data(signer, &[0; 64]);
+    let redeem_data = redeem(voucher).try_to_vec().unwrap();
+    let mut sysvar = TestAccount::new(instructions::id());
+    sysvar.data = construct_instructions_data(&[
+        BorrowedInstruction {
+            program_id: &ed25519_program::id(),
+            accounts: vec![],
+            data: &ed25519_data,
+        },
+        BorrowedInstruction {
+            program_id: &counter.program_id,
+            accounts: vec![],
+            data: &redeem_data,
+        },
+    ]);
+    store_current_index(&mut sysvar.data, 1);
+    let address = RedeemedVoucher::find_address(&counter.key, voucher.nonce, &counter.program_id).0;
+    let mut record = TestAccount::funded(address, 0, false);
+    record.data = vec![0; RedeemedVoucher::LEN];
+    vec![
+        TestAccount::funded(Pubkey::new_unique(), 10_000_000, true),
+        record,
+        sysvar,
+        TestAccount::new(system_program::id()),
+    ]
+}
+
+fn voucher(counter: &TestCounter, amount: u64, nonce: u64) -> Voucher {
+    Voucher {
+        counter: counter.key,
+        amount,
+        nonce,
+        expiry: EXPIRY,
+    }
+}
+
+//Accounts redeeming `voucher` by a holder of `amount` of the gate mint - the treasury and
+//the accounts of an increament follow
+fn redeeming_as_holder(counter: &TestCounter, voucher: &Voucher, amount: u64) -> Vec<TestAccount> {
+    let mut accounts = redeeming(counter, voucher, &counter.authority);
+    let redeemer = accounts[0].key;
+    let record = Contribution::find_address(&counter.key, &redeemer, &counter.program_id).0;
+    let mut contribution = TestAccount::funded(record, 0, false);
+    contribution.data = vec![0; Contribution::LEN];
+    let mut token = TestAccount::new(Pubkey::new_unique());
+    token.owner = spl_token::id();
+    token.data = vec![0; TokenAccount::LEN];
+    TokenAccount {
+        mint: counter.counter().token_gate.mint,
+        owner: redeemer,
+        amount,
+        state: AccountState::Initialized,
+        ..TokenAccount::default()
+    }
+    .pack_into_slice(&mut token.data);
+    accounts.extend([
+        TestAccount::new(Pubkey::new_unique()),
+        contribution,
+        TestAccount::new(Leaderboard::find_address(&counter.key, &counter.program_id).0),
+        TestAccount::new(TimeSeries::find_address(&counter.key, &counter.program_id).0),
+        token,
+    ]);
+    accounts
+}
+
+#[test]
+fn voucher_redeems_once() {
+    let _turn = setup();
+    warp(5, EXPIRY);
+    let mut counter = at(3);
+    let voucher = voucher(&counter, 10, 1);
+    let mut accounts = redeeming(&counter, &voucher, &counter.authority);
+    assert_eq!(
+        counter.process_with(redeem(&voucher), &mut accounts),
+        Ok(())
+    );
+    assert_eq!(counter.counter().count, 13);
+    let record = RedeemedVoucher::unpack(&accounts[1].data).unwrap();
+    assert_eq!(record.counter, counter.key);
+    assert_eq!(record.nonce, 1);
+    assert_eq!(record.redeemer, accounts[0].key);
+    assert_eq!(record.slot, 5);
+    //Replayed - the stubbed system program leaves the owner as it was
+    accounts[1].owner = counter.program_id;
+    assert_eq!(
+        counter.process_with(redeem(&voucher), &mut accounts),
+        rejected_with(CounterError::VoucherRedeemed)
+    );
+    assert_eq!(counter.counter().count, 13);
+}
+
+#[test]
+fn only_vouchers_of_the_authority_redeem() {
+    let _turn = setup();
+    let mut counter = at(3);
+    let voucher = voucher(&counter, 10, 1);
+    let mut accounts = redeeming(&counter, &voucher, &Pubkey::new_unique());
+    assert_eq!(
+        counter.process_with(redeem(&voucher), &mut accounts),
+        rejected_with(CounterError::VoucherNotApproved)
+    );
+    //Signed by the authority, yet for another amount
+    let mut accounts = redeeming(&counter, &voucher, &counter.authority);
+    let inflated = Voucher {
+        amount: 100,
+        ..voucher
+    };
+    assert_eq!(
+        counter.process_with(redeem(&inflated), &mut accounts),
+        rejected_with(CounterError::VoucherNotApproved)
+    );
+    assert_eq!(counter.counter().count, 3);
+}
+
+#[test]
+fn expired_vouchers_do_not_redeem() {
+    let _turn = setup();
+    warp(5, EXPIRY + 1);
+    let mut counter = at(3);
+    let voucher = voucher(&counter, 10, 1);
+    let mut accounts = redeeming(&counter, &voucher, &counter.authority);
+    assert_eq!(
+        counter.process_with(redeem(&voucher), &mut accounts),
+        rejected_with(CounterError::VoucherExpired)
+    );
+    accounts[0].is_signer = false;
+    assert_eq!(
+        counter.process_with(redeem(&voucher), &mut accounts),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(counter.counter().count, 3);
+}
+
+//Redeemed vouchers go through the rate limit and the token gate like increaments, and
+//are tracked as contributions of the redeemer
+#[test]
+fn vouchers_redeem_within_the_rate_limit_and_token_gate() {
+    let _turn = setup();
+    warp(5, 0);
+    let mint = Pubkey::new_unique();
+    let mut counter = TestCounter::new(Counter {
+        rate_limit: RateLimit {
+            unit: RateUnit::Slots,
+            min_interval: 5,
+            ..RateLimit::default()
+        },
+        token_gate: TokenGate {
+            mint,
+            min_balance: 500,
+        },
+        ..Counter::new(Pubkey::new_unique())
+    });
+    let first = voucher(&counter, 10, 1);
+    let mut accounts = redeeming_as_holder(&counter, &first, 499);
+    assert_eq!(
+        counter.process_with(redeem(&first), &mut accounts),
+        rejected_with(CounterError::TokenHoldingRequired)
+    );
+    let mut accounts = redeeming_as_holder(&counter, &first, 500);
+    assert_eq!(counter.process_with(redeem(&first), &mut accounts), Ok(()));
+    assert_eq!(counter.counter().count, 10);
+    assert_eq!(counter.counter().last_update_slot, 5);
+    let contribution = Contribution::unpack(&accounts[5].data).unwrap();
+    assert_eq!(contribution.contributor, accounts[0].key);
+    assert_eq!(contribution.total, 10);
+    //Another voucher - too soon after the first one
+    let second = voucher(&counter, 10, 2);
+    let mut accounts = redeeming_as_holder(&counter, &second, 500);
+    assert_eq!(
+        counter.process_with(redeem(&second), &mut accounts),
+        rejected_with(CounterError::IncreamentTooSoon)
+    );
+    warp(10, 0);
+    assert_eq!(counter.process_with(redeem(&second), &mut accounts), Ok(()));
+    assert_eq!(counter.counter().count, 20);
+}